@@ -29,4 +29,16 @@ fn main() {
         out_dir.display()
     );
     fs::write(generated_file_path, content).expect("Failed to write generated settings path file");
+
+    // Embed the current commit so it can be shown in the About dialog
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit);
 }
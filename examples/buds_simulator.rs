@@ -0,0 +1,300 @@
+//! Simulates a pair of Galaxy Buds on the other end of the TCP bridge, so the app can be
+//! run without hardware, e.g. for screenshots or to try out UI changes.
+//!
+//! Start it with an address to listen on (defaults to `127.0.0.1:7777`):
+//!
+//! ```sh
+//! cargo run --example buds_simulator -- 127.0.0.1:7777
+//! ```
+//!
+//! Then point the app at it and select any paired device:
+//!
+//! ```sh
+//! gsettings set com.github.rodrigost23.GalaxyBudsGui debug-tcp-bridge 127.0.0.1:7777
+//! gsettings set com.github.rodrigost23.GalaxyBudsGui experimental-features "['tcp-bridge']"
+//! ```
+//!
+//! The simulator answers the handshake with an extended status update, follows noise
+//! control and find requests, acknowledges every request and slowly drains the batteries.
+
+use std::time::Duration;
+
+use galaxy_buds_rs::message::ids;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, info, warn};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7777";
+const STATUS_INTERVAL: Duration = Duration::from_secs(10);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Request ids the simulator handles that `galaxy_buds_rs::message::ids` doesn't name.
+mod request_ids {
+    pub const ACKNOWLEDGEMENT: u8 = 66;
+    pub const SET_NOISE_CONTROLS_WITH_ONE_EARBUD: u8 = 111;
+    pub const SET_AMBIENT_MODE: u8 = 128;
+    pub const MANAGER_INFO: u8 = 136;
+    pub const SET_SIDETONE: u8 = 139;
+    pub const SET_NOISE_REDUCTION: u8 = 152;
+    pub const FIND_MY_EARBUDS_START: u8 = 160;
+    pub const FIND_MY_EARBUDS_STOP: u8 = 161;
+    pub const KEEP_ALIVE: u8 = 242;
+}
+
+const SOM: u8 = 0xFD;
+const EOM: u8 = 0xDD;
+
+/// Noise control modes, numbered as in noise controls updates.
+#[derive(Debug, Clone, Copy)]
+enum NoiseControl {
+    Off = 0,
+    NoiseReduction = 1,
+    AmbientSound = 2,
+}
+
+/// The state of the simulated buds.
+#[derive(Debug)]
+struct Buds {
+    battery_left: u8,
+    battery_right: u8,
+    battery_case: u8,
+    noise_control: NoiseControl,
+    finding: bool,
+}
+
+impl Default for Buds {
+    fn default() -> Self {
+        Self {
+            battery_left: 92,
+            battery_right: 87,
+            battery_case: 64,
+            noise_control: NoiseControl::Off,
+            finding: false,
+        }
+    }
+}
+
+impl Buds {
+    /// Battery levels and placement, in the layout of a status update.
+    fn status_update(&self) -> Vec<u8> {
+        encode_frame(
+            ids::STATUS_UPDATED,
+            &[
+                self.battery_left,
+                self.battery_right,
+                1,    // Coupled
+                0,    // Main connection: left
+                0x11, // Placement: both worn
+                self.battery_case,
+            ],
+        )
+    }
+
+    /// The full device state, in the layout of a Buds Live extended status update.
+    fn extended_status_update(&self) -> Vec<u8> {
+        let mut payload = vec![
+            3, // Revision
+            0, // Ear type
+            self.battery_left,
+            self.battery_right,
+            1,    // Coupled
+            0,    // Main connection: left
+            0x11, // Placement: both worn
+            self.battery_case,
+            0,    // Adjust sound sync
+            0,    // Equalizer
+            0,    // Touchpad lock
+            0x22, // Touchpad options: volume on both sides
+            matches!(self.noise_control, NoiseControl::NoiseReduction) as u8,
+            0, // Voice wake-up
+            0, // Color
+            0,
+        ];
+        // The remaining fields are feature toggles, all off.
+        payload.resize(32, 0);
+        encode_frame(ids::EXTENDED_STATUS_UPDATED, &payload)
+    }
+
+    fn noise_controls_update(&self) -> Vec<u8> {
+        encode_frame(ids::NOISE_CONTROLS_UPDATE, &[self.noise_control as u8])
+    }
+
+    /// Lowers the bud batteries a little, as if they were playing audio.
+    fn drain(&mut self) {
+        self.battery_left = self.battery_left.saturating_sub(1).max(5);
+        self.battery_right = self.battery_right.saturating_sub(1).max(5);
+    }
+
+    /// Applies a request from the app and returns the frames to reply with.
+    fn handle_request(&mut self, id: u8, payload: &[u8]) -> Vec<Vec<u8>> {
+        let enabled = payload.first().is_some_and(|value| *value != 0);
+        let mut replies = Vec::new();
+        match id {
+            request_ids::MANAGER_INFO => {
+                info!("Handshake received");
+                replies.push(self.extended_status_update());
+            }
+            request_ids::SET_NOISE_REDUCTION => {
+                self.noise_control = if enabled {
+                    NoiseControl::NoiseReduction
+                } else {
+                    NoiseControl::Off
+                };
+                info!("Noise control: {:?}", self.noise_control);
+                replies.push(self.noise_controls_update());
+            }
+            request_ids::SET_AMBIENT_MODE => {
+                self.noise_control = if enabled {
+                    NoiseControl::AmbientSound
+                } else {
+                    NoiseControl::Off
+                };
+                info!("Noise control: {:?}", self.noise_control);
+                replies.push(self.noise_controls_update());
+            }
+            request_ids::FIND_MY_EARBUDS_START | request_ids::FIND_MY_EARBUDS_STOP => {
+                self.finding = id == request_ids::FIND_MY_EARBUDS_START;
+                info!(finding = self.finding, "Find my buds");
+            }
+            request_ids::SET_NOISE_CONTROLS_WITH_ONE_EARBUD | request_ids::SET_SIDETONE => {
+                info!(id, enabled, "Setting changed");
+            }
+            _ => debug!(id, "Unhandled request"),
+        }
+        replies.push(encode_frame(request_ids::ACKNOWLEDGEMENT, &[id]));
+        replies
+    }
+}
+
+/// Builds a frame with the framing of the Buds+ and later models.
+fn encode_frame(id: u8, payload: &[u8]) -> Vec<u8> {
+    // The length covers the id, the payload and the CRC.
+    let length = (payload.len() + 3) as u16;
+    let mut body = Vec::with_capacity(payload.len() + 1);
+    body.push(id);
+    body.extend_from_slice(payload);
+    let crc = crc16(&body);
+
+    let mut frame = vec![SOM, (length & 0xFF) as u8, ((length >> 8) & 0x03) as u8];
+    frame.extend_from_slice(&body);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame.push(EOM);
+    frame
+}
+
+/// CRC-16/XMODEM, as used by the Buds protocol.
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// Takes the complete frames off the front of `buffer`, as `(id, payload)` pairs.
+///
+/// Garbage before a start of message is dropped, an incomplete frame is left in place.
+fn take_frames(buffer: &mut Vec<u8>) -> Vec<(u8, Vec<u8>)> {
+    let mut frames = Vec::new();
+    loop {
+        match buffer.iter().position(|&b| b == SOM) {
+            Some(start) => {
+                buffer.drain(..start);
+            }
+            None => {
+                buffer.clear();
+                break;
+            }
+        }
+        if buffer.len() < 3 {
+            break;
+        }
+        let length = (buffer[1] as usize) | ((buffer[2] as usize & 0x03) << 8);
+        // Start of message, two length bytes, then the id, payload and CRC, then end of message.
+        let frame_len = length + 4;
+        if length < 3 {
+            buffer.remove(0);
+            continue;
+        }
+        if buffer.len() < frame_len {
+            break;
+        }
+        if buffer[frame_len - 1] != EOM {
+            warn!("Dropping malformed frame");
+            buffer.remove(0);
+            continue;
+        }
+        let frame: Vec<u8> = buffer.drain(..frame_len).collect();
+        frames.push((frame[3], frame[4..frame_len - 3].to_vec()));
+    }
+    frames
+}
+
+async fn serve(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut buds = Buds::default();
+    let mut buffer = Vec::new();
+    let mut read_buf = [0u8; 1024];
+    let mut status_timer = tokio::time::interval(STATUS_INTERVAL);
+    let mut keep_alive_timer = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+    // Both fire right away otherwise, before the app has even sent its handshake.
+    status_timer.reset();
+    keep_alive_timer.reset();
+
+    loop {
+        tokio::select! {
+            read = stream.read(&mut read_buf) => {
+                let read = read?;
+                if read == 0 {
+                    return Ok(());
+                }
+                buffer.extend_from_slice(&read_buf[..read]);
+                for (id, payload) in take_frames(&mut buffer) {
+                    for reply in buds.handle_request(id, &payload) {
+                        stream.write_all(&reply).await?;
+                    }
+                }
+            }
+            _ = status_timer.tick() => {
+                buds.drain();
+                debug!(left = buds.battery_left, right = buds.battery_right, "Sending status update");
+                stream.write_all(&buds.status_update()).await?;
+            }
+            _ = keep_alive_timer.tick() => {
+                stream.write_all(&encode_frame(request_ids::KEEP_ALIVE, &[])).await?;
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "buds_simulator=debug".into()),
+        )
+        .init();
+
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let listener = TcpListener::bind(&address).await?;
+    info!("Simulated buds listening on {}", address);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        info!("App connected from {}", peer);
+        tokio::spawn(async move {
+            match serve(stream).await {
+                Ok(()) => info!("App disconnected"),
+                Err(e) => warn!("Connection to the app failed: {}", e),
+            }
+        });
+    }
+}
@@ -0,0 +1,235 @@
+use adw::prelude::AdwDialogExt;
+use galaxy_buds_rs::message::bud_property::NoiseControlMode;
+use gtk4::prelude::{GtkApplicationExt, GtkWindowExt};
+use relm4::{
+    ComponentSender,
+    actions::{ActionName, RelmAction, RelmActionGroup},
+};
+
+use crate::{
+    app::main::{AppInput, AppModel},
+    consts::{APP_ID, ISSUES_URL},
+};
+
+relm4::new_action_group!(pub AppActionGroup, "app");
+relm4::new_stateless_action!(pub AboutAction, AppActionGroup, "about");
+relm4::new_stateless_action!(pub PreferencesAction, AppActionGroup, "preferences");
+relm4::new_stateless_action!(pub ShortcutsAction, AppActionGroup, "shortcuts");
+relm4::new_stateless_action!(pub QuitAction, AppActionGroup, "quit");
+relm4::new_stateless_action!(pub DebugAction, AppActionGroup, "debug");
+relm4::new_stateless_action!(pub ConsoleAction, AppActionGroup, "console");
+relm4::new_stateless_action!(pub CommandPaletteAction, AppActionGroup, "command-palette");
+relm4::new_stateless_action!(pub ImportClientAction, AppActionGroup, "import-client");
+relm4::new_stateless_action!(pub ToggleAncAction, AppActionGroup, "toggle-anc");
+relm4::new_stateless_action!(pub SwitchToAncAction, AppActionGroup, "switch-to-anc");
+relm4::new_stateless_action!(pub FindBudsAction, AppActionGroup, "find-buds");
+
+/// Opens the page of the device whose address is the target, e.g. from a notification.
+pub struct OpenDeviceAction;
+
+impl ActionName for OpenDeviceAction {
+    type Group = AppActionGroup;
+    type Target = String;
+    type State = ();
+
+    const NAME: &'static str = "open-device";
+}
+
+const SHORTCUTS_UI: &str = r#"
+<interface>
+  <object class="GtkShortcutsWindow" id="shortcuts_window">
+    <property name="modal">True</property>
+    <child>
+      <object class="GtkShortcutsSection">
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">General</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Preferences</property>
+                <property name="action-name">app.preferences</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Command palette</property>
+                <property name="action-name">app.command-palette</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Show keyboard shortcuts</property>
+                <property name="action-name">app.shortcuts</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Close window</property>
+                <property name="action-name">window.close</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Quit</property>
+                <property name="action-name">app.quit</property>
+              </object>
+            </child>
+          </object>
+        </child>
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">Navigation</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Go back</property>
+                <property name="accelerator">Escape &lt;Alt&gt;Left</property>
+              </object>
+            </child>
+          </object>
+        </child>
+      </object>
+    </child>
+  </object>
+</interface>
+"#;
+
+/// Registers the application-wide actions and their keyboard accelerators.
+pub fn register_app_actions(window: &adw::ApplicationWindow, sender: &ComponentSender<AppModel>) {
+    let app = relm4::main_application();
+
+    let about_action: RelmAction<AboutAction> = {
+        let window = window.clone();
+        RelmAction::new_stateless(move |_| about_dialog().present(Some(&window)))
+    };
+    let preferences_action: RelmAction<PreferencesAction> = {
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| sender.input(AppInput::ShowPreferences))
+    };
+    let shortcuts_action: RelmAction<ShortcutsAction> = {
+        let window = window.clone();
+        RelmAction::new_stateless(move |_| {
+            let shortcuts_window: gtk4::ShortcutsWindow = gtk4::Builder::from_string(SHORTCUTS_UI)
+                .object("shortcuts_window")
+                .expect("Shortcuts window is defined in the UI string");
+            shortcuts_window.set_transient_for(Some(&window));
+            shortcuts_window.present();
+        })
+    };
+    let quit_action: RelmAction<QuitAction> = {
+        let window = window.clone();
+        RelmAction::new_stateless(move |_| window.close())
+    };
+    let debug_action: RelmAction<DebugAction> = {
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| sender.input(AppInput::ShowDebugWindow))
+    };
+    // Not in any menu, only reachable through its accelerator.
+    let console_action: RelmAction<ConsoleAction> = {
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| sender.input(AppInput::ShowConsole))
+    };
+    let command_palette_action: RelmAction<CommandPaletteAction> = {
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| sender.input(AppInput::ShowCommandPalette))
+    };
+    let import_client_action: RelmAction<ImportClientAction> = {
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| sender.input(AppInput::ShowImport))
+    };
+    // Triggered by the global shortcut, see `shortcuts`.
+    let toggle_anc_action: RelmAction<ToggleAncAction> = {
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| sender.input(AppInput::ToggleNoiseReduction))
+    };
+    // Buttons of the notifications, see `notifications`.
+    let switch_to_anc_action: RelmAction<SwitchToAncAction> = {
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| {
+            sender.input(AppInput::SetNoiseMode(NoiseControlMode::NoiseReduction))
+        })
+    };
+    let find_buds_action: RelmAction<FindBudsAction> = {
+        let window = window.clone();
+        let sender = sender.clone();
+        RelmAction::new_stateless(move |_| {
+            // The dialog is shown over the window, which may be hidden.
+            window.present();
+            sender.input(AppInput::ShowFindDialog);
+        })
+    };
+    let open_device_action: RelmAction<OpenDeviceAction> = {
+        let window = window.clone();
+        let sender = sender.clone();
+        RelmAction::new_with_target_value(move |_, address: String| {
+            window.present();
+            sender.input(AppInput::OpenDevice(address));
+        })
+    };
+
+    let mut group = RelmActionGroup::<AppActionGroup>::new();
+    group.add_action(about_action);
+    group.add_action(preferences_action);
+    group.add_action(shortcuts_action);
+    group.add_action(quit_action);
+    group.add_action(debug_action);
+    group.add_action(console_action);
+    group.add_action(command_palette_action);
+    group.add_action(import_client_action);
+    group.add_action(toggle_anc_action);
+    group.add_action(switch_to_anc_action);
+    group.add_action(find_buds_action);
+    group.add_action(open_device_action);
+    group.register_for_main_application();
+
+    app.set_accels_for_action("app.preferences", &["<Control>comma"]);
+    app.set_accels_for_action("app.shortcuts", &["<Control>question"]);
+    app.set_accels_for_action("app.command-palette", &["<Control>k"]);
+    app.set_accels_for_action("app.quit", &["<Control>q"]);
+    app.set_accels_for_action("window.close", &["<Control>w"]);
+    app.set_accels_for_action("app.console", &["<Control><Shift>d"]);
+}
+
+/// Builds the primary ("hamburger") menu shown in the header bar of the top-level pages.
+pub fn primary_menu() -> gtk4::gio::Menu {
+    relm4::menu! {
+        primary_menu: {
+            "Preferences" => PreferencesAction,
+            "Import from GalaxyBudsClient…" => ImportClientAction,
+            "Protocol Inspector" => DebugAction,
+            "Keyboard Shortcuts" => ShortcutsAction,
+            "About Galaxy Buds Manager" => AboutAction,
+        }
+    }
+    primary_menu
+}
+
+/// Creates a header bar button that opens the primary menu.
+pub fn primary_menu_button() -> gtk4::MenuButton {
+    gtk4::MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .tooltip_text("Main Menu")
+        .primary(true)
+        .menu_model(&primary_menu())
+        .build()
+}
+
+fn about_dialog() -> adw::AboutDialog {
+    let dialog = adw::AboutDialog::builder()
+        .application_name("Galaxy Buds Manager")
+        .application_icon(APP_ID)
+        .developer_name("rodrigost23")
+        .version(format!(
+            "{} ({})",
+            env!("CARGO_PKG_VERSION"),
+            env!("GIT_COMMIT")
+        ))
+        .website("https://github.com/rodrigost23/galaxy-buds-gui-rs")
+        .issue_url(ISSUES_URL)
+        .license_type(gtk4::License::Gpl30)
+        .build();
+    dialog.add_link(
+        "Galaxy Buds protocol library",
+        "https://github.com/rodrigost23/GalaxyBuds-rs",
+    );
+    dialog
+}
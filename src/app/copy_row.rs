@@ -0,0 +1,29 @@
+use adw::prelude::{ActionRowExt, PreferencesRowExt};
+use gtk4::prelude::{CastNone, StaticType, WidgetExt};
+
+/// Builds a property row that copies its value to the clipboard when clicked.
+///
+/// The copied value is the row's subtitle at the time, so it can be updated later.
+/// Confirms with a toast on the closest `adw::ToastOverlay` above the row, if any.
+pub fn new(title: &str, value: &str) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .subtitle(value)
+        .subtitle_selectable(true)
+        .activatable(true)
+        .tooltip_text("Copy")
+        .css_classes(["property"])
+        .build();
+    row.add_suffix(&gtk4::Image::from_icon_name("edit-copy-symbolic"));
+
+    row.connect_activated(|row| {
+        row.clipboard().set_text(&row.subtitle().unwrap_or_default());
+        let toast_overlay = row
+            .ancestor(adw::ToastOverlay::static_type())
+            .and_downcast::<adw::ToastOverlay>();
+        if let Some(toast_overlay) = toast_overlay {
+            toast_overlay.add_toast(adw::Toast::new(&format!("{} copied", row.title())));
+        }
+    });
+    row
+}
@@ -0,0 +1,199 @@
+use adw::prelude::AdwDialogExt;
+use gtk4::{
+    gio::{self, prelude::ActionGroupExt},
+    prelude::{Cast, EditableExt, IsA, ListBoxRowExt, WidgetExt},
+};
+use relm4::{ComponentParts, ComponentSender, SimpleComponent};
+use tracing::debug;
+
+/// An action that can be run from the command palette.
+#[derive(Debug, Clone)]
+pub struct PaletteEntry {
+    title: String,
+    group: gio::ActionGroup,
+    name: String,
+}
+
+impl PaletteEntry {
+    /// Lists the actions of `group` that can run right now, i.e. enabled and without a parameter.
+    ///
+    /// `prefix` is the name the group is inserted with, e.g. `app`, used to look up titles.
+    pub fn from_group(prefix: &str, group: &impl IsA<gio::ActionGroup>) -> Vec<Self> {
+        let mut entries: Vec<Self> = group
+            .list_actions()
+            .into_iter()
+            .filter(|name| group.is_action_enabled(name))
+            .filter(|name| group.action_parameter_type(name).is_none())
+            .filter_map(|name| {
+                let title = action_title(&format!("{}.{}", prefix, name))?;
+                Some(Self {
+                    title,
+                    group: group.clone().upcast(),
+                    name: name.to_string(),
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.title.cmp(&b.title));
+        entries
+    }
+}
+
+/// The title to show for an action, or `None` to leave it out of the palette.
+///
+/// Actions without a title here still show up, named after the action itself.
+fn action_title(detailed_name: &str) -> Option<String> {
+    let title = match detailed_name {
+        "app.command-palette" => return None,
+        "app.about" => "About Galaxy Buds Manager",
+        "app.debug" => "Protocol Inspector",
+        "app.console" => "Developer Console",
+        "app.shortcuts" => "Keyboard Shortcuts",
+        "manage.forget" => "Forget this Device",
+        "manage.export-history" => "Export Battery History",
+        "manage.noise-off" => "Noise Control: Off",
+        "manage.noise-ambient" => "Noise Control: Ambient Sound",
+        "manage.noise-anc" => "Noise Control: Noise Reduction",
+        "manage.noise-control" => "Open Noise Control",
+        "manage.find" => "Find my Buds",
+        _ => {
+            // e.g. "export-history" becomes "Export history".
+            let name = detailed_name
+                .split_once('.')
+                .map_or(detailed_name, |(_, name)| name);
+            let mut title = name.replace('-', " ");
+            if let Some(first) = title.get_mut(..1) {
+                first.make_ascii_uppercase();
+            }
+            return Some(title);
+        }
+    };
+    Some(title.to_string())
+}
+
+#[derive(Debug)]
+pub struct DialogCommandPalette {
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+    list_box: gtk4::ListBox,
+    search_entry: gtk4::SearchEntry,
+    entries: Vec<PaletteEntry>,
+}
+
+#[derive(Debug)]
+pub enum DialogCommandPaletteInput {
+    Show(Vec<PaletteEntry>),
+    Filter(String),
+    Activate(usize),
+    /// Runs the first entry that matches the search.
+    ActivateFirst,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DialogCommandPalette {
+    type Input = DialogCommandPaletteInput;
+    type Output = ();
+    type Init = adw::ApplicationWindow;
+
+    view! {
+        #[root]
+        adw::Dialog {
+            set_title: "Commands",
+            set_content_width: 400,
+            set_content_height: 360,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    set_show_title: false,
+
+                    #[wrap(Some)]
+                    #[name = "search_entry"]
+                    set_title_widget = &gtk4::SearchEntry {
+                        set_hexpand: true,
+                        set_placeholder_text: Some("Search commands"),
+                        connect_search_changed[sender] => move |entry| {
+                            sender.input(DialogCommandPaletteInput::Filter(entry.text().to_string()));
+                        },
+                        connect_activate => DialogCommandPaletteInput::ActivateFirst,
+                    },
+                },
+
+                #[wrap(Some)]
+                set_content = &gtk4::ScrolledWindow {
+                    set_hscrollbar_policy: gtk4::PolicyType::Never,
+
+                    #[name = "list_box"]
+                    gtk4::ListBox {
+                        add_css_class: "navigation-sidebar",
+                        connect_row_activated[sender] => move |_, row| {
+                            sender.input(DialogCommandPaletteInput::Activate(row.index() as usize));
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        parent: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let widgets = view_output!();
+        let model = DialogCommandPalette {
+            parent,
+            dialog: root.clone(),
+            list_box: widgets.list_box.clone(),
+            search_entry: widgets.search_entry.clone(),
+            entries: Vec::new(),
+        };
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            DialogCommandPaletteInput::Show(entries) => {
+                while let Some(row) = self.list_box.first_child() {
+                    self.list_box.remove(&row);
+                }
+                for entry in &entries {
+                    let row = adw::ActionRow::builder()
+                        .title(entry.title.as_str())
+                        .activatable(true)
+                        .build();
+                    self.list_box.append(&row);
+                }
+                self.entries = entries;
+                self.search_entry.set_text("");
+                self.dialog.present(Some(&self.parent));
+                self.search_entry.grab_focus();
+            }
+            DialogCommandPaletteInput::Filter(query) => {
+                let query = query.to_lowercase();
+                for (index, entry) in self.entries.iter().enumerate() {
+                    if let Some(row) = self.list_box.row_at_index(index as i32) {
+                        row.set_visible(entry.title.to_lowercase().contains(&query));
+                    }
+                }
+            }
+            DialogCommandPaletteInput::Activate(index) => {
+                if let Some(entry) = self.entries.get(index) {
+                    debug!(action = entry.name, "Running command");
+                    self.dialog.close();
+                    entry.group.activate_action(&entry.name, None);
+                }
+            }
+            DialogCommandPaletteInput::ActivateFirst => {
+                let first_visible = (0..self.entries.len()).find(|index| {
+                    self.list_box
+                        .row_at_index(*index as i32)
+                        .is_some_and(|row| row.is_visible())
+                });
+                if let Some(index) = first_visible {
+                    self.update(DialogCommandPaletteInput::Activate(index), sender);
+                }
+            }
+        }
+    }
+}
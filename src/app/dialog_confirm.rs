@@ -0,0 +1,133 @@
+use adw::prelude::{AdwDialogExt, AlertDialogExt};
+use gtk4::prelude::CheckButtonExt;
+use relm4::{Component, ComponentParts, ComponentSender, SimpleComponent};
+
+use crate::{
+    model::confirmation::{ConfirmationToken, Confirmations, DestructiveOperation},
+    sender_ext::SendOrLog,
+};
+
+const CANCEL_RESPONSE: &str = "cancel";
+const CONFIRM_RESPONSE: &str = "confirm";
+
+/// What to ask, for `DialogConfirm::ask`.
+#[derive(Debug, Clone)]
+pub struct DialogConfirmInit {
+    /// Widget the dialog is shown over.
+    pub parent: gtk4::Widget,
+    pub heading: &'static str,
+    pub body: String,
+    /// Label of the button that confirms, e.g. "Forget".
+    pub confirm_label: &'static str,
+    /// Styles the confirm button as destructive rather than suggested.
+    pub is_destructive: bool,
+    /// Label of a check button shown under the body, for an optional extra step.
+    pub option_label: Option<&'static str>,
+}
+
+/// The user's answer when they confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Confirmation {
+    /// Whether the check button of `DialogConfirmInit::option_label` was checked.
+    pub is_option_checked: bool,
+}
+
+/// Asks the user to confirm an action, e.g. before disconnecting or forgetting the device.
+#[derive(Debug)]
+pub struct DialogConfirm {
+    option: Option<gtk4::CheckButton>,
+}
+
+#[derive(Debug)]
+pub enum DialogConfirmInput {
+    Response { is_confirmed: bool },
+}
+
+#[derive(Debug)]
+pub enum DialogConfirmOutput {
+    /// `None` when the dialog was cancelled or closed.
+    Answered(Option<Confirmation>),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DialogConfirm {
+    type Input = DialogConfirmInput;
+    type Output = DialogConfirmOutput;
+    type Init = DialogConfirmInit;
+
+    view! {
+        #[root]
+        adw::AlertDialog {
+            add_response: (CANCEL_RESPONSE, "Cancel"),
+            set_default_response: Some(CANCEL_RESPONSE),
+            set_close_response: CANCEL_RESPONSE,
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let option = init.option_label.map(gtk4::CheckButton::with_label);
+        let model = DialogConfirm {
+            option: option.clone(),
+        };
+        let widgets = view_output!();
+
+        root.set_heading(Some(init.heading));
+        root.set_body(&init.body);
+        root.add_response(CONFIRM_RESPONSE, init.confirm_label);
+        root.set_response_appearance(
+            CONFIRM_RESPONSE,
+            if init.is_destructive {
+                adw::ResponseAppearance::Destructive
+            } else {
+                adw::ResponseAppearance::Suggested
+            },
+        );
+        root.set_extra_child(option.as_ref());
+        root.connect_response(None, move |_, response| {
+            sender.input(DialogConfirmInput::Response {
+                is_confirmed: response == CONFIRM_RESPONSE,
+            });
+        });
+        root.present(Some(&init.parent));
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            DialogConfirmInput::Response { is_confirmed } => {
+                let is_option_checked = self.option.as_ref().is_some_and(CheckButtonExt::is_active);
+                let confirmation = is_confirmed.then_some(Confirmation { is_option_checked });
+                sender
+                    .output_sender()
+                    .send_or_log(DialogConfirmOutput::Answered(confirmation));
+            }
+        }
+    }
+}
+
+impl DialogConfirm {
+    /// Shows the dialog and waits for the answer, which is `None` unless the user confirmed.
+    pub async fn ask(init: DialogConfirmInit) -> Option<Confirmation> {
+        let mut answers = DialogConfirm::builder().launch(init).into_stream();
+        match answers.recv_one().await {
+            Some(DialogConfirmOutput::Answered(confirmation)) => confirmation,
+            None => None,
+        }
+    }
+
+    /// Asks as `ask` does, issuing a token for `operation` from `confirmations` when the user
+    /// confirmed, which the worker asks for before carrying the operation out.
+    pub async fn ask_for(
+        init: DialogConfirmInit,
+        confirmations: &Confirmations,
+        operation: DestructiveOperation,
+    ) -> Option<(Confirmation, ConfirmationToken)> {
+        let confirmation = Self::ask(init).await?;
+        Some((confirmation, confirmations.issue(operation)))
+    }
+}
@@ -1,17 +1,34 @@
 use adw::prelude::{AdwDialogExt, AlertDialogExt};
-use gtk4::prelude::{ButtonExt, ToggleButtonExt, WidgetExt};
+use gtk4::prelude::{ButtonExt, WidgetExt};
 use relm4::{ComponentParts, ComponentSender, SimpleComponent};
 
+use crate::{inhibitor::SuspendInhibitor, sender_ext::SendOrLog};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FindState {
+    Stopped,
+    Started,
+}
+
 #[derive(Debug)]
 pub struct DialogFind {
     parent: adw::ApplicationWindow,
-    is_visible: bool,
+    dialog: adw::AlertDialog,
+    state: FindState,
+    /// Whether a bud was in an ear when the dialog was shown, which calls for a stronger warning.
+    worn: bool,
+    /// Held while the tone plays, so the buds don't keep beeping with the system asleep.
+    inhibitor: Option<SuspendInhibitor>,
 }
 
 #[derive(Debug)]
 pub enum DialogFindInput {
-    Show,
-    Toggle(bool),
+    Show {
+        worn: bool,
+    },
+    Toggle,
+    /// The dialog was closed, either by the user or programmatically.
+    Closed,
 }
 
 #[derive(Debug)]
@@ -27,23 +44,34 @@ impl SimpleComponent for DialogFind {
 
     view! {
         #[root]
-        #[name="root"]
         adw::AlertDialog {
             set_heading: Some("Find my Buds"),
-            set_body: "Your Galaxy Buds will make a loud noise when you press Start.\nMake sure not to be wearing them.",
+            #[watch]
+            set_body: if model.worn {
+                "A bud is in your ear. The tone is loud enough to hurt your hearing.\nTake both buds out before you start it."
+            } else {
+                "Your Galaxy Buds will make a loud noise when you press Start.\nMake sure not to be wearing them."
+            },
             add_response: ("close", "Close"),
             set_close_response: "close",
+            connect_closed => DialogFindInput::Closed,
 
             #[wrap(Some)]
-            #[name="toggle"]
-            set_extra_child = &gtk4::ToggleButton {
-                set_active: false,
-                add_css_class: "suggested-action",
-                connect_toggled[sender] => move |btn| {
-                    sender.input(DialogFindInput::Toggle(btn.is_active()))
+            #[name = "toggle_button"]
+            set_extra_child = &gtk4::Button {
+                connect_clicked => DialogFindInput::Toggle,
+                #[watch]
+                set_label: match model.state {
+                    FindState::Stopped if model.worn => "Start Anyway",
+                    FindState::Stopped => "Start",
+                    FindState::Started => "Stop",
                 },
                 #[watch]
-                set_label: if toggle.is_active() { "Stop" }  else { "Start" },
+                set_css_classes: match model.state {
+                    FindState::Stopped if model.worn => &["destructive-action"],
+                    FindState::Stopped => &["suggested-action"],
+                    FindState::Started => &["destructive-action"],
+                },
             },
         }
     }
@@ -55,7 +83,10 @@ impl SimpleComponent for DialogFind {
     ) -> ComponentParts<Self> {
         let model = DialogFind {
             parent,
-            is_visible: true,
+            dialog: root.clone(),
+            state: FindState::Stopped,
+            worn: false,
+            inhibitor: None,
         };
         let widgets = view_output!();
 
@@ -64,20 +95,81 @@ impl SimpleComponent for DialogFind {
 
     fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
         match message {
-            DialogFindInput::Show => {
-                self.is_visible = true;
+            DialogFindInput::Show { worn } => {
+                self.state = FindState::Stopped;
+                self.worn = worn;
+                self.dialog.present(Some(&self.parent));
             }
-            DialogFindInput::Toggle(active) => {
-                sender.output(DialogFindOutput::Find(active)).unwrap()
+            DialogFindInput::Toggle => {
+                self.state = match self.state {
+                    FindState::Stopped => FindState::Started,
+                    FindState::Started => FindState::Stopped,
+                };
+                self.inhibitor = match self.state {
+                    FindState::Started => {
+                        SuspendInhibitor::new(Some(&self.parent), "Playing the Find my Buds tone")
+                    }
+                    FindState::Stopped => None,
+                };
+                sender
+                    .output_sender()
+                    .send_or_log(DialogFindOutput::Find(self.state == FindState::Started));
+            }
+            DialogFindInput::Closed => {
+                // Don't leave the buds beeping after the dialog is gone.
+                if self.state == FindState::Started {
+                    sender
+                        .output_sender()
+                        .send_or_log(DialogFindOutput::Find(false));
+                }
+                self.state = FindState::Stopped;
+                self.inhibitor = None;
             }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_harness;
+
+    fn launch() -> (
+        relm4::Controller<DialogFind>,
+        test_harness::Outputs<DialogFindOutput>,
+    ) {
+        test_harness::launch::<DialogFind>(adw::ApplicationWindow::builder().build())
+    }
 
-    fn post_view(&self, widgets: &mut Self::Widgets, sender: ComponentSender<Self>) {
-        if self.is_visible {
-            widgets.root.present(Some(&self.parent));
-        } else {
-            widgets.root.close();
-        }
+    #[test]
+    fn offers_to_start_the_tone() {
+        test_harness::run(|| {
+            let (dialog, _) = launch();
+            test_harness::emit(&dialog, DialogFindInput::Show { worn: false });
+            let button = &dialog.widgets().toggle_button;
+            assert_eq!(button.label().as_deref(), Some("Start"));
+            assert!(button.has_css_class("suggested-action"));
+        });
+    }
+
+    #[test]
+    fn warns_before_starting_the_tone_while_worn() {
+        test_harness::run(|| {
+            let (dialog, _) = launch();
+            test_harness::emit(&dialog, DialogFindInput::Show { worn: true });
+            let button = &dialog.widgets().toggle_button;
+            assert_eq!(button.label().as_deref(), Some("Start Anyway"));
+            assert!(button.has_css_class("destructive-action"));
+        });
+    }
+
+    #[test]
+    fn sends_nothing_when_closed_before_starting() {
+        test_harness::run(|| {
+            let (dialog, outputs) = launch();
+            test_harness::emit(&dialog, DialogFindInput::Show { worn: false });
+            test_harness::emit(&dialog, DialogFindInput::Closed);
+            assert!(outputs.borrow().is_empty());
+        });
     }
 }
@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+
+use adw::prelude::{AdwDialogExt, AlertDialogExt};
+use gtk4::{gio::prelude::SettingsExt, prelude::FileExt};
+use relm4::{ComponentParts, ComponentSender, SimpleComponent};
+use tracing::{debug, error};
+
+use crate::{
+    consts::{DEVICE_ADDRESS_KEY, DEVICE_LABEL_KEY},
+    model::client_config::ClientConfig,
+    settings::{self, SettingsWriter},
+};
+
+#[derive(Debug)]
+pub struct DialogImport {
+    parent: adw::ApplicationWindow,
+    dialog: adw::AlertDialog,
+    settings_writer: SettingsWriter,
+    /// The config to import, or the reason it couldn't be read.
+    config: Result<ClientConfig, String>,
+}
+
+#[derive(Debug)]
+pub enum DialogImportInput {
+    ChooseFile,
+    /// Reads the config and shows what importing it would change.
+    Preview(PathBuf),
+    Apply,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DialogImport {
+    type Input = DialogImportInput;
+    type Output = ();
+    type Init = (adw::ApplicationWindow, SettingsWriter);
+
+    view! {
+        #[root]
+        adw::AlertDialog {
+            set_heading: Some("Import from GalaxyBudsClient"),
+            #[watch]
+            set_body: &model.preview_text(),
+            add_response: ("cancel", "Cancel"),
+            add_response: ("import", "Import"),
+            set_response_appearance: ("import", adw::ResponseAppearance::Suggested),
+            #[watch]
+            set_response_enabled: ("import", model.config.as_ref().is_ok_and(|config| !config.devices.is_empty())),
+            set_close_response: "cancel",
+        }
+    }
+
+    fn init(
+        (parent, settings_writer): Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = DialogImport {
+            parent,
+            dialog: root.clone(),
+            settings_writer,
+            config: Ok(ClientConfig::default()),
+        };
+        let widgets = view_output!();
+
+        root.connect_response(Some("import"), move |_, _| {
+            sender.input(DialogImportInput::Apply);
+        });
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            DialogImportInput::ChooseFile => {
+                let filters = gtk4::gio::ListStore::new::<gtk4::FileFilter>();
+                let filter = gtk4::FileFilter::new();
+                filter.set_name(Some("JSON"));
+                filter.add_pattern("*.json");
+                filters.append(&filter);
+                gtk4::FileDialog::builder()
+                    .title("Import GalaxyBudsClient Settings")
+                    .filters(&filters)
+                    .build()
+                    .open(
+                        Some(&self.parent),
+                        gtk4::gio::Cancellable::NONE,
+                        move |result| {
+                            if let Some(path) = result.ok().and_then(|file| file.path()) {
+                                sender.input(DialogImportInput::Preview(path));
+                            }
+                        },
+                    );
+            }
+            DialogImportInput::Preview(path) => {
+                self.config = std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| ClientConfig::parse(&json).map_err(|e| e.to_string()));
+                if let Err(e) = &self.config {
+                    error!("Failed to read {}: {}", path.display(), e);
+                }
+                self.dialog.present(Some(&self.parent));
+            }
+            DialogImportInput::Apply => {
+                let Ok(config) = &self.config else {
+                    return;
+                };
+                for device in &config.devices {
+                    let device_settings = settings::get_device_settings(&device.address);
+                    // Labels set in this app win over the imported names.
+                    if device.name.is_empty()
+                        || !device_settings.string(DEVICE_LABEL_KEY).is_empty()
+                    {
+                        continue;
+                    }
+                    self.settings_writer
+                        .for_device(&device.address)
+                        .set_string(DEVICE_LABEL_KEY, &device.name);
+                }
+
+                if let Some(address) = &config.last_device {
+                    if settings::get_settings()
+                        .string(DEVICE_ADDRESS_KEY)
+                        .is_empty()
+                    {
+                        self.settings_writer.set_string(DEVICE_ADDRESS_KEY, address);
+                    }
+                }
+                debug!(
+                    "Imported {} devices from GalaxyBudsClient",
+                    config.devices.len()
+                );
+            }
+        }
+    }
+}
+
+impl DialogImport {
+    fn preview_text(&self) -> String {
+        let config = match &self.config {
+            Ok(config) => config,
+            Err(e) => {
+                return format!("The file couldn't be read as a GalaxyBudsClient config.\n{e}");
+            }
+        };
+        if config.devices.is_empty() {
+            return "No devices were found in the file.".to_string();
+        }
+
+        let mut text = "These devices will be labeled with the names they had:\n".to_string();
+        for device in &config.devices {
+            let name = if device.name.is_empty() {
+                "No name"
+            } else {
+                &device.name
+            };
+            text.push_str(&format!("\n• {} ({})", name, device.address));
+        }
+        if let Some(address) = &config.last_device {
+            text.push_str(&format!(
+                "\n\nIf no device was picked yet, {address} will be connected to on startup."
+            ));
+        }
+        if !config.unsupported.is_empty() {
+            text.push_str(&format!(
+                "\n\nNot imported, as this app has no such settings yet: {}.",
+                config.unsupported.join(", ")
+            ));
+        }
+        text
+    }
+}
@@ -0,0 +1,171 @@
+use adw::prelude::{AdwDialogExt, ToolbarViewExt};
+use gtk4::prelude::{BoxExt, ButtonExt, OrientableExt, WidgetExt};
+use relm4::{ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use tracing::error;
+
+use crate::{consts::APP_ID, sender_ext::SendOrLog};
+
+/// Number of pages in the onboarding carousel.
+const PAGE_COUNT: u32 = 4;
+
+#[derive(Debug)]
+pub struct DialogOnboarding {
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+    page: u32,
+}
+
+#[derive(Debug)]
+pub enum DialogOnboardingInput {
+    Show,
+    PageChanged(u32),
+    OpenBluetoothSettings,
+    /// The dialog was closed, either by finishing or by dismissing it.
+    Closed,
+}
+
+#[derive(Debug)]
+pub enum DialogOnboardingOutput {
+    Finished,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DialogOnboarding {
+    type Input = DialogOnboardingInput;
+    type Output = DialogOnboardingOutput;
+    type Init = adw::ApplicationWindow;
+
+    view! {
+        #[root]
+        adw::Dialog {
+            set_title: "Welcome",
+            set_content_width: 360,
+            set_content_height: 480,
+            connect_closed => DialogOnboardingInput::Closed,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    set_show_title: false,
+                },
+
+                #[wrap(Some)]
+                set_content = &gtk4::Box {
+                    set_orientation: gtk4::Orientation::Vertical,
+
+                    #[name = "carousel"]
+                    adw::Carousel {
+                        set_vexpand: true,
+                        connect_page_changed[sender] => move |_, index| {
+                            sender.input(DialogOnboardingInput::PageChanged(index));
+                        },
+
+                        adw::StatusPage {
+                            set_hexpand: true,
+                            set_icon_name: Some(APP_ID),
+                            set_title: "Welcome to Galaxy Buds Manager",
+                            set_description: Some("Check the battery and change the settings of your Galaxy Buds."),
+                        },
+                        adw::StatusPage {
+                            set_hexpand: true,
+                            set_icon_name: Some("bluetooth-active-symbolic"),
+                            set_title: "Turn On Bluetooth",
+                            set_description: Some("The buds are reached through the system Bluetooth service, so make sure Bluetooth is turned on."),
+                        },
+                        adw::StatusPage {
+                            set_hexpand: true,
+                            set_icon_name: Some("audio-headphones-symbolic"),
+                            set_title: "Pair Your Buds",
+                            set_description: Some("Put the buds in the case, open it and hold the touchpads until they enter pairing mode. Then pair them in the system settings."),
+
+                            gtk4::Button {
+                                set_label: "Open Bluetooth Settings",
+                                set_halign: gtk4::Align::Center,
+                                add_css_class: "pill",
+                                connect_clicked => DialogOnboardingInput::OpenBluetoothSettings,
+                            },
+                        },
+                        adw::StatusPage {
+                            set_hexpand: true,
+                            set_icon_name: Some("object-select-symbolic"),
+                            set_title: "Pick Your Buds",
+                            set_description: Some("Paired buds show up in the device list. Select yours to connect."),
+                        },
+                    },
+
+                    adw::CarouselIndicatorDots {
+                        set_carousel: Some(&carousel),
+                    },
+                },
+
+                add_bottom_bar = &gtk4::Box {
+                    set_spacing: 6,
+                    set_margin_all: 12,
+                    set_homogeneous: true,
+
+                    gtk4::Button {
+                        set_label: "Back",
+                        #[watch]
+                        set_sensitive: model.page > 0,
+                        connect_clicked[carousel] => move |_| {
+                            let index = (carousel.position() as u32).saturating_sub(1);
+                            carousel.scroll_to(&carousel.nth_page(index), true);
+                        },
+                    },
+                    gtk4::Button {
+                        #[watch]
+                        set_label: if model.is_last_page() { "Get Started" } else { "Next" },
+                        add_css_class: "suggested-action",
+                        connect_clicked[carousel, root] => move |_| {
+                            let index = carousel.position() as u32 + 1;
+                            if index < carousel.n_pages() {
+                                carousel.scroll_to(&carousel.nth_page(index), true);
+                            } else {
+                                root.close();
+                            }
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        parent: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = DialogOnboarding {
+            parent,
+            dialog: root.clone(),
+            page: 0,
+        };
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            DialogOnboardingInput::Show => self.dialog.present(Some(&self.parent)),
+            DialogOnboardingInput::PageChanged(page) => self.page = page,
+            DialogOnboardingInput::OpenBluetoothSettings => {
+                if let Err(e) = std::process::Command::new("gnome-control-center")
+                    .arg("bluetooth")
+                    .spawn()
+                {
+                    error!("Failed to open the Bluetooth settings: {}", e);
+                }
+            }
+            DialogOnboardingInput::Closed => sender
+                .output_sender()
+                .send_or_log(DialogOnboardingOutput::Finished),
+        }
+    }
+}
+
+impl DialogOnboarding {
+    fn is_last_page(&self) -> bool {
+        self.page + 1 >= PAGE_COUNT
+    }
+}
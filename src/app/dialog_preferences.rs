@@ -0,0 +1,260 @@
+use adw::prelude::{
+    ActionRowExt, AdwDialogExt, ComboRowExt, PreferencesDialogExt, PreferencesGroupExt,
+    PreferencesPageExt, PreferencesRowExt, SwitchRowExt,
+};
+use gtk4::{
+    gio::prelude::{SettingsExt, SettingsExtManual},
+    prelude::{ButtonExt, WidgetExt},
+};
+use relm4::{ComponentParts, ComponentSender, SimpleComponent};
+use tracing::error;
+
+use crate::{
+    consts::{
+        ANC_SHORTCUT_KEY, BLOCK_MICROPHONE_KEY, LOG_LEVEL_KEY, LOG_TO_FILE_KEY,
+        NOISE_OFF_IN_CASE_KEY, REDACT_ADDRESSES_KEY, SIDEBAR_LAYOUT_KEY,
+    },
+    features::Feature,
+    logging::{LOG_LEVELS, log_dir},
+    settings::{self, SettingsWriter},
+    shortcuts::{BindOutcome, SHORTCUT_TRIGGERS},
+};
+
+#[derive(Debug)]
+pub struct DialogPreferences {
+    parent: adw::ApplicationWindow,
+    dialog: adw::PreferencesDialog,
+    settings: gtk4::gio::Settings,
+    settings_writer: SettingsWriter,
+    shortcut_outcome: BindOutcome,
+}
+
+#[derive(Debug)]
+pub enum DialogPreferencesInput {
+    Show,
+    SetLogLevel(u32),
+    SetShortcut(u32),
+    /// The portal answered about the shortcut picked in the settings.
+    ShortcutBound(BindOutcome),
+    OpenLogsFolder,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DialogPreferences {
+    type Input = DialogPreferencesInput;
+    type Output = ();
+    type Init = (adw::ApplicationWindow, SettingsWriter);
+
+    view! {
+        #[root]
+        adw::PreferencesDialog {
+            add = &adw::PreferencesPage {
+                add = &adw::PreferencesGroup {
+                    set_title: "Appearance",
+
+                    #[name = "sidebar_layout_row"]
+                    adw::SwitchRow {
+                        set_title: "Sidebar Layout",
+                        set_subtitle: "List the device settings next to the device page on wide windows",
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: "Audio",
+
+                    #[name = "block_microphone_row"]
+                    adw::SwitchRow {
+                        set_title: "Block Microphone",
+                        set_subtitle: "Keep high quality audio by not letting apps use the buds' microphone",
+                    },
+
+                    #[name = "noise_off_in_case_row"]
+                    adw::SwitchRow {
+                        set_title: "Turn Off Noise Control in Case",
+                        set_subtitle: "Save battery when both buds are put in the case",
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: "Shortcuts",
+                    set_description: Some("Work while the window isn't focused, if the desktop allows it"),
+
+                    adw::ComboRow {
+                        set_title: "Toggle Noise Reduction",
+                        #[watch]
+                        set_subtitle: &model.shortcut_status_text(),
+                        set_model: Some(&gtk4::StringList::new(&shortcut_titles)),
+                        set_selected: shortcut_index,
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(DialogPreferencesInput::SetShortcut(row.selected()));
+                        },
+
+                        add_suffix = &gtk4::Image {
+                            set_icon_name: Some("dialog-warning-symbolic"),
+                            set_tooltip_text: Some("The shortcut didn't get its key"),
+                            add_css_class: "warning",
+                            #[watch]
+                            set_visible: matches!(
+                                model.shortcut_outcome,
+                                BindOutcome::Unassigned | BindOutcome::Failed(_)
+                            ),
+                        },
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: "Diagnostics",
+
+                    #[name = "log_to_file_row"]
+                    adw::SwitchRow {
+                        set_title: "Save Logs to File",
+                        set_subtitle: "Useful to attach to bug reports",
+                    },
+
+                    #[name = "redact_addresses_row"]
+                    adw::SwitchRow {
+                        set_title: "Hide Device Addresses and Serial Numbers",
+                        set_subtitle: "Replaced with a hash in logs, crash reports and exports, so they can be shared publicly",
+                    },
+
+                    adw::ComboRow {
+                        set_title: "Log Level",
+                        set_model: Some(&gtk4::StringList::new(LOG_LEVELS)),
+                        set_selected: log_level_index,
+                        connect_selected_notify[sender] => move |row| {
+                            sender.input(DialogPreferencesInput::SetLogLevel(row.selected()));
+                        },
+                    },
+
+                    adw::ActionRow {
+                        set_title: "Logs Folder",
+                        add_suffix = &gtk4::Button {
+                            set_label: "Open",
+                            set_valign: gtk4::Align::Center,
+                            connect_clicked => DialogPreferencesInput::OpenLogsFolder,
+                        },
+                    },
+                },
+
+                // Filled with a row for each experimental feature in `init`.
+                #[name = "developer_group"]
+                add = &adw::PreferencesGroup {
+                    set_title: "Developer",
+                    set_description: Some("Experimental features may be unstable. Changes apply on the next connection."),
+                },
+            },
+        }
+    }
+
+    fn init(
+        (parent, settings_writer): Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let settings = settings::get_settings();
+        let log_level = settings.string(LOG_LEVEL_KEY);
+        let log_level_index = LOG_LEVELS
+            .iter()
+            .position(|level| *level == log_level.as_str())
+            .unwrap_or_default() as u32;
+        let shortcut = settings.string(ANC_SHORTCUT_KEY);
+        let shortcut_index = SHORTCUT_TRIGGERS
+            .iter()
+            .position(|(trigger, _)| *trigger == shortcut.as_str())
+            .unwrap_or_default() as u32;
+        let shortcut_titles: Vec<_> = SHORTCUT_TRIGGERS.iter().map(|(_, title)| *title).collect();
+
+        let model = DialogPreferences {
+            parent,
+            dialog: root.clone(),
+            settings,
+            settings_writer,
+            shortcut_outcome: BindOutcome::Off,
+        };
+        let widgets = view_output!();
+
+        model
+            .settings
+            .bind(SIDEBAR_LAYOUT_KEY, &widgets.sidebar_layout_row, "active")
+            .build();
+        model
+            .settings
+            .bind(
+                BLOCK_MICROPHONE_KEY,
+                &widgets.block_microphone_row,
+                "active",
+            )
+            .build();
+        model
+            .settings
+            .bind(NOISE_OFF_IN_CASE_KEY, &widgets.noise_off_in_case_row, "active")
+            .build();
+        model
+            .settings
+            .bind(LOG_TO_FILE_KEY, &widgets.log_to_file_row, "active")
+            .build();
+        model
+            .settings
+            .bind(REDACT_ADDRESSES_KEY, &widgets.redact_addresses_row, "active")
+            .build();
+
+        for feature in Feature::ALL {
+            let row = adw::SwitchRow::builder()
+                .title(feature.title())
+                .subtitle(feature.description())
+                .active(feature.is_enabled(&model.settings))
+                .build();
+            let settings = model.settings.clone();
+            row.connect_active_notify(move |row| feature.set_enabled(&settings, row.is_active()));
+            widgets.developer_group.add(&row);
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+        match message {
+            DialogPreferencesInput::Show => self.dialog.present(Some(&self.parent)),
+            DialogPreferencesInput::SetLogLevel(index) => {
+                if let Some(level) = LOG_LEVELS.get(index as usize) {
+                    self.settings_writer.set_string(LOG_LEVEL_KEY, level);
+                }
+            }
+            DialogPreferencesInput::SetShortcut(index) => {
+                if let Some((trigger, _)) = SHORTCUT_TRIGGERS.get(index as usize) {
+                    self.settings_writer.set_string(ANC_SHORTCUT_KEY, trigger);
+                }
+            }
+            DialogPreferencesInput::ShortcutBound(outcome) => self.shortcut_outcome = outcome,
+            DialogPreferencesInput::OpenLogsFolder => {
+                let dir = log_dir();
+                if let Err(e) = std::fs::create_dir_all(&dir) {
+                    error!("Failed to create {}: {}", dir.display(), e);
+                    return;
+                }
+                gtk4::FileLauncher::new(Some(&gtk4::gio::File::for_path(&dir))).launch(
+                    Some(&self.parent),
+                    gtk4::gio::Cancellable::NONE,
+                    |result| {
+                        if let Err(e) = result {
+                            error!("Failed to open the logs folder: {}", e);
+                        }
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl DialogPreferences {
+    fn shortcut_status_text(&self) -> String {
+        match &self.shortcut_outcome {
+            BindOutcome::Off => "Not bound".to_string(),
+            BindOutcome::Bound(trigger) => format!("Bound to {trigger}"),
+            BindOutcome::Unassigned => {
+                "Not bound, the key may be taken by the desktop or another app".to_string()
+            }
+            BindOutcome::Failed(reason) => format!("Not bound: {reason}"),
+        }
+    }
+}
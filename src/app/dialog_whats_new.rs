@@ -0,0 +1,100 @@
+use adw::prelude::{AdwDialogExt, PreferencesGroupExt, PreferencesRowExt, ToolbarViewExt};
+use gtk4::prelude::{BoxExt, OrientableExt, WidgetExt};
+use relm4::{ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+
+use crate::{model::changelog::Release, sender_ext::SendOrLog};
+
+#[derive(Debug)]
+pub struct DialogWhatsNew {
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+}
+
+#[derive(Debug)]
+pub enum DialogWhatsNewInput {
+    Show,
+    Closed,
+}
+
+#[derive(Debug)]
+pub enum DialogWhatsNewOutput {
+    /// The dialog was closed, so it shouldn't be shown again for these releases.
+    Seen,
+}
+
+pub struct DialogWhatsNewInit {
+    pub parent: adw::ApplicationWindow,
+    /// Releases to list, newest first.
+    pub releases: Vec<Release>,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DialogWhatsNew {
+    type Input = DialogWhatsNewInput;
+    type Output = DialogWhatsNewOutput;
+    type Init = DialogWhatsNewInit;
+
+    view! {
+        #[root]
+        adw::Dialog {
+            set_title: "What's New",
+            set_content_width: 420,
+            set_content_height: 520,
+            connect_closed => DialogWhatsNewInput::Closed,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+
+                #[wrap(Some)]
+                set_content = &gtk4::ScrolledWindow {
+                    set_hscrollbar_policy: gtk4::PolicyType::Never,
+
+                    #[name = "releases_box"]
+                    gtk4::Box {
+                        set_orientation: gtk4::Orientation::Vertical,
+                        set_spacing: 24,
+                        set_margin_all: 12,
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = DialogWhatsNew {
+            parent: init.parent,
+            dialog: root.clone(),
+        };
+        let widgets = view_output!();
+
+        for release in &init.releases {
+            let group = adw::PreferencesGroup::builder()
+                .title(format!("Version {}", release.version))
+                .build();
+            for feature in &release.features {
+                let row = adw::ActionRow::builder()
+                    .title(feature)
+                    .use_markup(false)
+                    .build();
+                group.add(&row);
+            }
+            widgets.releases_box.append(&group);
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            DialogWhatsNewInput::Show => self.dialog.present(Some(&self.parent)),
+            DialogWhatsNewInput::Closed => sender
+                .output_sender()
+                .send_or_log(DialogWhatsNewOutput::Seen),
+        }
+    }
+}
@@ -1,36 +1,85 @@
 use adw::gio::prelude::SettingsExt;
-use gtk4::gio::prelude::SettingsExtManual;
-use gtk4::prelude::GtkWindowExt;
+use galaxy_buds_rs::message::bud_property::NoiseControlMode;
+use gtk4::gio::prelude::ActionGroupExt;
+use gtk4::prelude::{Cast, GtkWindowExt, ListBoxRowExt, WidgetExt};
 use relm4::{
     Component, ComponentController, ComponentParts, ComponentSender, Controller, SimpleComponent,
-    prelude::{AsyncComponent, AsyncComponentController, AsyncController},
+    prelude::{AsyncComponent, AsyncComponentController},
 };
-use tracing::{debug, debug_span};
+use tracing::{debug, debug_span, error};
 
 use crate::{
     app::{
+        actions::register_app_actions,
+        dialog_command_palette::{DialogCommandPalette, DialogCommandPaletteInput, PaletteEntry},
+        dialog_confirm::{DialogConfirm, DialogConfirmInit},
         dialog_find::{DialogFind, DialogFindInput, DialogFindOutput},
+        dialog_import::{DialogImport, DialogImportInput},
+        dialog_onboarding::{DialogOnboarding, DialogOnboardingInput, DialogOnboardingOutput},
+        dialog_preferences::{DialogPreferences, DialogPreferencesInput},
+        dialog_whats_new::{
+            DialogWhatsNew, DialogWhatsNewInit, DialogWhatsNewInput, DialogWhatsNewOutput,
+        },
         page_connection::{PageConnectionInput, PageConnectionModel, PageConnectionOutput},
-        page_manage::{PageManageInput, PageManageModel, PageManageOutput},
+        page_manage::{PageId, PageManageInit, PageManageInput, PageManageModel, PageManageOutput},
+        router::{Page, Route, RouteChange, Router},
+        window_console::{WindowConsole, WindowConsoleInput, WindowConsoleOutput},
+        window_debug::{WindowDebug, WindowDebugInput},
     },
-    consts::DEVICE_ADDRESS_KEY,
-    define_page_enum,
-    model::device_info::DeviceInfo,
-    settings,
+    connection_manager::{ConnectionManager, SendPolicy},
+    consts::{
+        ANC_SHORTCUT_KEY, DEVICE_ADDRESS_KEY, LAST_SEEN_VERSION_KEY, LOG_LEVEL_KEY, LOG_TO_FILE_KEY,
+        NOTIFY_CASE_OPEN_KEY, ONBOARDING_DONE_KEY, REDACT_ADDRESSES_KEY, SIDEBAR_LAYOUT_KEY,
+    },
+    dbus::{self, BudsProperties, BudsService},
+    lock_ext::LockOrRecover,
+    logging,
+    metrics::SharedMetrics,
+    model::{
+        battery_stats, buds_message::BudsCommand, changelog, device_appearance::DeviceAppearance,
+        device_info::DeviceInfo,
+    },
+    notifications,
+    sender_ext::SendOrLog,
+    session_snapshot::{self, SNAPSHOT_INTERVAL, SessionSnapshot},
+    settings::{self, SettingsDispatcher, SettingsWriter},
+    shortcuts::{self, BindOutcome, GlobalShortcuts},
+    window_state,
 };
 
-define_page_enum!(Page {
-    Connection(AsyncController<PageConnectionModel>),
-    Manage(Controller<PageManageModel>),
-});
+const APP_TITLE: &str = "Galaxy Buds Manager";
 
 #[derive(Debug)]
 pub struct AppModel {
-    active_page: Option<Page>,
+    router: Router,
+    /// Settings of the device being managed, kept so the window title follows its appearance.
+    device_settings: Option<adw::gio::Settings>,
+    window_title: String,
     find_dialog: Controller<DialogFind>,
+    onboarding_dialog: Controller<DialogOnboarding>,
+    whats_new_dialog: Controller<DialogWhatsNew>,
+    preferences_dialog: Controller<DialogPreferences>,
+    import_dialog: Controller<DialogImport>,
+    command_palette: Controller<DialogCommandPalette>,
+    debug_window: Controller<WindowDebug>,
+    console_window: Controller<WindowConsole>,
     settings: adw::gio::Settings,
-    connect_page: AsyncController<PageConnectionModel>,
-    active_subpage: Option<adw::NavigationPage>,
+    settings_writer: SettingsWriter,
+    /// Applies changed preferences right away; kept so its callbacks stay connected.
+    settings_dispatcher: SettingsDispatcher,
+    /// Holds the global shortcuts bound through the portal, `None` when there are none.
+    global_shortcuts: Option<GlobalShortcuts>,
+    /// Kept here rather than in the device page so it survives reconnects.
+    metrics: SharedMetrics,
+    /// Kept here so a device page that's closed and opened again reuses the connection.
+    connections: ConnectionManager,
+    buds_service: Option<BudsService>,
+    /// Whether to show the device settings in a sidebar while a device page is open.
+    sidebar_layout: bool,
+    /// Whether the window is shown and not minimized, so polling is worth it.
+    is_window_visible: bool,
+    /// Session being restored after a crash, until the page of its device is open.
+    restored_session: Option<SessionSnapshot>,
 }
 
 #[derive(Debug)]
@@ -39,7 +88,44 @@ pub enum AppInput {
     Disconnect,
     FromPageManage(PageManageOutput),
     FromDialogFind(DialogFindOutput),
+    FromDialogOnboarding(DialogOnboardingOutput),
+    FromDialogWhatsNew(DialogWhatsNewOutput),
     PagePopped(adw::NavigationPage),
+    /// Navigates to a route, e.g. a device subpage from the sidebar.
+    ///
+    /// Routes below the device page are ignored while no device is open.
+    OpenRoute(Route),
+    /// Opens the page of the device at the address, e.g. from a notification.
+    OpenDevice(String),
+    /// Brings back the session the app was in before it crashed.
+    RestoreSession(SessionSnapshot),
+    /// Writes a snapshot of the session, to restore it if the app crashes.
+    SaveSnapshot,
+    ShowFindDialog,
+    SetSidebarLayout(bool),
+    ShowPreferences,
+    /// Imports the settings of GalaxyBudsClient, the Windows app.
+    ShowImport,
+    ShowCommandPalette,
+    ShowDebugWindow,
+    ShowConsole,
+    FromConsole(WindowConsoleOutput),
+    SetWindowTitle(String),
+    /// The window was shown, hidden or minimized.
+    WindowVisibilityChanged(bool),
+    /// Binds the global shortcuts again, as set in the settings.
+    BindShortcuts,
+    ShortcutsBound(Option<GlobalShortcuts>, BindOutcome),
+    ToggleNoiseReduction,
+    /// Requested from outside the app, e.g. through D-Bus.
+    SetNoiseMode(NoiseControlMode),
+    /// Sets the noise control mode of the last connected device, even if it isn't anymore,
+    /// then answers the D-Bus call that asked for it.
+    SendNoiseMode {
+        mode: NoiseControlMode,
+        policy: SendPolicy,
+        invocation: gtk4::gio::DBusMethodInvocation,
+    },
 }
 
 #[derive(Debug)]
@@ -56,13 +142,59 @@ impl SimpleComponent for AppModel {
     view! {
         #[root]
         adw::ApplicationWindow {
-            set_title: Some("Galaxy Buds Manager"),
+            #[watch]
+            set_title: Some(&model.window_title),
+            // Small enough to fit phones running Phosh.
+            set_width_request: 360,
+            set_height_request: 294,
+
+            // Collapsed, this only shows the content, so it behaves like the plain navigation view.
+            adw::NavigationSplitView {
+                #[watch]
+                set_collapsed: !model.sidebar_layout || model.router.manage_page().is_none(),
+                set_show_content: true,
+
+                #[wrap(Some)]
+                set_sidebar = &adw::NavigationPage {
+                    set_title: "Settings",
+
+                    #[wrap(Some)]
+                    set_child = &adw::ToolbarView {
+                        add_top_bar = &adw::HeaderBar {
+                            set_show_end_title_buttons: false,
+                        },
+
+                        #[wrap(Some)]
+                        set_content = &gtk4::ListBox {
+                            add_css_class: "navigation-sidebar",
+                            set_selection_mode: gtk4::SelectionMode::None,
 
-            #[name = "nav_view"]
-            adw::NavigationView {
-                add: &connect_page_widget,
-                connect_popped[sender] => move |_, page| {
-                    sender.input(AppInput::PagePopped(page.clone()));
+                            adw::ActionRow {
+                                set_title: "Noise Control",
+                                set_activatable: true,
+                                connect_activated => AppInput::OpenRoute(Route::Subpage(PageId::Noise)),
+                            },
+                            adw::ActionRow {
+                                set_title: "Find My Earbuds",
+                                set_activatable: true,
+                                connect_activated => AppInput::ShowFindDialog,
+                            },
+                        },
+                    },
+                },
+
+                #[wrap(Some)]
+                set_content = &adw::NavigationPage {
+                    set_title: APP_TITLE,
+
+                    #[name = "nav_view"]
+                    adw::NavigationView {
+                        set_pop_on_escape: true,
+                        add: &connect_page_widget,
+                        connect_popped[sender] => move |_, page| {
+                            sender.input(AppInput::PagePopped(page.clone()));
+                        },
+                    },
                 },
             },
         }
@@ -74,39 +206,151 @@ impl SimpleComponent for AppModel {
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
         let settings = settings::get_settings();
+        let settings_writer = SettingsWriter::new(settings.clone());
 
-        // -> Add these two lines to bind the window size
-        settings
-            .bind("window-width", &window, "default-width")
-            .flags(gtk4::gio::SettingsBindFlags::DEFAULT)
-            .build();
+        window_state::bind(&window, &settings, &settings_writer);
+
+        register_app_actions(&window, &sender);
+
+        let visibility_changed = {
+            let sender = sender.clone();
+            move |window: &adw::ApplicationWindow| {
+                let is_visible = window.is_visible() && !window.is_suspended();
+                sender.input(AppInput::WindowVisibilityChanged(is_visible));
+            }
+        };
+        window.connect_visible_notify(visibility_changed.clone());
+        window.connect_suspended_notify(visibility_changed);
 
-        settings
-            .bind("window-height", &window, "default-height")
-            .flags(gtk4::gio::SettingsBindFlags::DEFAULT)
-            .build();
+        let mut settings_dispatcher = SettingsDispatcher::new(settings.clone());
+        settings_dispatcher.on_change(&[SIDEBAR_LAYOUT_KEY], {
+            let sender = sender.clone();
+            move |settings| {
+                sender.input(AppInput::SetSidebarLayout(settings.boolean(SIDEBAR_LAYOUT_KEY)));
+            }
+        });
+        settings_dispatcher.on_change(
+            &[LOG_TO_FILE_KEY, LOG_LEVEL_KEY, REDACT_ADDRESSES_KEY],
+            logging::apply_settings,
+        );
+        settings_dispatcher.on_change(&[ANC_SHORTCUT_KEY], {
+            let sender = sender.clone();
+            move |_| sender.input(AppInput::BindShortcuts)
+        });
+        sender.input(AppInput::BindShortcuts);
 
         let find_dialog = DialogFind::builder()
             .launch(window.clone())
             .forward(sender.input_sender(), AppInput::FromDialogFind);
 
-        let connect_page = PageConnectionModel::builder().launch(()).forward(
-            sender.input_sender(),
-            |msg| match msg {
+        let onboarding_dialog = DialogOnboarding::builder()
+            .launch(window.clone())
+            .forward(sender.input_sender(), AppInput::FromDialogOnboarding);
+
+        // Only on the very first launch, not for users who already picked a device.
+        let is_first_launch = !settings.boolean(ONBOARDING_DONE_KEY)
+            && settings.string(DEVICE_ADDRESS_KEY).is_empty();
+        if is_first_launch {
+            onboarding_dialog.emit(DialogOnboardingInput::Show);
+        }
+
+        let releases = changelog::releases_since(&settings.string(LAST_SEEN_VERSION_KEY));
+        let has_news = !releases.is_empty();
+        let whats_new_dialog = DialogWhatsNew::builder()
+            .launch(DialogWhatsNewInit {
+                parent: window.clone(),
+                releases,
+            })
+            .forward(sender.input_sender(), AppInput::FromDialogWhatsNew);
+        if is_first_launch {
+            // Everything is new on the first launch, so there's no point listing it.
+            sender.input(AppInput::FromDialogWhatsNew(DialogWhatsNewOutput::Seen));
+        } else if has_news {
+            whats_new_dialog.emit(DialogWhatsNewInput::Show);
+        }
+
+        let preferences_dialog = DialogPreferences::builder()
+            .launch((window.clone(), settings_writer.clone()))
+            .detach();
+        let import_dialog = DialogImport::builder()
+            .launch((window.clone(), settings_writer.clone()))
+            .detach();
+        let command_palette = DialogCommandPalette::builder()
+            .launch(window.clone())
+            .detach();
+
+        let debug_window = WindowDebug::builder()
+            .transient_for(&window)
+            .launch(())
+            .detach();
+
+        let console_window = WindowConsole::builder()
+            .transient_for(&window)
+            .launch(())
+            .forward(sender.input_sender(), AppInput::FromConsole);
+
+        if let Some(snapshot) = session_snapshot::take_previous() {
+            let parent = window.clone().upcast();
+            let sender = sender.clone();
+            relm4::spawn_local(async move {
+                let init = DialogConfirmInit {
+                    parent,
+                    heading: "Restore Previous Session?",
+                    body: "Galaxy Buds Manager didn't quit properly last time. \
+                           Open the buds it was managing again?"
+                        .to_string(),
+                    confirm_label: "Restore",
+                    is_destructive: false,
+                    option_label: None,
+                };
+                if DialogConfirm::ask(init).await.is_some() {
+                    sender.input(AppInput::RestoreSession(snapshot));
+                }
+            });
+        }
+        gtk4::glib::timeout_add_local(SNAPSHOT_INTERVAL, {
+            let input = sender.input_sender().clone();
+            move || {
+                input.send_or_log(AppInput::SaveSnapshot);
+                gtk4::glib::ControlFlow::Continue
+            }
+        });
+
+        let connect_page = PageConnectionModel::builder()
+            .launch(settings_writer.clone())
+            .forward(sender.input_sender(), |msg| match msg {
                 PageConnectionOutput::SelectDevice(device) => AppInput::SelectDevice(device),
-            },
-        );
+            });
 
         let connect_page_widget = connect_page.widget().clone();
+        let connections = ConnectionManager::new(settings_writer.clone());
 
         let model = AppModel {
-            active_page: None,
-            active_subpage: None,
-            connect_page,
+            router: Router::new(connect_page),
+            device_settings: None,
+            window_title: APP_TITLE.to_string(),
             find_dialog,
+            onboarding_dialog,
+            whats_new_dialog,
+            preferences_dialog,
+            import_dialog,
+            command_palette,
+            debug_window,
+            console_window,
+            sidebar_layout: settings.boolean(SIDEBAR_LAYOUT_KEY),
+            is_window_visible: true,
+            restored_session: None,
             settings,
+            settings_writer,
+            settings_dispatcher,
+            global_shortcuts: None,
+            metrics: SharedMetrics::default(),
+            connections,
+            buds_service: BudsService::export(sender.input_sender().clone()),
         };
 
+        dbus::export_metrics(model.metrics.clone());
+
         let widgets = view_output!();
 
         sender.input(AppInput::Disconnect);
@@ -118,71 +362,351 @@ impl SimpleComponent for AppModel {
         match message {
             AppInput::SelectDevice(device) => {
                 debug!("{:?}", device);
+                let address = device.address.clone();
+                let device_settings = settings::get_device_settings(&device.address);
+                self.window_title = window_title(&device.name, &device_settings);
+                device_settings.connect_changed(None, {
+                    let sender = sender.clone();
+                    let name = device.name.clone();
+                    move |device_settings, _| {
+                        sender.input(AppInput::SetWindowTitle(window_title(&name, device_settings)));
+                    }
+                });
+                self.device_settings = Some(device_settings);
+
                 let page = PageManageModel::builder()
-                    .launch(device)
+                    .launch(PageManageInit {
+                        device,
+                        connections: self.connections.clone(),
+                        settings_writer: self.settings_writer.clone(),
+                    })
                     .forward(sender.input_sender(), AppInput::FromPageManage);
-                self.active_page = Some(Page::Manage(page));
+                if !self.is_window_visible {
+                    page.emit(PageManageInput::SetPolling(false));
+                }
+                let change = self.router.replace(Page::Manage(page));
+                self.route_changed(change);
+                let restored_session = self
+                    .restored_session
+                    .take_if(|snapshot| snapshot.address == address);
+                if let Some(snapshot) = restored_session {
+                    self.queue_noise_modes(&address, snapshot.queued_noise_modes);
+                }
             }
             AppInput::Disconnect => {
-                self.active_page = None;
+                let change = self.router.pop_to_root();
+                self.route_changed(change);
             }
             AppInput::FromPageManage(msg) => match msg {
-                PageManageOutput::OpenFindDialog => self.find_dialog.emit(DialogFindInput::Show),
+                PageManageOutput::OpenFindDialog { worn } => {
+                    self.find_dialog.emit(DialogFindInput::Show { worn });
+                }
                 PageManageOutput::Disconnect => {
-                    let _ = self.settings.set_string(DEVICE_ADDRESS_KEY, "");
+                    self.settings_writer.set_string(DEVICE_ADDRESS_KEY, "");
                     sender.input(AppInput::Disconnect)
                 }
-                PageManageOutput::Navigate(page) => {
-                    self.active_subpage = Some(page);
+                PageManageOutput::Forget(device) => {
+                    self.settings_writer.set_string(DEVICE_ADDRESS_KEY, "");
+                    settings::reset_device_settings(&device.address, &[]);
+                    battery_stats::forget(&device.address);
+                    self.connections.remove(&device.address);
+                    // Also forgets that auto-connect was turned off for it.
+                    self.router.connect_page().emit(PageConnectionInput::SetAutoConnect {
+                        address: device.address.clone(),
+                        enabled: true,
+                    });
+                    sender.input(AppInput::Disconnect)
+                }
+                PageManageOutput::Navigate(page_id, page) => {
+                    let change = self.router.push_subpage(page_id, page);
+                    self.route_changed(change);
+                }
+                PageManageOutput::UnknownFrame { frame, session } => {
+                    self.debug_window
+                        .emit(WindowDebugInput::AddFrame { frame, session });
+                }
+                PageManageOutput::SessionControl(message) => {
+                    self.metrics.lock_or_recover().record_session_control(message);
+                }
+                PageManageOutput::MessageReceived(description) => {
+                    self.console_window
+                        .emit(WindowConsoleInput::Log(description));
+                }
+                PageManageOutput::CaseEvent {
+                    is_open,
+                    battery_summary,
+                } => {
+                    debug!(is_open, "Case event");
+                    if is_open && self.settings.boolean(NOTIFY_CASE_OPEN_KEY) {
+                        notifications::case_opened(&battery_summary);
+                    }
+                }
+                PageManageOutput::LowBattery { address, level } => {
+                    notifications::low_battery(&address, level)
+                }
+                PageManageOutput::ConnectionChanged(is_connected) => {
+                    if is_connected {
+                        self.metrics.lock_or_recover().record_connected();
+                        // Shown in the window otherwise.
+                        if !self.is_window_visible {
+                            notifications::connected(&self.window_title);
+                        }
+                    } else {
+                        self.metrics.lock_or_recover().record_disconnected();
+                        self.update_buds_service(BudsProperties::default());
+                    }
+                }
+                PageManageOutput::StatusChanged(status) => {
+                    self.update_buds_service(BudsProperties::from(&status));
                 }
             },
             AppInput::FromDialogFind(msg) => {
-                if let Some(Page::Manage(page)) = &self.active_page {
+                if let Some(page) = self.router.manage_page() {
                     page.emit(PageManageInput::FindDialogCommand(msg));
                 }
             }
-            AppInput::PagePopped(popped_page) => {
-                if let Some(subpage) = &self.active_subpage {
-                    if popped_page == subpage.clone() {
-                        self.active_subpage = None;
+            AppInput::FromDialogOnboarding(msg) => match msg {
+                DialogOnboardingOutput::Finished => {
+                    self.settings_writer.set_boolean(ONBOARDING_DONE_KEY, true);
+                }
+            },
+            AppInput::FromDialogWhatsNew(msg) => match msg {
+                DialogWhatsNewOutput::Seen => self
+                    .settings_writer
+                    .set_string(LAST_SEEN_VERSION_KEY, env!("CARGO_PKG_VERSION")),
+            },
+            AppInput::SetNoiseMode(mode) => {
+                if let Some(page) = self.router.manage_page() {
+                    page.emit(PageManageInput::SetNoiseMode(mode));
+                }
+            }
+            AppInput::SendNoiseMode {
+                mode,
+                policy,
+                invocation,
+            } => {
+                let Some(address) = self.connections.last_used() else {
+                    invocation.return_dbus_error(
+                        "org.freedesktop.DBus.Error.Failed",
+                        "No device was connected since the app started",
+                    );
+                    return;
+                };
+                let command = BudsCommand::SetNoiseControlMode(mode);
+                match self.connections.send_with_policy(&address, command, policy) {
+                    Ok(()) => {
+                        session_snapshot::record(self.snapshot());
+                        invocation.return_value(None)
+                    }
+                    Err(e) => {
+                        invocation.return_dbus_error("org.freedesktop.DBus.Error.Failed", &e)
                     }
                 }
-
-                if let Some(active_page) = &self.active_page {
-                    if popped_page == active_page.widget().clone() {
-                        self.active_page = None;
+            }
+            AppInput::OpenRoute(route) => match route {
+                Route::Connection => sender.input(AppInput::Disconnect),
+                Route::Manage => {
+                    if let Some((change, popped_page)) = self.router.pop_subpage() {
+                        if let Some(page) = self.router.manage_page() {
+                            page.emit(PageManageInput::SubpagePopped(popped_page));
+                        }
+                        self.route_changed(change);
+                    }
+                }
+                Route::Subpage(page_id) => {
+                    if let Some(page) = self.router.manage_page() {
+                        page.emit(PageManageInput::Navigate(page_id));
+                    }
+                }
+            },
+            AppInput::OpenDevice(address) => {
+                let is_open = self
+                    .router
+                    .manage_page()
+                    .is_some_and(|page| page.model().address() == address);
+                if is_open {
+                    sender.input(AppInput::OpenRoute(Route::Manage));
+                } else {
+                    let connect_page = self.router.connect_page().sender().clone();
+                    relm4::spawn_local(async move {
+                        match DeviceInfo::from_address(&address).await {
+                            Ok(device) => connect_page
+                                .send_or_log(PageConnectionInput::SelectDevice(device)),
+                            Err(e) => error!(%address, "Failed to open the device: {}", e),
+                        }
+                    });
+                }
+            }
+            AppInput::RestoreSession(snapshot) => {
+                let is_open = self
+                    .router
+                    .manage_page()
+                    .is_some_and(|page| page.model().address() == snapshot.address);
+                if is_open {
+                    if let Some(page_id) = snapshot.subpage {
+                        sender.input(AppInput::OpenRoute(Route::Subpage(page_id)));
                     }
+                    self.queue_noise_modes(&snapshot.address, snapshot.queued_noise_modes);
+                } else {
+                    // The device page opens the subpage saved here once connected.
+                    window_state::save_subpage(&self.settings_writer, snapshot.subpage);
+                    sender.input(AppInput::OpenDevice(snapshot.address.clone()));
+                    self.restored_session = Some(snapshot);
+                }
+            }
+            AppInput::SaveSnapshot => {
+                session_snapshot::record(self.snapshot());
+                session_snapshot::save();
+            }
+            AppInput::ShowFindDialog => {
+                if let Some(page) = self.router.manage_page() {
+                    page.emit(PageManageInput::OpenFindDialog);
+                }
+            }
+            AppInput::SetSidebarLayout(sidebar_layout) => self.sidebar_layout = sidebar_layout,
+            AppInput::ShowCommandPalette => {
+                // Listed on every show, so newly added or disabled actions are always up to date.
+                let mut entries = PaletteEntry::from_group("app", &relm4::main_application());
+                if let Some(page) = self.router.manage_page() {
+                    entries.extend(PaletteEntry::from_group(
+                        "manage",
+                        page.model().action_group(),
+                    ));
+                }
+                self.command_palette
+                    .emit(DialogCommandPaletteInput::Show(entries));
+            }
+            AppInput::SetWindowTitle(title) => self.window_title = title,
+            AppInput::WindowVisibilityChanged(is_visible) => {
+                if is_visible == self.is_window_visible {
+                    return;
+                }
+                debug!(is_visible, "Window visibility changed");
+                self.is_window_visible = is_visible;
+                if let Some(page) = self.router.manage_page() {
+                    page.emit(PageManageInput::SetPolling(is_visible));
+                } else if is_visible {
+                    self.router.connect_page().emit(PageConnectionInput::Shown);
+                } else {
+                    // Cancels the rescans, which keep the device list up to date.
+                    self.router.connect_page().emit(PageConnectionInput::Hidden);
+                }
+            }
+            AppInput::BindShortcuts => {
+                // Releases the keys before asking for them again.
+                self.global_shortcuts = None;
+                let trigger = self.settings.string(ANC_SHORTCUT_KEY).to_string();
+                relm4::spawn_local(async move {
+                    let (global_shortcuts, outcome) = shortcuts::bind_toggle_anc(&trigger, || {
+                        relm4::main_application().activate_action("toggle-anc", None);
+                    })
+                    .await;
+                    sender.input(AppInput::ShortcutsBound(global_shortcuts, outcome));
+                });
+            }
+            AppInput::ShortcutsBound(global_shortcuts, outcome) => {
+                self.global_shortcuts = global_shortcuts;
+                self.preferences_dialog.emit(DialogPreferencesInput::ShortcutBound(outcome));
+            }
+            AppInput::ToggleNoiseReduction => {
+                if let Some(page) = self.router.manage_page() {
+                    page.emit(PageManageInput::ToggleNoiseReduction);
+                }
+            }
+            AppInput::ShowPreferences => self.preferences_dialog.emit(DialogPreferencesInput::Show),
+            AppInput::ShowImport => self.import_dialog.emit(DialogImportInput::ChooseFile),
+            AppInput::ShowDebugWindow => self.debug_window.emit(WindowDebugInput::Show),
+            AppInput::ShowConsole => self.console_window.emit(WindowConsoleInput::Show),
+            AppInput::FromConsole(msg) => match msg {
+                WindowConsoleOutput::SendData(data) => match self.router.manage_page() {
+                    Some(page) => page.emit(PageManageInput::SendData(data)),
+                    _ => self
+                        .console_window
+                        .emit(WindowConsoleInput::Log("Not connected".to_string())),
+                },
+            },
+            AppInput::PagePopped(popped_page) => {
+                // `pop_to_page` emits `popped` once per page, so this may run
+                // several times in a row when popping more than one level.
+                if let Some(change) = self.router.popped(&popped_page) {
+                    if let Route::Subpage(_) = change.from {
+                        if let Some(page) = self.router.manage_page() {
+                            page.emit(PageManageInput::SubpagePopped(popped_page));
+                        }
+                    }
+                    self.route_changed(change);
                 }
             }
         }
     }
 
-    fn post_view(&self, widgets: &mut Self::Widgets, sender: ComponentSender<Self>) {
-        match &self.active_page {
-            Some(page_to_push) => {
-                let mut is_visible = false;
-
-                if let Some(visible_page) = widgets.nav_view.visible_page() {
-                    if &visible_page == page_to_push.widget() {
-                        is_visible = true;
-                    }
-                }
+    fn shutdown(&mut self, _widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        self.settings_writer.flush();
+        self.connections.stop_find();
+        session_snapshot::discard();
+    }
 
-                if !is_visible {
-                    widgets.nav_view.push(page_to_push.widget());
-                }
+    fn post_view(&self, widgets: &mut Self::Widgets, _sender: ComponentSender<Self>) {
+        self.router.sync(&widgets.nav_view);
+    }
+}
 
-                if let Some(subpage) = &self.active_subpage {
-                    widgets.nav_view.push(subpage);
-                }
+impl AppModel {
+    /// Reacts to navigating between routes, wherever the navigation came from.
+    fn route_changed(&mut self, change: RouteChange) {
+        debug!(?change, "Route changed");
+        if change.to == Route::Connection {
+            // Also when already there, so the list is scanned again after a disconnect.
+            self.device_settings = None;
+            self.window_title = APP_TITLE.to_string();
+            self.metrics.lock_or_recover().record_disconnected();
+            self.update_buds_service(BudsProperties::default());
+            if self.is_window_visible {
+                self.router.connect_page().emit(PageConnectionInput::Shown);
             }
-            None => {
-                widgets.nav_view.pop_to_page(self.connect_page.widget());
-                self.connect_page
-                    .sender()
-                    .send(PageConnectionInput::LoadDevices)
-                    .unwrap()
+        } else if change.from == Route::Connection {
+            self.router.connect_page().emit(PageConnectionInput::Hidden);
+        }
+        session_snapshot::record(self.snapshot());
+    }
+
+    /// What to restore of this session if the app crashes, `None` while no device is open.
+    fn snapshot(&self) -> Option<SessionSnapshot> {
+        let address = self.router.manage_page()?.model().address().to_string();
+        let subpage = match self.router.route() {
+            Route::Subpage(page_id) => Some(page_id),
+            _ => None,
+        };
+        let queued_noise_modes = self.connections.queued_noise_modes(&address);
+        Some(SessionSnapshot {
+            address,
+            subpage,
+            queued_noise_modes,
+        })
+    }
+
+    /// Queues noise modes restored from a crashed session until the device connects.
+    ///
+    /// The device page connects to it anyway, so there's no need to wake it.
+    fn queue_noise_modes(&self, address: &str, modes: Vec<NoiseControlMode>) {
+        for mode in modes {
+            let command = BudsCommand::SetNoiseControlMode(mode);
+            let policy = SendPolicy::QueueUntilConnected;
+            if let Err(e) = self.connections.send_with_policy(address, command, policy) {
+                error!(%address, "Failed to queue a restored noise mode: {}", e);
             }
         }
     }
+
+    fn update_buds_service(&self, properties: BudsProperties) {
+        if let Some(buds_service) = &self.buds_service {
+            buds_service.update(properties);
+        }
+    }
+}
+
+/// Names the managed device the way the user chose to, next to the app name.
+fn window_title(name: &str, device_settings: &adw::gio::Settings) -> String {
+    let appearance = DeviceAppearance::from_settings(device_settings);
+    format!("{} – {}", appearance.display_name(name), APP_TITLE)
 }
@@ -1,5 +1,20 @@
+pub mod actions;
+pub mod copy_row;
+pub mod dialog_command_palette;
+pub mod dialog_confirm;
 pub mod dialog_find;
+pub mod dialog_import;
+pub mod dialog_onboarding;
+pub mod dialog_preferences;
+pub mod dialog_whats_new;
 pub mod main;
+pub mod page_details;
 pub mod page_connection;
 pub mod page_manage;
 pub mod page_noise;
+pub mod router;
+pub mod status_chip;
+#[cfg(test)]
+mod test_harness;
+pub mod window_console;
+pub mod window_debug;
@@ -1,37 +1,100 @@
+use std::time::{Duration, SystemTime};
+
 use adw::{
     gio::prelude::SettingsExt,
     prelude::{ActionRowExt, NavigationPageExt, PreferencesGroupExt, PreferencesRowExt},
 };
-use bluer::{Device, Session, Uuid};
-use futures::future;
-use gtk4::prelude::{ButtonExt, ListBoxRowExt, WidgetExt};
+use bluer::{DeviceEvent, DeviceProperty};
+use futures::StreamExt;
+use gtk4::prelude::{ButtonExt, ListBoxRowExt, StaticType, ToValue, WidgetExt};
 use relm4::{
     AsyncComponentSender, FactorySender,
-    component::{AsyncComponentParts, SimpleAsyncComponent},
+    component::{AsyncComponent, AsyncComponentParts},
     prelude::{DynamicIndex, FactoryComponent, FactoryVecDeque},
 };
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
+
+use crate::{
+    app::actions::primary_menu_button,
+    backoff::Backoff,
+    bluetooth::discovery::{DiscoveredDevice, Support, discover_galaxy_buds, is_galaxy_buds},
+    connection_manager,
+    consts::{DEVICE_ADDRESS_KEY, DEVICE_PRIORITY_KEY, MANUAL_CONNECT_DEVICES_KEY},
+    model::{device_appearance::DeviceAppearance, device_info::DeviceInfo, util::relative_time},
+    sender_ext::SendOrLog,
+    settings::{self, SettingsWriter},
+};
+
+/// What a device row shows, kept apart from the widgets and BlueZ.
+///
+/// Holds no GTK or BlueZ types, so the title and subtitle can be checked on their own.
+#[derive(Debug, Clone, Copy)]
+struct DeviceRowView<'a> {
+    name: &'a str,
+    appearance: &'a DeviceAppearance,
+    support: Support,
+    is_connected: bool,
+    last_connected: Option<SystemTime>,
+    rssi: Option<i16>,
+}
 
-use crate::{consts::{DEVICE_ADDRESS_KEY, SAMSUNG_SPP_UUID}, model::device_info::DeviceInfo, settings};
+impl DeviceRowView<'_> {
+    fn title(&self) -> String {
+        self.appearance.display_name(self.name)
+    }
+
+    fn subtitle(&self) -> String {
+        if let Support::Unsupported { reason } = self.support {
+            return reason.to_string();
+        }
+        let connected = if self.is_connected {
+            Some("Connected".to_string())
+        } else {
+            self.last_connected
+                .map(|time| format!("Last connected {}", relative_time(time)))
+        };
+        let signal = self.rssi.map(|rssi| format!("Signal {rssi} dBm"));
+        connected
+            .into_iter()
+            .chain(signal)
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
 
 #[derive(Debug)]
 struct DeviceComponent {
-    device: DeviceInfo,
+    device: DiscoveredDevice,
+    /// Whether the device is connected to on startup, when it was the last one used.
+    auto_connect: bool,
+    appearance: DeviceAppearance,
+    /// Signal strength in dBm, only known while BlueZ is discovering.
+    rssi: Option<i16>,
+    last_connected: Option<SystemTime>,
+    /// Keeps the row up to date with the BlueZ device properties.
+    property_watcher: Option<tokio::task::JoinHandle<()>>,
 }
 
 #[derive(Debug)]
 enum DeviceInput {
     Connect,
+    SetAutoConnect(bool),
+    ConnectedChanged(bool),
+    AliasChanged(String),
+    RssiChanged(i16),
 }
 
 #[derive(Debug)]
 enum DeviceOutput {
     Connect(DeviceInfo),
+    SetAutoConnect { address: String, enabled: bool },
+    /// The row at `from` was dropped on this one, at `to`.
+    Move { from: usize, to: usize },
 }
 
 #[relm4::factory]
 impl FactoryComponent for DeviceComponent {
-    type Init = DeviceInfo;
+    type Init = (DiscoveredDevice, bool);
     type Input = DeviceInput;
     type Output = DeviceOutput;
     type CommandOutput = ();
@@ -40,36 +103,206 @@ impl FactoryComponent for DeviceComponent {
     view! {
         #[root]
         adw::ActionRow {
-            set_activatable: true,
+            set_activatable: self.device.is_supported(),
+            // Greyed out, as its manage page couldn't work.
+            set_sensitive: self.device.is_supported(),
             connect_activated => DeviceInput::Connect,
-            set_title: self.device.name.as_str(),
+            #[watch]
+            set_title: &self.view().title(),
+            #[watch]
+            set_subtitle: &self.view().subtitle(),
+
+            add_prefix = &gtk4::Image {
+                set_icon_name: Some("list-drag-handle-symbolic"),
+                set_tooltip_text: Some("Drag to change the order devices are connected in"),
+            },
+            add_prefix = &gtk4::Image {
+                set_icon_name: Some("media-record-symbolic"),
+                set_visible: self.appearance.color_class().is_some(),
+                set_css_classes: self.appearance.color_class().as_slice(),
+            },
+
+            add_suffix = &gtk4::Switch {
+                set_visible: self.device.is_supported(),
+                set_valign: gtk4::Align::Center,
+                set_tooltip_text: Some("Connect automatically on startup"),
+                set_active: self.auto_connect,
+                connect_active_notify[sender] => move |switch| {
+                    sender.input(DeviceInput::SetAutoConnect(switch.is_active()));
+                },
+            },
+
+            // Rows carry their index while dragged, and move to the row they're dropped on.
+            add_controller = gtk4::DragSource {
+                set_actions: gtk4::gdk::DragAction::MOVE,
+                connect_prepare[index] => move |_, _, _| {
+                    let from = index.current_index() as u32;
+                    Some(gtk4::gdk::ContentProvider::for_value(&from.to_value()))
+                },
+            },
+            add_controller = gtk4::DropTarget::new(u32::static_type(), gtk4::gdk::DragAction::MOVE) {
+                connect_drop[sender, index] => move |_, value, _, _| {
+                    let Ok(from) = value.get::<u32>() else {
+                        return false;
+                    };
+                    sender.output_sender().send_or_log(DeviceOutput::Move {
+                        from: from as usize,
+                        to: index.current_index(),
+                    });
+                    true
+                },
+            },
         }
     }
 
-    fn init_model(device: Self::Init, _index: &DynamicIndex, _sender: FactorySender<Self>) -> Self {
-        Self { device }
+    fn init_model(
+        (device, auto_connect): Self::Init,
+        _index: &DynamicIndex,
+        sender: FactorySender<Self>,
+    ) -> Self {
+        let appearance =
+            DeviceAppearance::from_settings(&settings::get_device_settings(&device.info.address));
+        let last_connected = connection_manager::last_connected(&device.info.address);
+        let mut row = Self {
+            device,
+            auto_connect,
+            appearance,
+            rssi: None,
+            last_connected,
+            property_watcher: None,
+        };
+        if row.device.is_supported() {
+            row.watch_properties(&sender);
+        }
+        row
     }
 
     fn update(&mut self, msg: Self::Input, sender: FactorySender<Self>) {
         match msg {
             DeviceInput::Connect => {
-                let _ = sender.output(DeviceOutput::Connect(self.device.clone()));
+                if !self.device.is_supported() {
+                    return;
+                }
+                sender
+                    .output_sender()
+                    .send_or_log(DeviceOutput::Connect(self.device.info.clone()));
             }
+            DeviceInput::SetAutoConnect(enabled) => {
+                self.auto_connect = enabled;
+                sender.output_sender().send_or_log(DeviceOutput::SetAutoConnect {
+                    address: self.device.info.address.clone(),
+                    enabled,
+                });
+            }
+            DeviceInput::ConnectedChanged(is_connected) => {
+                self.device.info.is_connected = is_connected;
+            }
+            DeviceInput::AliasChanged(alias) => self.device.info.alias = Some(alias),
+            DeviceInput::RssiChanged(rssi) => self.rssi = Some(rssi),
+        }
+    }
+
+    fn shutdown(&mut self, _widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        if let Some(property_watcher) = self.property_watcher.take() {
+            property_watcher.abort();
         }
     }
 }
 
+impl DeviceComponent {
+    fn view(&self) -> DeviceRowView<'_> {
+        DeviceRowView {
+            name: self.device.info.alias.as_deref().unwrap_or(&self.device.info.name),
+            appearance: &self.appearance,
+            support: self.device.support,
+            is_connected: self.device.info.is_connected,
+            last_connected: self.last_connected,
+            rssi: self.rssi,
+        }
+    }
+
+    /// Forwards the BlueZ property changes of the device to the row, until it's removed.
+    fn watch_properties(&mut self, sender: &FactorySender<Self>) {
+        let device = self.device.info.device.clone();
+        let input = sender.input_sender().clone();
+        self.property_watcher = Some(relm4::spawn(async move {
+            let events = match device.events().await {
+                Ok(events) => events,
+                Err(e) => {
+                    warn!("Failed to watch the device properties: {}", e);
+                    return;
+                }
+            };
+            let mut events = std::pin::pin!(events);
+            while let Some(event) = events.next().await {
+                // Other events may be added to BlueZ; they don't end the watch.
+                let DeviceEvent::PropertyChanged(property) = event else {
+                    continue;
+                };
+                let message = match property {
+                    DeviceProperty::Connected(is_connected) => {
+                        DeviceInput::ConnectedChanged(is_connected)
+                    }
+                    DeviceProperty::Alias(alias) => DeviceInput::AliasChanged(alias),
+                    DeviceProperty::Rssi(rssi) => DeviceInput::RssiChanged(rssi),
+                    _ => continue,
+                };
+                if input.send(message).is_err() {
+                    break;
+                }
+            }
+        }));
+    }
+}
+
+/// How soon the device list is refreshed while the page is visible. Backs off up to
+/// `RESCAN_MAX_INTERVAL` while the list doesn't change.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+const RESCAN_MAX_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 pub struct PageConnectionModel {
     devices: FactoryVecDeque<DeviceComponent>,
-    settings: adw::gio::Settings,
+    settings_writer: SettingsWriter,
+    /// Addresses of the devices with auto-connect turned off. Kept here, as the settings are
+    /// written with a delay.
+    manual_connect_devices: Vec<String>,
     is_loading: bool,
+    /// Incremented to cancel the running scan and the next rescan, which are then ignored.
+    scan_generation: u64,
+    /// The generation the running scan's result is for, while one runs.
+    running_scan: Option<u64>,
+    rescan_backoff: Backoff,
 }
 
 #[derive(Debug)]
 pub enum PageConnectionInput {
     SelectDevice(DeviceInfo),
+    SetAutoConnect { address: String, enabled: bool },
+    /// Moves the device at `from` in the list to `to`, saving the new order.
+    MoveDevice { from: usize, to: usize },
+    /// Starts a scan, unless one is already running.
     LoadDevices,
+    /// Scans again, unless the page was hidden since the rescan was scheduled.
+    Rescan { generation: u64 },
+    /// The page became visible; scans now and periodically until hidden.
+    Shown,
+    /// The page was covered; cancels the running scan and stops rescanning.
+    Hidden,
+}
+
+#[derive(Debug)]
+pub struct ScanFinished {
+    result: Result<Vec<DiscoveredDevice>, String>,
+}
+
+/// A step turning the listed rows into the discovered devices, in the same order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowChange {
+    Remove(usize),
+    Move { from: usize, to: usize },
+    /// Adds the discovered device at this index, as a row at the same index.
+    Insert(usize),
 }
 
 #[derive(Debug)]
@@ -78,10 +311,11 @@ pub enum PageConnectionOutput {
 }
 
 #[relm4::component(pub async)]
-impl SimpleAsyncComponent for PageConnectionModel {
+impl AsyncComponent for PageConnectionModel {
     type Input = PageConnectionInput;
     type Output = PageConnectionOutput;
-    type Init = ();
+    type Init = SettingsWriter;
+    type CommandOutput = ScanFinished;
 
     view! {
         #[root]
@@ -90,40 +324,46 @@ impl SimpleAsyncComponent for PageConnectionModel {
 
             #[wrap(Some)]
             set_child = &adw::ToolbarView {
-                add_top_bar = &adw::HeaderBar {},
+                add_top_bar = &adw::HeaderBar {
+                    pack_end: &primary_menu_button(),
+                },
                 add_top_bar = &adw::Banner {},
 
                 #[wrap(Some)]
-                set_content = &adw::Clamp {
-
-                    if model.devices.is_empty() {
-                        adw::StatusPage {
-                            set_icon_name: Some("bluetooth-disconnected-symbolic"),
-                            set_title: "No Galaxy Buds detected",
-                            set_description: Some("First you need to pair a Galaxy Buds device in your system settings."),
-
-                            gtk4::Button {
-                                set_label: "Refresh",
-                                #[watch]
-                                set_sensitive: !model.is_loading,
-                                connect_clicked => PageConnectionInput::LoadDevices,
+                #[name = "toast_overlay"]
+                set_content = &adw::ToastOverlay {
+                    #[wrap(Some)]
+                    set_child = &adw::Clamp {
+
+                        if model.devices.is_empty() {
+                            adw::StatusPage {
+                                set_icon_name: Some("bluetooth-disconnected-symbolic"),
+                                set_title: "No Galaxy Buds detected",
+                                set_description: Some("First you need to pair a Galaxy Buds device in your system settings."),
+
+                                gtk4::Button {
+                                    set_label: "Refresh",
+                                    #[watch]
+                                    set_sensitive: !model.is_loading,
+                                    connect_clicked => PageConnectionInput::LoadDevices,
+                                }
                             }
-                        }
-                    } else {
-                        adw::PreferencesPage {
-                            #[local_ref]
-                            devices_group -> adw::PreferencesGroup {
-                                set_title: "Discovered Galaxy Buds",
+                        } else {
+                            adw::PreferencesPage {
+                                #[local_ref]
+                                devices_group -> adw::PreferencesGroup {
+                                    set_title: "Discovered Galaxy Buds",
+                                }
                             }
                         }
-                    }
+                    },
                 }
             },
         }
     }
 
     async fn init(
-        _init: Self::Init,
+        settings_writer: Self::Init,
         root: Self::Root,
         sender: AsyncComponentSender<Self>,
     ) -> AsyncComponentParts<Self> {
@@ -132,112 +372,393 @@ impl SimpleAsyncComponent for PageConnectionModel {
             .launch(adw::PreferencesGroup::default())
             .forward(sender.input_sender(), |output| match output {
                 DeviceOutput::Connect(device) => PageConnectionInput::SelectDevice(device),
+                DeviceOutput::SetAutoConnect { address, enabled } => {
+                    PageConnectionInput::SetAutoConnect { address, enabled }
+                }
+                DeviceOutput::Move { from, to } => PageConnectionInput::MoveDevice { from, to },
             });
 
         let mut model = PageConnectionModel {
             devices,
-            settings: settings.clone(),
+            settings_writer,
+            manual_connect_devices: settings
+                .strv(MANUAL_CONNECT_DEVICES_KEY)
+                .iter()
+                .map(|address| address.to_string())
+                .collect(),
             is_loading: true,
+            scan_generation: 0,
+            running_scan: None,
+            rescan_backoff: Backoff::new(RESCAN_INTERVAL, RESCAN_MAX_INTERVAL),
         };
         let devices_group = model.devices.widget();
         let widgets = view_output!();
 
         // Perform the initial device scan before showing the page.
         match discover_galaxy_buds().await {
-            Ok(discovered_devices) => {
+            Ok(mut discovered_devices) => {
+                sort_by_priority(&mut discovered_devices);
                 let address = settings.string(DEVICE_ADDRESS_KEY).to_string();
 
-                if !address.is_empty() {
+                if !model.is_auto_connect(&address) {
+                    debug!(address = %address, "Auto-connect is off for the saved device.");
+                } else if !address.is_empty() {
                     for device in &discovered_devices {
-                        if device.address().to_string() == address {
+                        if device.info.address == address && device.is_supported() {
                             debug!(address = %address, "Found autoconnect device, sending output.");
-                            let device_info = DeviceInfo::from_device(device.clone()).await;
-                            let _ = sender.output(PageConnectionOutput::SelectDevice(device_info));
+                            sender.output_sender().send_or_log(
+                                PageConnectionOutput::SelectDevice(device.info.clone()),
+                            );
                             return AsyncComponentParts { model, widgets };
                         }
                     }
-                    let _ = settings.set_string(DEVICE_ADDRESS_KEY, "");
-                    debug!("Autoconnect address set, but device not found.");
+                    model.settings_writer.set_string(DEVICE_ADDRESS_KEY, "");
+                    // Otherwise the first buds in the user's order that are connected already.
+                    if let Some(device) = discovered_devices.iter().find(|device| {
+                        device.is_supported()
+                            && device.info.is_connected
+                            && model.is_auto_connect(&device.info.address)
+                    }) {
+                        debug!(address = %device.info.address, "Auto-connecting by priority");
+                        sender
+                            .output_sender()
+                            .send_or_log(PageConnectionOutput::SelectDevice(device.info.clone()));
+                        return AsyncComponentParts { model, widgets };
+                    }
+                    // Connecting to something that isn't a pair of buds would hang the
+                    // manage page, so it was skipped above; tell the user why.
+                    if let Ok(Some(false)) = is_galaxy_buds(&address).await {
+                        warn!(address = %address, "Saved device isn't a pair of Galaxy Buds");
+                        widgets.toast_overlay.add_toast(adw::Toast::new(
+                            "The saved device isn't a pair of Galaxy Buds. Pick one from the list.",
+                        ));
+                    } else {
+                        debug!("Autoconnect address set, but device not found.");
+                    }
                 }
 
                 debug!("Populating list with discovered devices.");
-                model.populate_devices_list(discovered_devices).await;
+                model.populate_devices_list(discovered_devices);
             }
             Err(e) => {
                 error!("Failed to discover devices: {}", e);
+                model.is_loading = false;
             }
         };
 
         AsyncComponentParts { model, widgets }
     }
 
-    async fn update(&mut self, message: Self::Input, sender: AsyncComponentSender<Self>) {
+    async fn update(
+        &mut self,
+        message: Self::Input,
+        sender: AsyncComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
         match message {
             PageConnectionInput::LoadDevices => {
-                debug!("PageConnectionInput::LoadDevices");
                 self.is_loading = true;
-                if let Ok(discovered_devices) = discover_galaxy_buds().await {
-                    self.populate_devices_list(discovered_devices).await;
+                if self.running_scan.is_some() {
+                    // Even if it was cancelled, its result is as fresh as a new scan's.
+                    debug!("Scan already running, not starting another one");
+                    self.running_scan = Some(self.scan_generation);
+                    return;
                 }
+                debug!("PageConnectionInput::LoadDevices");
+                self.running_scan = Some(self.scan_generation);
+                sender.oneshot_command(async move {
+                    ScanFinished {
+                        result: discover_galaxy_buds().await.map_err(|e| e.to_string()),
+                    }
+                });
+            }
+            PageConnectionInput::Rescan { generation } => {
+                if generation == self.scan_generation {
+                    sender.input(PageConnectionInput::LoadDevices);
+                }
+            }
+            PageConnectionInput::Shown => {
+                self.rescan_backoff.reset();
+                sender.input(PageConnectionInput::LoadDevices);
+            }
+            PageConnectionInput::Hidden => {
+                self.scan_generation += 1;
+                self.is_loading = false;
             }
 
-            PageConnectionInput::SelectDevice(device) => {
+            PageConnectionInput::SelectDevice(mut device) => {
                 debug!("Selected device");
-                let _ = self
-                    .settings
-                    .set_string(DEVICE_ADDRESS_KEY, &device.address);
-                let _ = sender.output(PageConnectionOutput::SelectDevice(device));
+                // The list may be a minute old by now.
+                device.refresh().await;
+                self.settings_writer.set_string(DEVICE_ADDRESS_KEY, &device.address);
+                sender
+                    .output_sender()
+                    .send_or_log(PageConnectionOutput::SelectDevice(device));
+            }
+            PageConnectionInput::SetAutoConnect { address, enabled } => {
+                self.manual_connect_devices.retain(|manual| *manual != address);
+                if !enabled {
+                    self.manual_connect_devices.push(address);
+                }
+                self.settings_writer
+                    .set_strv(MANUAL_CONNECT_DEVICES_KEY, &self.manual_connect_devices);
+            }
+            PageConnectionInput::MoveDevice { from, to } => {
+                if from == to || from >= self.devices.len() || to >= self.devices.len() {
+                    return;
+                }
+                self.devices.guard().move_to(from, to);
+                self.save_priorities();
             }
         }
     }
+
+    async fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        sender: AsyncComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        if self.running_scan.take() != Some(self.scan_generation) {
+            debug!("Ignoring the result of a cancelled scan");
+            return;
+        }
+
+        match message.result {
+            Ok(mut discovered_devices) => {
+                sort_by_priority(&mut discovered_devices);
+                // Back to frequent scans while devices come and go.
+                if discovered_devices.len() != self.devices.len() {
+                    self.rescan_backoff.reset();
+                }
+                self.populate_devices_list(discovered_devices);
+            }
+            Err(e) => {
+                error!("Failed to discover devices: {}", e);
+                self.is_loading = false;
+            }
+        }
+
+        // Also cancels a rescan that was already scheduled, e.g. before a manual refresh.
+        self.scan_generation += 1;
+        let generation = self.scan_generation;
+        gtk4::glib::timeout_add_local_once(self.rescan_backoff.next_delay(), move || {
+            sender.input(PageConnectionInput::Rescan { generation });
+        });
+    }
 }
 
 impl PageConnectionModel {
-    /// Clears the existing list and populates it with the given devices.
-    async fn populate_devices_list(&mut self, discovered_devices: Vec<Device>) {
+    fn is_auto_connect(&self, address: &str) -> bool {
+        !self.manual_connect_devices.iter().any(|manual| manual == address)
+    }
+
+    /// Saves the order of the list, which is also the order they are connected to automatically.
+    fn save_priorities(&self) {
+        for (priority, row) in self.devices.iter().enumerate() {
+            self.settings_writer
+                .for_device(&row.device.info.address)
+                .set_int(DEVICE_PRIORITY_KEY, priority as i32);
+        }
+    }
+
+    /// Updates the list to show the given devices, in their order.
+    ///
+    /// Rows of devices still there are kept, with their signal strength and property watcher,
+    /// and so is a row being dragged.
+    fn populate_devices_list(&mut self, discovered_devices: Vec<DiscoveredDevice>) {
+        let listed: Vec<String> = self
+            .devices
+            .iter()
+            .map(|row| row.device.info.address.clone())
+            .collect();
+        let changes = row_changes(
+            &listed.iter().map(String::as_str).collect::<Vec<_>>(),
+            &discovered_devices
+                .iter()
+                .map(|device| device.info.address.as_str())
+                .collect::<Vec<_>>(),
+        );
+        let auto_connect: Vec<_> = discovered_devices
+            .iter()
+            .map(|device| self.is_auto_connect(&device.info.address))
+            .collect();
+        let mut discovered_devices: Vec<_> = discovered_devices.into_iter().map(Some).collect();
+
         let mut guard = self.devices.guard();
-        guard.clear();
-        for device in discovered_devices {
-            guard.push_back(DeviceInfo::from_device(device).await);
+        for change in changes {
+            match change {
+                RowChange::Remove(index) => {
+                    guard.remove(index);
+                }
+                RowChange::Move { from, to } => guard.move_to(from, to),
+                RowChange::Insert(index) => {
+                    if let Some(device) = discovered_devices[index].take() {
+                        guard.insert(index, (device, auto_connect[index]));
+                    }
+                }
+            }
+        }
+        // The kept rows show what was discovered, e.g. whether the device is still supported.
+        for (index, device) in discovered_devices.into_iter().enumerate() {
+            if let (Some(device), Some(row)) = (device, guard.get_mut(index)) {
+                row.device = device;
+            }
         }
         self.is_loading = false;
     }
 }
 
-/// Scans for and returns the devices matching the Galaxy Buds SPP UUID.
-async fn discover_galaxy_buds() -> Result<Vec<Device>, Box<dyn std::error::Error>> {
-    let session = Session::new().await.unwrap();
-    let adapter = session.default_adapter().await.unwrap();
-    adapter.set_powered(true).await?;
+/// The steps turning the rows listing the `listed` addresses into rows for the `discovered`
+/// ones, in order, to be applied one after the other.
+fn row_changes(listed: &[&str], discovered: &[&str]) -> Vec<RowChange> {
+    let mut rows = listed.to_vec();
+    let mut changes = Vec::new();
+    for index in (0..rows.len()).rev() {
+        if !discovered.contains(&rows[index]) {
+            rows.remove(index);
+            changes.push(RowChange::Remove(index));
+        }
+    }
+    for (to, address) in discovered.iter().enumerate() {
+        // The rows before `to` already match.
+        match rows[to..].iter().position(|row| row == address) {
+            Some(0) => {}
+            Some(offset) => {
+                let from = to + offset;
+                let row = rows.remove(from);
+                rows.insert(to, row);
+                changes.push(RowChange::Move { from, to });
+            }
+            None => {
+                rows.insert(to, address);
+                changes.push(RowChange::Insert(to));
+            }
+        }
+    }
+    changes
+}
+
+/// Orders devices as the user arranged them, followed by the ones never moved, as discovered.
+fn sort_by_priority(devices: &mut [DiscoveredDevice]) {
+    devices.sort_by_cached_key(|device| {
+        let priority = settings::get_device_settings(&device.info.address).int(DEVICE_PRIORITY_KEY);
+        (priority < 0, priority)
+    });
+}
 
-    let custom_spp_uuid: Uuid = SAMSUNG_SPP_UUID.parse()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(appearance: &DeviceAppearance) -> DeviceRowView<'_> {
+        DeviceRowView {
+            name: "Galaxy Buds2 Pro",
+            appearance,
+            support: Support::Supported,
+            is_connected: false,
+            last_connected: None,
+            rssi: None,
+        }
+    }
 
-    // Get all known device addresses and create a future to check each one.
-    let device_addrs = adapter.device_addresses().await?;
-    let check_futures = device_addrs
-        .into_iter()
-        .filter_map(|addr| adapter.device(addr).ok())
-        .map(|device| async move {
-            // Check for the specific UUID. If found, return the device.
-            let has_uuid = match device.uuids().await {
-                Ok(Some(uuids)) => uuids.contains(&custom_spp_uuid),
-                _ => false,
-            };
+    #[test]
+    fn titles_the_row_with_the_label_and_emoji() {
+        let appearance = DeviceAppearance::default();
+        assert_eq!(view(&appearance).title(), "Galaxy Buds2 Pro");
 
-            if has_uuid { Some(device) } else { None }
-        });
+        let appearance = DeviceAppearance {
+            label: "Work buds".to_string(),
+            emoji: "💼".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(view(&appearance).title(), "💼 Work buds");
+    }
+
+    #[test]
+    fn says_when_the_device_was_last_connected() {
+        let appearance = DeviceAppearance::default();
+        assert_eq!(view(&appearance).subtitle(), "");
+
+        let two_hours_ago = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        let disconnected = DeviceRowView {
+            last_connected: Some(two_hours_ago),
+            rssi: Some(-60),
+            ..view(&appearance)
+        };
+        assert_eq!(disconnected.subtitle(), "Last connected 2 hours ago · Signal -60 dBm");
+
+        let connected = DeviceRowView {
+            is_connected: true,
+            ..disconnected
+        };
+        assert_eq!(connected.subtitle(), "Connected · Signal -60 dBm");
+    }
+
+    /// Applies `changes` to `listed` the way the list does.
+    fn apply<'a>(
+        listed: &[&'a str],
+        discovered: &[&'a str],
+        changes: &[RowChange],
+    ) -> Vec<&'a str> {
+        let mut rows = listed.to_vec();
+        for change in changes {
+            match *change {
+                RowChange::Remove(index) => {
+                    rows.remove(index);
+                }
+                RowChange::Move { from, to } => {
+                    let row = rows.remove(from);
+                    rows.insert(to, row);
+                }
+                RowChange::Insert(index) => rows.insert(index, discovered[index]),
+            }
+        }
+        rows
+    }
 
-    // Run all checks concurrently and filter out the `None` results.
-    let found_devices: Vec<Device> = future::join_all(check_futures)
-        .await
-        .into_iter()
-        .flatten()
-        .collect();
+    #[test]
+    fn keeps_the_rows_when_nothing_changed() {
+        let listed = ["A", "B", "C"];
+        assert_eq!(row_changes(&listed, &listed), []);
+    }
 
-    // Log the found devices.
-    for device in &found_devices {
-        debug!(device = ?device, "Found device");
+    #[test]
+    fn adds_and_removes_only_the_rows_that_changed() {
+        let listed = ["A", "B", "C"];
+        let discovered = ["A", "C", "D"];
+        let changes = row_changes(&listed, &discovered);
+        assert_eq!(changes, [RowChange::Remove(1), RowChange::Insert(2)]);
+        assert_eq!(apply(&listed, &discovered, &changes), discovered);
     }
 
-    Ok(found_devices)
+    #[test]
+    fn moves_rows_into_the_new_order() {
+        let listed = ["A", "B", "C", "D"];
+        let discovered = ["D", "E", "B", "A", "C"];
+        let changes = row_changes(&listed, &discovered);
+        assert!(!changes.iter().any(|change| matches!(change, RowChange::Remove(_))));
+        assert_eq!(apply(&listed, &discovered, &changes), discovered);
+    }
+
+    #[test]
+    fn fills_an_empty_list() {
+        let discovered = ["A", "B"];
+        let changes = row_changes(&[], &discovered);
+        assert_eq!(changes, [RowChange::Insert(0), RowChange::Insert(1)]);
+        assert_eq!(apply(&[], &discovered, &changes), discovered);
+    }
+
+    #[test]
+    fn explains_why_a_device_is_unsupported() {
+        let appearance = DeviceAppearance::default();
+        let unsupported = DeviceRowView {
+            support: Support::Unsupported {
+                reason: "Not a pair of Galaxy Buds",
+            },
+            is_connected: true,
+            ..view(&appearance)
+        };
+        assert_eq!(unsupported.subtitle(), "Not a pair of Galaxy Buds");
+    }
 }
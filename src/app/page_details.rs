@@ -0,0 +1,225 @@
+use adw::prelude::{
+    ActionRowExt, ComboRowExt, NavigationPageExt, PreferencesGroupExt, PreferencesRowExt,
+};
+use bytes::Bytes;
+use gtk4::{
+    gio::prelude::{SettingsExt, SettingsExtManual},
+    prelude::{ButtonExt, WidgetExt},
+};
+use relm4::{ComponentParts, ComponentSender, SimpleComponent};
+use tracing::error;
+
+use crate::{
+    app::copy_row,
+    consts::{DEVICE_COLOR_KEY, DEVICE_EMOJI_KEY, DEVICE_LABEL_KEY},
+    model::{device_appearance::DEVICE_COLORS, device_info::DeviceInfo, util::to_hex},
+    sender_ext::SendOrLog,
+    settings,
+};
+
+#[derive(Debug)]
+pub struct PageDetailsModel {
+    device: DeviceInfo,
+    device_settings: gtk4::gio::Settings,
+    /// Shows the latest debug data, "N/A" until the device answers the first request.
+    debug_data_row: adw::ActionRow,
+    /// Describes an imbalance between the buds' discharge rates, `None` while there's none.
+    drain_balance_hint: Option<String>,
+    /// Describes the recorded battery history, `None` while it's empty.
+    history_summary: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum PageDetailsInput {
+    DebugDataUpdate(Bytes),
+    DrainBalanceUpdate(Option<String>),
+    HistoryUpdate(Option<String>),
+    SetColor(u32),
+}
+
+#[derive(Debug)]
+pub enum PageDetailsOutput {
+    /// Asks the device for fresh debug data.
+    RefreshDebugData,
+    /// Asks where to export the battery history to.
+    ExportHistory,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for PageDetailsModel {
+    type Input = PageDetailsInput;
+    type Output = PageDetailsOutput;
+    type Init = DeviceInfo;
+
+    view! {
+        #[root]
+        adw::NavigationPage {
+            set_title: "Device Details",
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+
+                #[wrap(Some)]
+                set_content = &adw::ToastOverlay {
+                    #[wrap(Some)]
+                    set_child = &adw::PreferencesPage {
+                        // Filled with copyable rows in `init`.
+                        #[name = "device_group"]
+                        adw::PreferencesGroup {
+                            set_title: "Device",
+                        },
+
+                        adw::PreferencesGroup {
+                            set_title: "Appearance",
+                            set_description: Some("How this device is shown in the device list and the window title"),
+
+                            #[name = "label_row"]
+                            adw::EntryRow {
+                                set_title: "Label",
+                            },
+                            #[name = "emoji_row"]
+                            adw::EntryRow {
+                                set_title: "Emoji",
+                            },
+                            adw::ComboRow {
+                                set_title: "Color",
+                                set_model: Some(&gtk4::StringList::new(&color_titles)),
+                                set_selected: color_index,
+                                connect_selected_notify[sender] => move |row| {
+                                    sender.input(PageDetailsInput::SetColor(row.selected()));
+                                },
+                            },
+                        },
+
+                        adw::PreferencesGroup {
+                            set_title: "Battery Health",
+                            set_description: Some("Uneven battery drain can be a sign of a hardware issue."),
+
+                            #[local_ref]
+                            drain_balance_row -> adw::ActionRow {
+                                #[watch]
+                                set_subtitle: model.drain_balance_hint.as_deref().unwrap_or("No imbalance noticed"),
+                                add_suffix = &gtk4::Image {
+                                    set_icon_name: Some("dialog-warning-symbolic"),
+                                    add_css_class: "warning",
+                                    #[watch]
+                                    set_visible: model.drain_balance_hint.is_some(),
+                                },
+                            },
+                            adw::ActionRow {
+                                set_title: "History",
+                                add_css_class: "property",
+                                #[watch]
+                                set_subtitle: model.history_summary.as_deref().unwrap_or("Nothing recorded yet"),
+                                add_suffix = &gtk4::Button {
+                                    set_icon_name: "document-save-symbolic",
+                                    set_tooltip_text: Some("Export…"),
+                                    set_valign: gtk4::Align::Center,
+                                    add_css_class: "flat",
+                                    #[watch]
+                                    set_sensitive: model.history_summary.is_some(),
+                                    connect_clicked[sender] => move |_| {
+                                        sender
+                                            .output_sender()
+                                            .send_or_log(PageDetailsOutput::ExportHistory);
+                                    },
+                                },
+                            },
+                        },
+
+                        // Filled with a copyable row in `init`.
+                        #[name = "diagnostics_group"]
+                        adw::PreferencesGroup {
+                            set_title: "Diagnostics",
+                            set_description: Some("Raw debug data from the buds, to include in bug reports"),
+                            #[wrap(Some)]
+                            set_header_suffix = &gtk4::Button {
+                                set_icon_name: "view-refresh-symbolic",
+                                set_tooltip_text: Some("Refresh"),
+                                set_valign: gtk4::Align::Center,
+                                add_css_class: "flat",
+                                connect_clicked[sender] => move |_| {
+                                    sender
+                                        .output_sender()
+                                        .send_or_log(PageDetailsOutput::RefreshDebugData);
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        device: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let device_settings = settings::get_device_settings(&device.address);
+        let color = device_settings.string(DEVICE_COLOR_KEY);
+        let color_index = DEVICE_COLORS
+            .iter()
+            .position(|device_color| device_color.id == color.as_str())
+            .unwrap_or_default() as u32;
+        let color_titles: Vec<_> = DEVICE_COLORS
+            .iter()
+            .map(|device_color| device_color.title)
+            .collect();
+
+        let drain_balance_row = copy_row::new("Drain Balance", "");
+        let model = PageDetailsModel {
+            device,
+            device_settings,
+            debug_data_row: copy_row::new("Debug Data", "N/A"),
+            drain_balance_hint: None,
+            history_summary: None,
+        };
+        let widgets = view_output!();
+
+        let device = &model.device;
+        widgets.device_group.add(&copy_row::new("Name", &device.name));
+        widgets.device_group.add(&copy_row::new("Address", &device.address));
+        widgets.device_group.add(&copy_row::new(
+            "Modalias",
+            device.modalias.as_deref().unwrap_or("N/A"),
+        ));
+        widgets.diagnostics_group.add(&model.debug_data_row);
+
+        model
+            .device_settings
+            .bind(DEVICE_LABEL_KEY, &widgets.label_row, "text")
+            .build();
+        model
+            .device_settings
+            .bind(DEVICE_EMOJI_KEY, &widgets.emoji_row, "text")
+            .build();
+
+        sender
+            .output_sender()
+            .send_or_log(PageDetailsOutput::RefreshDebugData);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, _sender: ComponentSender<Self>) {
+        match msg {
+            PageDetailsInput::DebugDataUpdate(data) => {
+                self.debug_data_row.set_subtitle(&to_hex(&data));
+            }
+            PageDetailsInput::DrainBalanceUpdate(hint) => self.drain_balance_hint = hint,
+            PageDetailsInput::HistoryUpdate(summary) => self.history_summary = summary,
+            PageDetailsInput::SetColor(index) => {
+                if let Some(device_color) = DEVICE_COLORS.get(index as usize) {
+                    if let Err(e) = self
+                        .device_settings
+                        .set_string(DEVICE_COLOR_KEY, device_color.id)
+                    {
+                        error!("Failed to save the device color: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
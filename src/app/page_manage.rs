@@ -1,71 +1,406 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
 use adw::prelude::{ActionRowExt, NavigationPageExt, PreferencesRowExt};
-use gtk4::prelude::{BoxExt, ButtonExt, ListBoxRowExt, OrientableExt, WidgetExt};
+use bytes::Bytes;
+use galaxy_buds_rs::{message::bud_property::NoiseControlMode, model::Model};
+use gtk4::gio::prelude::{FileExt, SettingsExt};
+use gtk4::AccessibleAnnouncementPriority;
+use gtk4::prelude::{
+    AccessibleExt, BoxExt, ButtonExt, Cast, CastNone, ListBoxRowExt, OrientableExt, RangeExt,
+    ToggleButtonExt, WidgetExt,
+};
 use relm4::{
     Component, ComponentController, ComponentParts, ComponentSender, Controller, RelmWidgetExt,
-    SimpleComponent, WorkerController,
+    actions::{RelmAction, RelmActionGroup},
 };
 
-use tracing::{debug, error};
+use tracing::{debug, error, info, trace, warn};
 
 use crate::{
     app::{
+        actions::primary_menu_button,
+        dialog_confirm::{DialogConfirm, DialogConfirmInit},
         dialog_find::DialogFindOutput,
-        page_noise::{PageNoiseInput, PageNoiseModel, PageNoiseOutput},
+        page_details::{PageDetailsInput, PageDetailsModel, PageDetailsOutput},
+        page_noise::{PageNoiseInit, PageNoiseInput, PageNoiseModel, PageNoiseOutput},
+        status_chip::{Severity, StatusChip, StatusChipInput, StatusChipState},
     },
-    buds_worker::{BluetoothWorker, BudsWorkerInput, BudsWorkerOutput},
+    audio::{AudioCard, AudioSink},
+    backoff::Backoff,
+    buds_worker::{BudsWorkerInput, BudsWorkerOutput, ConnectStage},
+    connection_manager::{self, ConnectionManager},
+    consts::{BLOCK_MICROPHONE_KEY, ISSUES_URL, NOISE_OFF_IN_CASE_KEY, REDACT_ADDRESSES_KEY},
     define_page_enum,
     model::{
-        buds_message::{BudsCommand, BudsMessage},
+        battery_stats::{BatteryStats, ExportFormat},
+        buds_message::{BudsCommand, BudsMessage, SessionControl},
         buds_status::{BudsStatus, UpdateFrom},
+        capabilities::Capabilities,
+        confirmation::{ConfirmationToken, Confirmations, DestructiveOperation},
+        device_appearance,
         device_info::DeviceInfo,
-        util::OptionNaExt,
+        policy,
+        session_info::SessionInfo,
+        util::{OptionNaExt, relative_time},
     },
+    redact,
+    sender_ext::SendOrLog,
+    settings::{self, SettingsDispatcher, SettingsWriter},
+    telephony::{CallState, ModemCall},
+    throttle::Throttle,
+    window_state,
 };
 
 #[derive(Debug)]
 enum ConnectionState {
     Connected,
     Disconnected,
-    Connecting,
+    Connecting(ConnectStage),
+    /// The RFCOMM stream is open, but the device hasn't identified itself yet.
+    Handshaking,
+    /// The device never answered the handshake with a known message.
+    Unsupported,
+    /// The connection was lost, and the next attempt to reconnect is scheduled.
+    WaitingToReconnect,
+    /// Another app holds the SPP profile. Not retried automatically, as it would fail the same
+    /// way until that app is closed.
+    ProfileInUse,
     Error(String),
 }
 
+/// What the page shows for a connection state and device status, kept apart from the widgets.
+///
+/// Holds no GTK types, so the label text and sensitivity rules can be checked on their own.
+#[derive(Debug, Clone, Copy)]
+struct ManageView<'a> {
+    connection_state: &'a ConnectionState,
+    buds_status: Option<&'a BudsStatus>,
+    /// Noise control mode that was requested but not yet confirmed by the device.
+    pending_noise_mode: Option<NoiseControlMode>,
+    /// Attempts to reconnect since the connection was lost, or 0 if it wasn't.
+    reconnect_attempt: u32,
+}
+
+impl ManageView<'_> {
+    fn is_connected(&self) -> bool {
+        matches!(self.connection_state, ConnectionState::Connected)
+    }
+
+    fn battery_text(&self) -> String {
+        self.buds_status.or_na(|status| status.battery_text())
+    }
+
+    fn case_battery_text(&self) -> String {
+        self.buds_status.or_na(|status| status.case_battery_text())
+    }
+
+    fn is_buds_charging(&self) -> bool {
+        self.buds_status.is_some_and(BudsStatus::is_buds_charging)
+    }
+
+    fn is_case_charging(&self) -> bool {
+        self.buds_status.is_some_and(BudsStatus::is_case_charging)
+    }
+
+    fn is_battery_stale(&self) -> bool {
+        self.buds_status.is_some_and(BudsStatus::is_battery_stale)
+    }
+
+    /// Explains why the battery levels are dimmed, when they are.
+    fn battery_tooltip(&self) -> Option<String> {
+        let updated = self.buds_status?.battery_updated()?;
+        self.is_battery_stale().then(|| {
+            format!("Not updated for {} minutes", updated.elapsed().as_secs() / 60)
+        })
+    }
+
+    fn battery_severity(&self) -> Severity {
+        if self.is_battery_stale() {
+            Severity::Dimmed
+        } else {
+            Severity::Normal
+        }
+    }
+
+    fn buds_chip(&self) -> StatusChipState {
+        StatusChipState {
+            icon_name: "audio-headphones-symbolic",
+            label: self.battery_text(),
+            tooltip: self.battery_tooltip(),
+            severity: self.battery_severity(),
+            is_charging: self.is_buds_charging(),
+            is_visible: true,
+        }
+    }
+
+    fn case_chip(&self) -> StatusChipState {
+        StatusChipState {
+            icon_name: "printer-symbolic",
+            label: self.case_battery_text(),
+            tooltip: self.battery_tooltip(),
+            severity: self.battery_severity(),
+            is_charging: self.is_case_charging(),
+            is_visible: true,
+        }
+    }
+
+    fn wearing_chip(&self) -> StatusChipState {
+        let is_worn = self.buds_status.is_some_and(BudsStatus::is_worn);
+        StatusChipState {
+            icon_name: "avatar-default-symbolic",
+            label: if is_worn { "In Ear" } else { "Not Worn" }.to_string(),
+            is_visible: self.buds_status.is_some(),
+            ..Default::default()
+        }
+    }
+
+    fn missing_bud_chip(&self) -> StatusChipState {
+        let label = self.buds_status.and_then(BudsStatus::missing_bud_text);
+        StatusChipState {
+            icon_name: "dialog-warning-symbolic",
+            is_visible: self.is_connected() && label.is_some(),
+            label: label.unwrap_or_default(),
+            severity: Severity::Warning,
+            ..Default::default()
+        }
+    }
+
+    /// Text under the spinner while connecting.
+    fn connect_stage_text(&self) -> String {
+        let stage = match self.connection_state {
+            ConnectionState::Connecting(stage) => stage.description(),
+            ConnectionState::Handshaking => ConnectStage::Handshake.description(),
+            ConnectionState::WaitingToReconnect => "Connection lost, reconnecting soon...",
+            _ => "Connecting...",
+        };
+        if self.reconnect_attempt > 0 {
+            format!("{} (attempt {})", stage, self.reconnect_attempt)
+        } else {
+            stage.to_string()
+        }
+    }
+
+    /// Taking over only helps when connecting failed, usually because another device has the buds.
+    /// That includes waiting to reconnect, which replaces the error state when reconnecting
+    /// automatically.
+    fn can_take_over(&self) -> bool {
+        matches!(
+            self.connection_state,
+            ConnectionState::Error(_) | ConnectionState::WaitingToReconnect
+        )
+    }
+
+    /// The noise control mode to show as selected, including one that is still pending.
+    fn noise_mode(&self) -> Option<NoiseControlMode> {
+        self.pending_noise_mode
+            .or(self.buds_status.and_then(BudsStatus::noise_control_mode))
+    }
+
+    fn noise_mode_text(&self) -> String {
+        self.buds_status
+            .or_na(|status| status.noise_control_mode_text())
+    }
+
+    /// The noise control buttons are locked until the device confirms the pending mode.
+    fn is_noise_mode_sensitive(&self) -> bool {
+        self.pending_noise_mode.is_none()
+    }
+}
+
 define_page_enum!(PageId, Page {
     Noise(Controller<PageNoiseModel>),
+    Details(Controller<PageDetailsModel>),
 });
 
+/// Width, in sp, below which the page switches to its layout for phones.
+const COMPACT_MAX_WIDTH: f64 = 400.0;
+/// How often to check whether the buds are in a call.
+const CALL_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// How long to wait for the device to confirm a noise control change before assuming it applied.
+const NOISE_MODE_SETTLE_DELAY: Duration = Duration::from_secs(1);
+/// Delay before the first attempt to reconnect, doubled on each failed attempt up to the max.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+/// Shortest time between two connection error toasts, so a flapping connection doesn't
+/// flood the page with them.
+const ERROR_TOAST_INTERVAL: Duration = Duration::from_secs(30);
+
+relm4::new_action_group!(ManageActionGroup, "manage");
+relm4::new_stateless_action!(DisconnectAction, ManageActionGroup, "disconnect");
+relm4::new_stateless_action!(ForgetAction, ManageActionGroup, "forget");
+relm4::new_stateless_action!(ExportHistoryAction, ManageActionGroup, "export-history");
+relm4::new_stateless_action!(NoiseOffAction, ManageActionGroup, "noise-off");
+relm4::new_stateless_action!(NoiseAmbientAction, ManageActionGroup, "noise-ambient");
+relm4::new_stateless_action!(NoiseAncAction, ManageActionGroup, "noise-anc");
+relm4::new_stateless_action!(NoiseControlAction, ManageActionGroup, "noise-control");
+relm4::new_stateless_action!(FindAction, ManageActionGroup, "find");
+
 #[derive(Debug)]
 pub struct PageManageModel {
-    bt_worker: WorkerController<BluetoothWorker>,
+    root: adw::NavigationPage,
+    /// Input of the device's worker, shared through the `ConnectionManager`.
+    bt_worker: relm4::Sender<BudsWorkerInput>,
+    /// Owns the find tone, so it's stopped when the connection drops.
+    connections: ConnectionManager,
+    /// Issues the tokens the worker asks for before taking over or forgetting.
+    confirmations: Confirmations,
     connection_state: ConnectionState,
+    /// Set once the handshake is done, and cleared when disconnected.
+    session: Option<SessionInfo>,
     buds_status: Option<BudsStatus>,
+    battery_stats: BatteryStats,
+    /// Noise control mode that was requested but not yet confirmed by the device.
+    pending_noise_mode: Option<NoiseControlMode>,
     device: DeviceInfo,
-    active_page: Option<Page>,
+    /// When the app was last connected to the device, shown while it isn't.
+    last_connected: Option<SystemTime>,
+    /// Subpages created so far, kept alive so they can be reused on the next navigation.
+    pages: HashMap<PageId, Page>,
+    active_page: Option<PageId>,
+    /// Whether the narrow layout for small screens is in use.
+    is_compact: bool,
+    /// The system audio sink of the device, found after connecting.
+    audio_sink: Option<AudioSink>,
+    /// Volume of `audio_sink`, in percent.
+    volume: u32,
+    /// Whether the hands-free profile is in use, i.e. the buds are in a call.
+    in_call: bool,
+    /// Task polling `in_call`, running while connected.
+    call_monitor: Option<tokio::task::JoinHandle<()>>,
+    /// The call on the phone's modem, if any, which answer and hang up act on.
+    modem_call: Option<ModemCall>,
+    /// Task polling `modem_call`, running while `in_call`.
+    modem_call_monitor: Option<gtk4::glib::JoinHandle<()>>,
+    toast_overlay: adw::ToastOverlay,
+    /// Indicators under the device name, updated after every change of the page.
+    buds_chip: Controller<StatusChip>,
+    case_chip: Controller<StatusChip>,
+    wearing_chip: Controller<StatusChip>,
+    missing_bud_chip: Controller<StatusChip>,
+    action_group: gtk4::gio::SimpleActionGroup,
+    settings: gtk4::gio::Settings,
+    settings_writer: SettingsWriter,
+    /// Kept so its callbacks stay connected for as long as the page exists.
+    settings_dispatcher: SettingsDispatcher,
+    /// Subpage that was open when the app was last closed, reopened after the first status update.
+    restore_page: Option<PageId>,
+    /// Whether to reconnect when the connection drops. Set once connected, and cleared when
+    /// the user disconnects.
+    auto_reconnect: bool,
+    reconnect_backoff: Backoff,
+    /// The timer of the scheduled reconnect, kept to cancel it when the user connects or
+    /// disconnects in the meantime.
+    reconnect_source: Option<gtk4::glib::SourceId>,
+    error_toast_throttle: Throttle,
+    /// Whether a `Render` is queued for the device data received since the last render.
+    is_render_scheduled: bool,
+}
+
+#[derive(Debug)]
+pub struct PageManageInit {
+    pub device: DeviceInfo,
+    pub connections: ConnectionManager,
+    pub settings_writer: SettingsWriter,
 }
 
 #[derive(Debug)]
 pub enum PageManageInput {
     Connect,
+    /// The delay before reconnecting is over.
+    ReconnectDue,
+    ConfirmTakeOver,
+    TakeOver(ConfirmationToken),
+    ConfirmDisconnect,
     Disconnect,
-    BluetoothEvent(BudsWorkerOutput),
+    ConfirmForget,
+    Forget {
+        token: ConfirmationToken,
+        unpair: bool,
+    },
+    ChooseExportFile,
+    ExportBatteryHistory(PathBuf),
+    /// Output of the device's worker, shared with everything else following the device.
+    BluetoothEvent(Arc<BudsWorkerOutput>),
     BluetoothCommand(BudsCommand),
+    SendData(Vec<u8>),
+    /// Pauses polling the device while the window isn't visible (`false`), or resumes it.
+    SetPolling(bool),
+    SetNoiseMode(NoiseControlMode),
+    NoiseModeSettled,
+    SetNoiseControlsWithOneEarbud(bool),
+    SetAmbientSoundDuringCalls(bool),
     OpenFindDialog,
     FindDialogCommand(DialogFindOutput),
     Navigate(PageId),
+    SubpagePopped(adw::NavigationPage),
+    SetCompact(bool),
+    AudioSinkFound(Option<(AudioSink, u32)>),
+    SetVolume(u32),
+    CallStateChanged(bool),
+    ModemCallChanged(Option<ModemCall>),
+    AnswerCall,
+    /// Hangs up the modem call, or declines it while it's incoming.
+    HangUpCall,
+    /// Switches the buds back to high quality audio for this call only.
+    LeaveHandsFree,
+    /// Switches the buds back to high quality audio, and keeps doing so from now on.
+    BlockMicrophone,
+    /// Switches between noise reduction and no noise control.
+    ToggleNoiseReduction,
+    /// Microphone blocking was turned on, e.g. in the preferences.
+    MicrophoneBlockingEnabled,
+    /// Shows the device data received since the last render.
+    Render,
 }
 
 #[derive(Debug)]
 pub enum PageManageOutput {
-    OpenFindDialog,
+    OpenFindDialog {
+        /// Whether a bud is in an ear, so the tone could hurt.
+        worn: bool,
+    },
     Disconnect,
-    Navigate(adw::NavigationPage),
+    Forget(DeviceInfo),
+    Navigate(PageId, adw::NavigationPage),
+    UnknownFrame {
+        frame: Bytes,
+        /// The session the frame came in, to tell the model and revision in reports.
+        session: Option<SessionInfo>,
+    },
+    SessionControl(SessionControl),
+    MessageReceived(String),
+    /// The device finished connecting (`true`) or the connection was lost (`false`).
+    ConnectionChanged(bool),
+    /// The status was updated, e.g. to mirror it to desktop integrations.
+    StatusChanged(BudsStatus),
+    /// The case was opened or closed with the buds inside.
+    CaseEvent {
+        is_open: bool,
+        battery_summary: String,
+    },
+    /// The emptier bud dropped below one of `policy::LOW_BATTERY_LEVELS`.
+    LowBattery { address: String, level: i8 },
 }
 
 #[relm4::component(pub)]
-impl SimpleComponent for PageManageModel {
+impl Component for PageManageModel {
     type Input = PageManageInput;
     type Output = PageManageOutput;
-    type Init = DeviceInfo;
+    type Init = PageManageInit;
+    type CommandOutput = ();
+
+    menu! {
+        device_menu: {
+            "Export Battery History…" => ExportHistoryAction,
+            "Disconnect" => DisconnectAction,
+            "Forget this device" => ForgetAction,
+        }
+    }
 
     view! {
         #[root]
@@ -73,264 +408,1549 @@ impl SimpleComponent for PageManageModel {
             set_title: model.device.name.as_str(),
 
             #[wrap(Some)]
-            set_child = &adw::ToolbarView {
-                add_top_bar = &adw::HeaderBar {},
-                add_top_bar = &adw::Banner {},
-
+            #[name = "toast_overlay"]
+            set_child = &adw::ToastOverlay {
                 #[wrap(Some)]
-                set_content = &adw::Clamp {
-                    gtk4::Box {
-                        set_orientation: gtk4::Orientation::Vertical,
-                        set_margin_horizontal: 4,
-                        set_margin_vertical: 8,
-                        set_spacing: 16,
-
-                        gtk4::Box {
-                            set_orientation: gtk4::Orientation::Vertical,
-                            set_margin_horizontal: 4,
-                            set_margin_vertical: 8,
-                            set_spacing: 16,
-
-                            gtk4::Image {
-                                set_icon_name: Some("image-missing"),
-                                set_icon_size: gtk4::IconSize::Large,
-                                set_pixel_size: 128,
-                            },
+                set_child = &adw::ToolbarView {
+                    add_top_bar = &adw::HeaderBar {
+                        pack_end: &primary_menu_button(),
+                        pack_end = &gtk4::MenuButton {
+                            set_icon_name: "view-more-symbolic",
+                            set_tooltip_text: Some("Device menu"),
+                            set_menu_model: Some(&device_menu),
+                        },
+                    },
+                    // Follows the hands-free profile, whichever app owns the call.
+                    add_top_bar = &gtk4::Revealer {
+                        #[watch]
+                        set_reveal_child: model.in_call,
+
+                        #[wrap(Some)]
+                        set_child = &gtk4::Box {
+                            add_css_class: "toolbar",
+                            set_spacing: 6,
 
                             gtk4::Label {
+                                set_hexpand: true,
+                                set_xalign: 0.0,
+                                #[watch]
+                                set_label: model.modem_call.as_ref().map_or("In a call", ModemCall::state_text),
+                            },
+                            gtk4::Button {
+                                set_label: "Answer",
+                                add_css_class: "suggested-action",
+                                #[watch]
+                                set_visible: model.is_call_incoming(),
+                                connect_clicked => PageManageInput::AnswerCall,
+                            },
+                            gtk4::Button {
                                 #[watch]
-                                set_label: model.device.name.as_str(),
-                                add_css_class: "title-1",
+                                set_label: if model.is_call_incoming() { "Decline" } else { "Hang Up" },
+                                add_css_class: "destructive-action",
+                                // Only calls on the phone's modem can be ended from here.
+                                #[watch]
+                                set_visible: model.modem_call.is_some(),
+                                connect_clicked => PageManageInput::HangUpCall,
+                            },
+                            // Ends the call's use of the buds, for calls of any app.
+                            gtk4::Button {
+                                set_label: "High Quality Audio",
+                                set_tooltip_text: Some("Stop using the microphone of the buds, which lowers audio quality"),
+                                connect_clicked => PageManageInput::LeaveHandsFree,
                             },
+                        },
+                    },
 
-                            #[transition = "SlideUp"]
-                            match model.connection_state {
-                                ConnectionState::Connected => gtk4::Box {
-                                    set_orientation: gtk4::Orientation::Horizontal,
-                                    set_halign: gtk4::Align::Center,
-                                    set_spacing: 8,
+                    #[wrap(Some)]
+                    #[name = "breakpoint_bin"]
+                    set_content = &adw::BreakpointBin {
+                        set_width_request: 360,
+                        set_height_request: 294,
 
-                                    gtk4::Box {
-                                        set_spacing: 4,
+                        #[wrap(Some)]
+                        set_child = &adw::Clamp {
+                            gtk4::Box {
+                                set_orientation: gtk4::Orientation::Vertical,
+                                set_margin_horizontal: 4,
+                                set_margin_vertical: 8,
+                                set_spacing: 16,
 
-                                        gtk4::Image {
-                                            set_icon_name: Some("audio-headphones-symbolic"),
-                                        },
+                                gtk4::Box {
+                                    set_orientation: gtk4::Orientation::Vertical,
+                                    set_margin_horizontal: 4,
+                                    set_margin_vertical: 8,
+                                    set_spacing: 16,
+
+                                    adw::Avatar {
+                                        set_size: 128,
+                                        set_halign: gtk4::Align::Center,
+                                        set_show_initials: true,
+                                        #[watch]
+                                        set_text: Some(&device_appearance::avatar_text(&model.device.name)),
+                                        add_css_class: device_appearance::avatar_color_class(&model.device.address),
+                                    },
+
+                                    gtk4::Label {
+                                        #[watch]
+                                        set_label: model.device.name.as_str(),
+                                        add_css_class: "title-1",
+                                    },
 
-                                        gtk4::Label {
+                                    #[transition = "SlideUp"]
+                                    match model.connection_state {
+                                        ConnectionState::Connected => gtk4::Box {
                                             #[watch]
-                                            set_label: &model.buds_status.or_na(BudsStatus::battery_text),
-                                            add_css_class: "heading",
+                                            set_orientation: if model.is_compact {
+                                                gtk4::Orientation::Vertical
+                                            } else {
+                                                gtk4::Orientation::Horizontal
+                                            },
+                                            set_halign: gtk4::Align::Center,
+                                            set_spacing: 8,
+
+                                            #[local_ref]
+                                            buds_chip -> gtk4::Box {},
+                                            #[local_ref]
+                                            case_chip -> gtk4::Box {},
+                                            #[local_ref]
+                                            wearing_chip -> gtk4::Box {},
+                                        },
+                                        ConnectionState::Connecting(_)
+                                        | ConnectionState::Handshaking
+                                        | ConnectionState::WaitingToReconnect => gtk4::Box {
+                                            set_orientation: gtk4::Orientation::Vertical,
+                                            set_halign: gtk4::Align::Center,
+                                            set_spacing: 8,
+
+                                            adw::Spinner {
+                                                set_height_request: 32,
+                                            },
+                                            gtk4::Label {
+                                                #[watch]
+                                                set_label: &model.view().connect_stage_text(),
+                                                add_css_class: "dim-label",
+                                            },
+                                            // Reconnecting won't help while another device has the buds.
+                                            gtk4::Button {
+                                                #[watch]
+                                                set_visible: model.view().can_take_over(),
+                                                set_label: "Take Over Connection",
+                                                set_halign: gtk4::Align::Center,
+                                                set_tooltip_text: Some("Move the audio from the other device to this one, then connect"),
+                                                connect_clicked => PageManageInput::ConfirmTakeOver,
+                                            },
                                         },
+                                        ConnectionState::Unsupported => gtk4::Box {
+                                            set_orientation: gtk4::Orientation::Vertical,
+                                            set_halign: gtk4::Align::Center,
+                                            set_spacing: 8,
+
+                                            gtk4::Label {
+                                                set_label: "This device doesn't appear to be a supported Galaxy Buds model",
+                                                set_wrap: true,
+                                                set_justify: gtk4::Justification::Center,
+                                                add_css_class: "heading",
+                                            },
+                                            gtk4::Label {
+                                                #[watch]
+                                                set_label: &model.device.identification_text(),
+                                                set_selectable: true,
+                                                add_css_class: "monospace",
+                                                add_css_class: "dim-label",
+                                            },
+                                            gtk4::LinkButton {
+                                                set_label: "Report an issue",
+                                                set_uri: ISSUES_URL,
+                                            },
+                                        },
+                                        ConnectionState::ProfileInUse => gtk4::Box {
+                                            set_orientation: gtk4::Orientation::Vertical,
+                                            set_halign: gtk4::Align::Center,
+                                            set_spacing: 8,
+
+                                            gtk4::Label {
+                                                set_label: "Another app is connected to the buds",
+                                                set_wrap: true,
+                                                set_justify: gtk4::Justification::Center,
+                                                add_css_class: "heading",
+                                            },
+                                            gtk4::Label {
+                                                set_label: "Only one app at a time can talk to the buds. Close the other one, e.g. GalaxyBudsClient running in Wine, then try again.",
+                                                set_wrap: true,
+                                                set_justify: gtk4::Justification::Center,
+                                                add_css_class: "dim-label",
+                                            },
+                                            gtk4::Button {
+                                                set_label: "Try Again",
+                                                set_halign: gtk4::Align::Center,
+                                                add_css_class: "pill",
+                                                connect_clicked => PageManageInput::Connect,
+                                            },
+                                        },
+                                        ConnectionState::Disconnected | ConnectionState::Error(_) => gtk4::Box {
+                                            set_orientation: gtk4::Orientation::Horizontal,
+                                            set_halign: gtk4::Align::Center,
+                                            set_spacing: 8,
+
+                                            gtk4::Label { set_label: "Disconnected" },
+                                            gtk4::Button {
+                                                set_label: "Connect",
+                                                connect_clicked => PageManageInput::Connect,
+                                            },
+                                            // Connecting usually fails because the buds are in use by another device.
+                                            gtk4::Button {
+                                                #[watch]
+                                                set_visible: model.view().can_take_over(),
+                                                set_label: "Take Over Connection",
+                                                set_tooltip_text: Some("Move the audio from the other device to this one, then connect"),
+                                                connect_clicked => PageManageInput::ConfirmTakeOver,
+                                            }
+                                        },
+                                    },
+
+                                    gtk4::Label {
+                                        #[watch]
+                                        set_visible: !model.view().is_connected() && model.last_connected.is_some(),
+                                        #[watch]
+                                        set_label: &model.last_connected_text(),
+                                        add_css_class: "caption",
+                                        add_css_class: "dim-label",
+                                    },
+
+                                    #[local_ref]
+                                    missing_bud_chip -> gtk4::Box {
+                                        set_halign: gtk4::Align::Center,
+                                    },
+
+                                    gtk4::Label {
+                                        #[watch]
+                                        set_visible: model.view().is_connected()
+                                            && !model.view().is_buds_charging()
+                                            && model.battery_stats.time_remaining_text().is_some(),
+                                        #[watch]
+                                        set_label: &model.battery_stats.time_remaining_text().unwrap_or_default(),
+                                        set_tooltip_text: Some("Estimated from the battery drain over the last hour"),
+                                        add_css_class: "caption",
+                                        add_css_class: "dim-label",
                                     },
 
                                     gtk4::Box {
-                                        set_spacing: 4,
+                                        set_halign: gtk4::Align::Center,
+                                        set_homogeneous: true,
+                                        add_css_class: "linked",
+                                        #[watch]
+                                        set_visible: model.view().is_connected(),
+                                        #[watch]
+                                        set_sensitive: model.view().is_noise_mode_sensitive(),
 
-                                        gtk4::Image {
-                                            set_icon_name: Some("printer-symbolic"),
+                                        #[name = "noise_off_button"]
+                                        gtk4::ToggleButton {
+                                            set_label: "Off",
+                                            #[watch]
+                                            set_active: model.view().noise_mode() == Some(NoiseControlMode::Off),
+                                            connect_toggled: select_noise_mode(sender.clone(), NoiseControlMode::Off),
                                         },
-
-                                        gtk4::Label {
+                                        gtk4::ToggleButton {
+                                            set_label: "Ambient",
+                                            set_group: Some(&noise_off_button),
+                                            #[watch]
+                                            set_active: model.view().noise_mode() == Some(NoiseControlMode::AmbientSound),
+                                            connect_toggled: select_noise_mode(sender.clone(), NoiseControlMode::AmbientSound),
+                                        },
+                                        gtk4::ToggleButton {
+                                            set_label: "ANC",
+                                            set_group: Some(&noise_off_button),
                                             #[watch]
-                                            set_label: &model.buds_status.or_na(BudsStatus::case_battery_text),
-                                            add_css_class: "heading",
+                                            set_active: model.view().noise_mode() == Some(NoiseControlMode::NoiseReduction),
+                                            connect_toggled: select_noise_mode(sender.clone(), NoiseControlMode::NoiseReduction),
                                         },
                                     },
                                 },
-                                ConnectionState::Connecting => gtk4::Label {
-                                    set_label: "Connecting..."
-                                },
-                                ConnectionState::Disconnected | ConnectionState::Error(_) => gtk4::Box {
-                                    set_orientation: gtk4::Orientation::Horizontal,
-                                    set_halign: gtk4::Align::Center,
-                                    set_spacing: 8,
-
-                                    gtk4::Label { set_label: "Disconnected" },
-                                    gtk4::Button {
-                                        set_label: "Connect",
-                                        connect_clicked => PageManageInput::Connect,
-                                    }
-                                },
-                            },
-                        },
 
-                        adw::PreferencesGroup {
-                            adw::ActionRow {
-                                set_title: "Noise control",
-                                #[watch]
-                                set_sensitive: matches!(model.connection_state, ConnectionState::Connected),
-                                set_activatable: true,
-                                add_suffix = &gtk4::Label {
-                                    #[watch]
-                                    set_label: &model.buds_status.or_na(BudsStatus::noise_control_mode_text),
-                                    add_css_class: "dim-label",
-                                },
-                                add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
-                                connect_activated => PageManageInput::Navigate(PageId::Noise),
-                            },
-                            adw::ActionRow {
-                                set_title: "Touch options",
-                                #[watch]
-                                set_sensitive: matches!(model.connection_state, ConnectionState::Connected),
-                                set_activatable: true,
-                                add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
+                                adw::PreferencesGroup {
+                                    adw::ActionRow {
+                                        set_title: "Volume",
+                                        #[watch]
+                                        set_visible: model.audio_sink.is_some(),
+                                        add_prefix: &gtk4::Image::from_icon_name("audio-volume-high-symbolic"),
+                                        add_suffix = &gtk4::Scale::with_range(gtk4::Orientation::Horizontal, 0.0, 100.0, 1.0) {
+                                            set_hexpand: true,
+                                            set_valign: gtk4::Align::Center,
+                                            #[watch]
+                                            #[block_signal(volume_handler)]
+                                            set_value: model.volume as f64,
+                                            connect_value_changed[sender] => move |scale| {
+                                                sender.input(PageManageInput::SetVolume(scale.value() as u32));
+                                            } @volume_handler,
+                                        },
+                                    },
+                                    adw::ActionRow {
+                                        set_title: "Noise control",
+                                        #[watch]
+                                        set_sensitive: model.view().is_connected(),
+                                        set_activatable: true,
+                                        add_suffix = &gtk4::Label {
+                                            #[watch]
+                                            set_label: &model.view().noise_mode_text(),
+                                            #[watch]
+                                            set_visible: !model.is_compact,
+                                            add_css_class: "dim-label",
+                                        },
+                                        add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
+                                        connect_activated => PageManageInput::Navigate(PageId::Noise),
+                                    },
+                                    adw::ActionRow {
+                                        set_title: "Touch options",
+                                        #[watch]
+                                        set_sensitive: model.view().is_connected(),
+                                        set_activatable: true,
+                                        add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
 
-                            },
-                            adw::ActionRow {
-                                set_title: "Equalizer",
-                                #[watch]
-                                set_sensitive: matches!(model.connection_state, ConnectionState::Connected),
-                                set_activatable: true,
-                                add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
+                                    },
+                                    adw::ActionRow {
+                                        set_title: "Equalizer",
+                                        #[watch]
+                                        set_sensitive: model.view().is_connected(),
+                                        set_activatable: true,
+                                        add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
 
-                            },
-                            adw::ActionRow {
-                                set_title: "Find my Buds",
-                                #[watch]
-                                set_sensitive: matches!(model.connection_state, ConnectionState::Connected),
-                                set_activatable: true,
-                                add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
-                                connect_activated => PageManageInput::OpenFindDialog,
-                            },
+                                    },
+                                    adw::ActionRow {
+                                        set_title: "Find my Buds",
+                                        #[watch]
+                                        set_sensitive: model.view().is_connected(),
+                                        set_activatable: true,
+                                        add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
+                                        connect_activated => PageManageInput::OpenFindDialog,
+                                    },
+                                    adw::ActionRow {
+                                        set_title: "Device details",
+                                        #[watch]
+                                        set_sensitive: model.view().is_connected(),
+                                        set_activatable: true,
+                                        add_suffix: &gtk4::Image::from_icon_name("go-next-symbolic"),
+                                        connect_activated => PageManageInput::Navigate(PageId::Details),
+                                    },
+                                }
+                            }
                         }
                     }
-                }
+                },
             },
         }
     }
 
     fn init(
-        device: Self::Init,
+        init: Self::Init,
         root: Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let model = PageManageModel {
-            device: device.clone(),
-            bt_worker: BluetoothWorker::builder()
-                .detach_worker(device.clone())
-                .forward(sender.input_sender(), PageManageInput::BluetoothEvent),
+        let mut actions = RelmActionGroup::<ManageActionGroup>::new();
+        let disconnect_action: RelmAction<DisconnectAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| sender.input(PageManageInput::ConfirmDisconnect))
+        };
+        let forget_action: RelmAction<ForgetAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| sender.input(PageManageInput::ConfirmForget))
+        };
+        let export_history_action: RelmAction<ExportHistoryAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| sender.input(PageManageInput::ChooseExportFile))
+        };
+        let noise_off_action: RelmAction<NoiseOffAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| {
+                sender.input(PageManageInput::SetNoiseMode(NoiseControlMode::Off))
+            })
+        };
+        let noise_ambient_action: RelmAction<NoiseAmbientAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| {
+                sender.input(PageManageInput::SetNoiseMode(
+                    NoiseControlMode::AmbientSound,
+                ))
+            })
+        };
+        let noise_anc_action: RelmAction<NoiseAncAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| {
+                sender.input(PageManageInput::SetNoiseMode(
+                    NoiseControlMode::NoiseReduction,
+                ))
+            })
+        };
+        let noise_control_action: RelmAction<NoiseControlAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| {
+                sender.input(PageManageInput::Navigate(PageId::Noise))
+            })
+        };
+        let find_action: RelmAction<FindAction> = {
+            let sender = sender.clone();
+            RelmAction::new_stateless(move |_| sender.input(PageManageInput::OpenFindDialog))
+        };
+        actions.add_action(disconnect_action);
+        actions.add_action(forget_action);
+        actions.add_action(export_history_action);
+        actions.add_action(noise_off_action);
+        actions.add_action(noise_ambient_action);
+        actions.add_action(noise_anc_action);
+        actions.add_action(noise_control_action);
+        actions.add_action(find_action);
+        // Kept in the model, so the command palette can list the actions.
+        let action_group = actions.into_action_group();
+        root.insert_action_group("manage", Some(&action_group));
+
+        let (bt_events, bt_events_receiver) = relm4::channel();
+        relm4::spawn_local(bt_events_receiver.forward(
+            sender.input_sender().clone(),
+            PageManageInput::BluetoothEvent,
+        ));
+
+        let settings = settings::get_settings();
+        let mut settings_dispatcher = SettingsDispatcher::new(settings.clone());
+        settings_dispatcher.on_change(&[BLOCK_MICROPHONE_KEY], {
+            let sender = sender.clone();
+            move |settings| {
+                if settings.boolean(BLOCK_MICROPHONE_KEY) {
+                    sender.input(PageManageInput::MicrophoneBlockingEnabled);
+                }
+            }
+        });
+        let battery_stats = BatteryStats::load(&init.device.address);
+        let mut model = PageManageModel {
+            root: root.clone(),
+            bt_worker: init.connections.subscribe(&init.device, bt_events),
+            confirmations: init.connections.confirmations(&init.device),
+            connections: init.connections,
+            last_connected: connection_manager::last_connected(&init.device.address),
+            device: init.device,
             connection_state: ConnectionState::Disconnected,
+            session: None,
             buds_status: None,
+            battery_stats,
+            pending_noise_mode: None,
+            pages: HashMap::new(),
             active_page: None,
+            is_compact: false,
+            audio_sink: None,
+            volume: 0,
+            in_call: false,
+            call_monitor: None,
+            modem_call: None,
+            modem_call_monitor: None,
+            // Replaced by the one from the view below.
+            toast_overlay: adw::ToastOverlay::new(),
+            buds_chip: StatusChip::builder().launch(StatusChipState::default()).detach(),
+            case_chip: StatusChip::builder().launch(StatusChipState::default()).detach(),
+            wearing_chip: StatusChip::builder().launch(StatusChipState::default()).detach(),
+            missing_bud_chip: StatusChip::builder().launch(StatusChipState::default()).detach(),
+            action_group,
+            restore_page: window_state::saved_subpage(&settings),
+            auto_reconnect: false,
+            reconnect_backoff: Backoff::new(RECONNECT_DELAY, RECONNECT_MAX_DELAY),
+            reconnect_source: None,
+            error_toast_throttle: Throttle::new(ERROR_TOAST_INTERVAL),
+            is_render_scheduled: false,
+            settings,
+            settings_writer: init.settings_writer,
+            settings_dispatcher,
         };
 
+        let buds_chip = model.buds_chip.widget();
+        let case_chip = model.case_chip.widget();
+        let wearing_chip = model.wearing_chip.widget();
+        let missing_bud_chip = model.missing_bud_chip.widget();
         let widgets = view_output!();
+        model.toast_overlay = widgets.toast_overlay.clone();
+
+        let breakpoint = adw::Breakpoint::new(adw::BreakpointCondition::new_length(
+            adw::BreakpointConditionLengthType::MaxWidth,
+            COMPACT_MAX_WIDTH,
+            adw::LengthUnit::Sp,
+        ));
+        breakpoint.connect_apply({
+            let sender = sender.clone();
+            move |_| sender.input(PageManageInput::SetCompact(true))
+        });
+        breakpoint.connect_unapply({
+            let sender = sender.clone();
+            move |_| sender.input(PageManageInput::SetCompact(false))
+        });
+        widgets.breakpoint_bin.add_breakpoint(breakpoint);
 
         sender.input(PageManageInput::Connect);
 
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+    fn post_view(&self, _widgets: &mut Self::Widgets, _sender: ComponentSender<Self>) {
+        let view = self.view();
+        self.buds_chip.emit(StatusChipInput::Set(view.buds_chip()));
+        self.case_chip.emit(StatusChipInput::Set(view.case_chip()));
+        self.wearing_chip.emit(StatusChipInput::Set(view.wearing_chip()));
+        self.missing_bud_chip.emit(StatusChipInput::Set(view.missing_bud_chip()));
+    }
+
+    fn shutdown(&mut self, _widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        self.stop_monitoring_calls();
+        self.cancel_reconnect();
+    }
+
+    /// Renders once a burst of device data was handled, e.g. debug data, rather than after
+    /// every message of it.
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::Input,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        let is_device_data = matches!(
+            &message,
+            PageManageInput::BluetoothEvent(output)
+                if matches!(**output, BudsWorkerOutput::DataReceived(_))
+        );
+        self.update(message, sender.clone(), root);
+        if !is_device_data {
+            self.update_view(widgets, sender);
+        } else if !self.is_render_scheduled {
+            self.is_render_scheduled = true;
+            // Idle callbacks run after the pending inputs, i.e. at the end of the burst.
+            gtk4::glib::idle_add_local_once(move || sender.input(PageManageInput::Render));
+        }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _root: &Self::Root) {
         match message {
-            PageManageInput::BluetoothEvent(output) => match output {
-                BudsWorkerOutput::DataReceived(data) => match data {
-                    BudsMessage::StatusUpdate(status) => {
-                        debug!("Status Update: {:?}", status);
-                        if let Some(buds_status) = self.buds_status.as_mut() {
-                            buds_status.update(&status);
-                        }
-                    }
-                    BudsMessage::ExtendedStatusUpdate(ext_status) => {
-                        debug!("Extended Status Update: {:?}", ext_status);
-                        let buds_status = BudsStatus::from(&ext_status);
-                        if let Some(Page::Noise(page)) = &self.active_page {
-                            page.emit(PageNoiseInput::ModeUpdate(buds_status.noise_control_mode()));
+            PageManageInput::BluetoothEvent(output) => match &*output {
+                BudsWorkerOutput::DataReceived(data) => {
+                    if matches!(
+                        self.connection_state,
+                        ConnectionState::Handshaking | ConnectionState::Unsupported
+                    ) {
+                        // The device answers the manager info request with an extended status update.
+                        if let BudsMessage::ExtendedStatusUpdate(response) = data {
+                            let session = SessionInfo::from_response(self.device.model(), response);
+                            info!(?session, "Handshake done");
+                            self.check_model(session.model);
+                            self.session = Some(session);
+                            self.connection_state = ConnectionState::Connected;
+                            self.auto_reconnect = true;
+                            self.reconnect_backoff.reset();
+                            self.find_audio_sink(&sender);
+                            self.monitor_calls(&sender);
+                            self.announce_connection_state();
+                            sender
+                                .output_sender()
+                                .send_or_log(PageManageOutput::ConnectionChanged(true));
                         }
-                        self.buds_status = Some(buds_status);
                     }
-                    BudsMessage::NoiseControlsUpdate(noise_controls_updated) => {
-                        debug!("Noise Controls Update: {:?}", noise_controls_updated);
-                        if let Some(buds_status) = self.buds_status.as_mut() {
-                            buds_status.update(&noise_controls_updated);
-                        }
-                        if let Some(Page::Noise(page)) = &self.active_page {
-                            page.emit(PageNoiseInput::ModeUpdate(
-                                noise_controls_updated.noise_control_mode,
-                            ));
-                        }
+                    self.handle_message(data, &sender);
+                }
+                BudsWorkerOutput::HandshakeTimeout => {
+                    if let ConnectionState::Handshaking = self.connection_state {
+                        self.connection_state = ConnectionState::Unsupported;
+                        self.announce_connection_state();
                     }
-                    BudsMessage::Unknown { id, buffer: _ } => {
-                        debug!("Unknown message ID: {}", id);
+                }
+                BudsWorkerOutput::Progress(stage) => {
+                    debug!(?stage, "Connect progress");
+                    if let ConnectionState::Connecting(_) = self.connection_state {
+                        self.connection_state = ConnectionState::Connecting(*stage);
                     }
-                },
+                }
                 BudsWorkerOutput::Connected => {
                     debug!("Bluetooth connected");
-                    self.connection_state = ConnectionState::Connected;
+                    self.connection_state = ConnectionState::Handshaking;
                 }
+                // Handled along with the response itself, which comes next as `DataReceived`.
+                BudsWorkerOutput::HandshakeDone => {}
                 BudsWorkerOutput::Disconnected => {
                     debug!("Bluetooth disconnected");
+                    self.last_connected = connection_manager::last_connected(&self.device.address);
                     self.connection_state = ConnectionState::Disconnected;
+                    self.session = None;
+                    self.audio_sink = None;
+                    self.stop_monitoring_calls();
+                    self.pending_noise_mode = None;
+                    self.clear_pages();
+                    sender
+                        .output_sender()
+                        .send_or_log(PageManageOutput::ConnectionChanged(false));
+                    self.schedule_reconnect(&sender);
+                    self.announce_connection_state();
+                }
+                BudsWorkerOutput::ProfileInUse => {
+                    self.connection_state = ConnectionState::ProfileInUse;
+                    self.session = None;
+                    self.audio_sink = None;
+                    self.stop_monitoring_calls();
+                    self.pending_noise_mode = None;
+                    self.announce_connection_state();
+                    sender
+                        .output_sender()
+                        .send_or_log(PageManageOutput::ConnectionChanged(false));
                 }
                 BudsWorkerOutput::Error(err) => {
                     error!("Bluetooth error: {}", err);
-                    self.connection_state = ConnectionState::Error(err);
+                    self.connection_state = ConnectionState::Error(err.clone());
+                    self.session = None;
+                    self.audio_sink = None;
+                    self.stop_monitoring_calls();
+                    self.pending_noise_mode = None;
+                    sender
+                        .output_sender()
+                        .send_or_log(PageManageOutput::ConnectionChanged(false));
+                    self.schedule_reconnect(&sender);
+                    // Only the error state shows it otherwise.
+                    if let ConnectionState::WaitingToReconnect = self.connection_state {
+                        self.show_error_toast(err);
+                    }
+                    self.announce_connection_state();
                 }
             },
             PageManageInput::Connect => {
-                if let ConnectionState::Disconnected | ConnectionState::Error(_) =
-                    self.connection_state
+                if let ConnectionState::Disconnected
+                | ConnectionState::WaitingToReconnect
+                | ConnectionState::ProfileInUse
+                | ConnectionState::Error(_) = self.connection_state
                 {
                     debug!("PageManageInput::Connect");
-                    self.connection_state = ConnectionState::Connecting;
-                    self.bt_worker
-                        .sender()
-                        .send(BudsWorkerInput::Connect)
-                        .unwrap();
+                    self.cancel_reconnect();
+                    self.connection_state = ConnectionState::Connecting(ConnectStage::Baseband);
+                    self.bt_worker.send_or_log(BudsWorkerInput::Connect);
                 }
             }
+            PageManageInput::ReconnectDue => {
+                // Cleared when the reconnect was cancelled after the timer already fired.
+                if self.reconnect_source.take().is_some() {
+                    sender.input(PageManageInput::Connect);
+                }
+            }
+            PageManageInput::ConfirmTakeOver => {
+                let init = DialogConfirmInit {
+                    parent: self.root.clone().upcast(),
+                    heading: "Take over the connection?",
+                    body: format!(
+                        "{} will stop playing audio from the device it's connected to.",
+                        self.device.name
+                    ),
+                    confirm_label: "Take Over",
+                    is_destructive: true,
+                    option_label: None,
+                };
+                let confirmations = self.confirmations.clone();
+                relm4::spawn_local(async move {
+                    let operation = DestructiveOperation::TakeOver;
+                    if let Some((_, token)) =
+                        DialogConfirm::ask_for(init, &confirmations, operation).await
+                    {
+                        sender.input(PageManageInput::TakeOver(token));
+                    }
+                });
+            }
+            PageManageInput::TakeOver(token) => {
+                if let ConnectionState::Error(_) | ConnectionState::WaitingToReconnect =
+                    self.connection_state
+                {
+                    debug!("PageManageInput::TakeOver");
+                    self.cancel_reconnect();
+                    self.connection_state = ConnectionState::Connecting(ConnectStage::Baseband);
+                    self.bt_worker.send_or_log(BudsWorkerInput::TakeOver(token));
+                }
+            }
+            PageManageInput::ConfirmDisconnect => {
+                let init = DialogConfirmInit {
+                    parent: self.root.clone().upcast(),
+                    heading: "Disconnect?",
+                    body: format!(
+                        "{} will be disconnected and won't reconnect automatically.",
+                        self.device.name
+                    ),
+                    confirm_label: "Disconnect",
+                    is_destructive: true,
+                    option_label: None,
+                };
+                relm4::spawn_local(async move {
+                    if DialogConfirm::ask(init).await.is_some() {
+                        sender.input(PageManageInput::Disconnect);
+                    }
+                });
+            }
             PageManageInput::Disconnect => {
-                self.bt_worker
-                    .sender()
-                    .send(BudsWorkerInput::Disconnect)
-                    .unwrap();
-                sender.output(PageManageOutput::Disconnect).unwrap();
+                self.auto_reconnect = false;
+                self.cancel_reconnect();
+                self.bt_worker.send_or_log(BudsWorkerInput::Disconnect);
+                sender
+                    .output_sender()
+                    .send_or_log(PageManageOutput::Disconnect);
+            }
+            PageManageInput::ConfirmForget => {
+                let init = DialogConfirmInit {
+                    parent: self.root.clone().upcast(),
+                    heading: "Forget this device?",
+                    body: format!(
+                        "{} will be disconnected and its saved settings will be removed.",
+                        self.device.name
+                    ),
+                    confirm_label: "Forget",
+                    is_destructive: true,
+                    option_label: Some("Also remove the Bluetooth pairing"),
+                };
+                let confirmations = self.confirmations.clone();
+                relm4::spawn_local(async move {
+                    let operation = DestructiveOperation::Forget;
+                    if let Some((confirmation, token)) =
+                        DialogConfirm::ask_for(init, &confirmations, operation).await
+                    {
+                        sender.input(PageManageInput::Forget {
+                            token,
+                            unpair: confirmation.is_option_checked,
+                        });
+                    }
+                });
+            }
+            PageManageInput::Forget { token, unpair } => {
+                self.auto_reconnect = false;
+                self.cancel_reconnect();
+                self.bt_worker.send_or_log(BudsWorkerInput::Forget { token, unpair });
+                sender
+                    .output_sender()
+                    .send_or_log(PageManageOutput::Forget(self.device.clone()));
+            }
+            PageManageInput::ChooseExportFile => {
+                let filters = gtk4::gio::ListStore::new::<gtk4::FileFilter>();
+                for (name, pattern) in [("CSV", "*.csv"), ("JSON", "*.json")] {
+                    let filter = gtk4::FileFilter::new();
+                    filter.set_name(Some(name));
+                    filter.add_pattern(pattern);
+                    filters.append(&filter);
+                }
+                let parent = self.root.root().and_downcast::<gtk4::Window>();
+                gtk4::FileDialog::builder()
+                    .title("Export Battery History")
+                    .initial_name("battery-history.csv")
+                    .filters(&filters)
+                    .build()
+                    .save(
+                        parent.as_ref(),
+                        gtk4::gio::Cancellable::NONE,
+                        move |result| {
+                            if let Some(path) = result.ok().and_then(|file| file.path()) {
+                                sender.input(PageManageInput::ExportBatteryHistory(path));
+                            }
+                        },
+                    );
+            }
+            PageManageInput::ExportBatteryHistory(path) => {
+                let format = ExportFormat::from_path(&path);
+                let mut contents = self.battery_stats.export(&self.device, format);
+                if self.settings.boolean(REDACT_ADDRESSES_KEY) {
+                    contents = redact::identifiers(&contents).into_owned();
+                }
+                match std::fs::write(&path, contents) {
+                    Ok(()) => debug!("Battery history exported to {}", path.display()),
+                    Err(e) => error!("Failed to export battery history: {}", e),
+                }
             }
             PageManageInput::BluetoothCommand(command) => {
-                self.bt_worker
-                    .sender()
-                    .send(BudsWorkerInput::SendCommand(command))
-                    .unwrap();
+                self.bt_worker.send_or_log(BudsWorkerInput::SendCommand(command));
             }
-            PageManageInput::OpenFindDialog => {
-                sender.output(PageManageOutput::OpenFindDialog).unwrap()
+            PageManageInput::SendData(data) => {
+                self.bt_worker.send_or_log(BudsWorkerInput::SendData(data));
             }
-            PageManageInput::FindDialogCommand(cmd) => {
-                sender.input(PageManageInput::BluetoothCommand(match cmd {
-                    DialogFindOutput::Find(active) => BudsCommand::Find(active),
-                }));
+            PageManageInput::SetPolling(active) => {
+                self.bt_worker.send_or_log(if active {
+                    BudsWorkerInput::ResumePolling
+                } else {
+                    BudsWorkerInput::PausePolling
+                });
             }
-            PageManageInput::Navigate(page_id) => {
-                match page_id {
-                    PageId::Noise => {
-                        // Replace page if not a match
-                        if !matches!(self.active_page, Some(Page::Noise(_))) {
-                            if let Some(buds_status) = &self.buds_status {
-                                self.active_page = Some(Page::Noise(
-                                    PageNoiseModel::builder()
-                                        .launch(buds_status.noise_control_mode())
-                                        .forward(sender.input_sender(), |msg| match msg {
-                                            PageNoiseOutput::SetMode(noise_control_mode) => {
-                                                PageManageInput::BluetoothCommand(
-                                                    BudsCommand::SetNoiseControlMode(
-                                                        noise_control_mode,
-                                                    ),
-                                                )
-                                            }
-                                        }),
-                                ));
-                            }
-                        }
+            PageManageInput::SetNoiseMode(mode) => {
+                if self.pending_noise_mode.is_some() || self.view().noise_mode() == Some(mode) {
+                    return;
+                }
+                self.pending_noise_mode = Some(mode);
+                sender.input(PageManageInput::BluetoothCommand(
+                    BudsCommand::SetNoiseControlMode(mode),
+                ));
+                // The device doesn't always confirm the change, so assume it was applied.
+                gtk4::glib::timeout_add_local_once(NOISE_MODE_SETTLE_DELAY, move || {
+                    sender.input(PageManageInput::NoiseModeSettled);
+                });
+            }
+            PageManageInput::NoiseModeSettled => {
+                if let Some(mode) = self.pending_noise_mode.take() {
+                    if let Some(buds_status) = self.buds_status.as_mut() {
+                        buds_status.update(mode);
+                    }
+                    if let Some(Page::Noise(page)) = self.pages.get(&PageId::Noise) {
+                        page.emit(PageNoiseInput::ModeUpdate(mode));
                     }
+                    self.emit_status(&sender);
+                }
+            }
+            PageManageInput::SetNoiseControlsWithOneEarbud(enabled) => {
+                let Some(buds_status) = self.buds_status.as_mut() else {
+                    return;
+                };
+                if buds_status.noise_controls_with_one_earbud() == Some(enabled) {
+                    return;
+                }
+                buds_status.set_noise_controls_with_one_earbud(enabled);
+                sender.input(PageManageInput::BluetoothCommand(
+                    BudsCommand::SetNoiseControlsWithOneEarbud(enabled),
+                ));
+                if let Some(Page::Noise(page)) = self.pages.get(&PageId::Noise) {
+                    page.emit(PageNoiseInput::OneEarbudUpdate(enabled));
+                }
+            }
+            PageManageInput::SetAmbientSoundDuringCalls(enabled) => {
+                let Some(buds_status) = self.buds_status.as_mut() else {
+                    return;
                 };
+                if buds_status.ambient_sound_during_calls() == Some(enabled) {
+                    return;
+                }
+                buds_status.set_ambient_sound_during_calls(enabled);
+                sender.input(PageManageInput::BluetoothCommand(
+                    BudsCommand::SetAmbientSoundDuringCalls(enabled),
+                ));
+                if let Some(Page::Noise(page)) = self.pages.get(&PageId::Noise) {
+                    page.emit(PageNoiseInput::AmbientDuringCallsUpdate(enabled));
+                }
+            }
+            PageManageInput::OpenFindDialog => {
+                let worn = self.buds_status.as_ref().is_some_and(BudsStatus::is_worn);
+                sender
+                    .output_sender()
+                    .send_or_log(PageManageOutput::OpenFindDialog { worn });
+            }
+            PageManageInput::FindDialogCommand(cmd) => match cmd {
+                DialogFindOutput::Find(active) => self.connections.set_find(&self.device, active),
+            },
+            PageManageInput::Navigate(page_id) => {
+                if !self.pages.contains_key(&page_id) {
+                    if let Some(page) = self.create_page(page_id, &sender) {
+                        self.pages.insert(page_id, page);
+                    }
+                }
 
-                if let Some(page) = &self.active_page {
+                if let Some(page) = self.pages.get(&page_id) {
+                    self.active_page = Some(page_id);
+                    window_state::save_subpage(&self.settings_writer, Some(page_id));
                     sender
-                        .output(PageManageOutput::Navigate(page.widget().clone()))
-                        .unwrap();
+                        .output_sender()
+                        .send_or_log(PageManageOutput::Navigate(page_id, page.widget().clone()));
+                }
+            }
+            PageManageInput::SetCompact(is_compact) => self.is_compact = is_compact,
+            PageManageInput::AudioSinkFound(found) => {
+                if let Some((audio_sink, volume)) = found {
+                    self.audio_sink = Some(audio_sink);
+                    self.volume = volume;
+                }
+            }
+            PageManageInput::CallStateChanged(in_call) => {
+                debug!(in_call, "Call state changed");
+                self.in_call = in_call;
+                if in_call {
+                    self.monitor_modem_call(&sender);
+                    // The call controls aren't read out when revealed.
+                    self.root.announce(
+                        "In a call, audio quality lowered",
+                        AccessibleAnnouncementPriority::Medium,
+                    );
+                    if self.settings.boolean(BLOCK_MICROPHONE_KEY) {
+                        self.use_high_fidelity_profile();
+                    } else {
+                        let toast = adw::Toast::builder()
+                            .title("An app is using the microphone, lowering audio quality")
+                            .button_label("Block")
+                            .build();
+                        toast.connect_button_clicked(move |_| {
+                            sender.input(PageManageInput::BlockMicrophone);
+                        });
+                        self.toast_overlay.add_toast(toast);
+                    }
+                } else {
+                    self.stop_monitoring_modem_call();
+                }
+            }
+            PageManageInput::ModemCallChanged(modem_call) => {
+                debug!(?modem_call, "Modem call changed");
+                // Sent before the monitor stopped with the call.
+                if !self.in_call {
+                    return;
+                }
+                let was_incoming = self.is_call_incoming();
+                self.modem_call = modem_call;
+                if self.is_call_incoming() && !was_incoming {
+                    self.root.announce("Incoming call", AccessibleAnnouncementPriority::High);
+                }
+            }
+            PageManageInput::AnswerCall => {
+                if let Some(modem_call) = self.modem_call.clone() {
+                    relm4::spawn_local(async move {
+                        if let Err(e) = modem_call.accept().await {
+                            error!("Failed to answer the call: {}", e);
+                        }
+                    });
+                }
+            }
+            PageManageInput::HangUpCall => {
+                if let Some(modem_call) = self.modem_call.clone() {
+                    relm4::spawn_local(async move {
+                        if let Err(e) = modem_call.hang_up().await {
+                            error!("Failed to hang up the call: {}", e);
+                        }
+                    });
+                }
+            }
+            PageManageInput::LeaveHandsFree => self.use_high_fidelity_profile(),
+            PageManageInput::BlockMicrophone => {
+                // Applied once the change comes back as `MicrophoneBlockingEnabled`.
+                self.settings_writer.set_boolean(BLOCK_MICROPHONE_KEY, true);
+            }
+            PageManageInput::ToggleNoiseReduction => {
+                let mode = match self.view().noise_mode() {
+                    Some(NoiseControlMode::NoiseReduction) => NoiseControlMode::Off,
+                    _ => NoiseControlMode::NoiseReduction,
+                };
+                sender.input(PageManageInput::SetNoiseMode(mode));
+            }
+            PageManageInput::Render => self.is_render_scheduled = false,
+            PageManageInput::MicrophoneBlockingEnabled => {
+                // Otherwise it's applied when the next call starts.
+                if self.in_call {
+                    self.use_high_fidelity_profile();
+                }
+            }
+            PageManageInput::SetVolume(volume) => {
+                self.volume = volume;
+                if let Some(audio_sink) = self.audio_sink.clone() {
+                    relm4::spawn(async move {
+                        if let Err(e) = audio_sink.set_volume(volume).await {
+                            error!("Failed to set the volume: {}", e);
+                        }
+                    });
+                }
+            }
+            PageManageInput::SubpagePopped(popped_page) => {
+                if let Some(page_id) = self.active_page {
+                    let page = self.pages.get(&page_id);
+                    if page.is_some_and(|page| page.widget() == &popped_page) {
+                        self.active_page = None;
+                        window_state::save_subpage(&self.settings_writer, None);
+                        // Keep the controller cached for reuse, unless it outlived its connection.
+                        if !matches!(self.connection_state, ConnectionState::Connected) {
+                            self.pages.remove(&page_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl PageManageModel {
+    /// Address of the device the page manages.
+    pub fn address(&self) -> &str {
+        &self.device.address
+    }
+
+    /// The `manage` actions of the page, e.g. to list them in the command palette.
+    pub fn action_group(&self) -> &gtk4::gio::SimpleActionGroup {
+        &self.action_group
+    }
+
+    fn view(&self) -> ManageView<'_> {
+        ManageView {
+            connection_state: &self.connection_state,
+            buds_status: self.buds_status.as_ref(),
+            pending_noise_mode: self.pending_noise_mode,
+            reconnect_attempt: self.reconnect_backoff.attempt(),
+        }
+    }
+
+    /// Looks for the system audio sink of the device in the background, to control its volume.
+    fn find_audio_sink(&self, sender: &ComponentSender<Self>) {
+        let address = self.device.address.clone();
+        let sender = sender.clone();
+        relm4::spawn(async move {
+            let found = match AudioSink::find(&address).await {
+                Some(audio_sink) => match audio_sink.volume().await {
+                    Ok(volume) => Some((audio_sink, volume)),
+                    Err(e) => {
+                        error!("Failed to get the volume: {}", e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            sender.input(PageManageInput::AudioSinkFound(found));
+        });
+    }
+
+    /// Starts polling whether the device's audio card is in the hands-free profile, which is
+    /// what shows the call controls.
+    fn monitor_calls(&mut self, sender: &ComponentSender<Self>) {
+        self.stop_monitoring_calls();
+        let address = self.device.address.clone();
+        let input = sender.input_sender().clone();
+        self.call_monitor = Some(relm4::spawn(async move {
+            let Some(audio_card) = AudioCard::find(&address).await else {
+                return;
+            };
+            let mut in_call = false;
+            loop {
+                match audio_card.is_in_call().await {
+                    Ok(is_in_call) if is_in_call != in_call => {
+                        in_call = is_in_call;
+                        if input
+                            .send(PageManageInput::CallStateChanged(in_call))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!("Failed to get the call state: {}", e);
+                        break;
+                    }
+                }
+                tokio::time::sleep(CALL_POLL_INTERVAL).await;
+            }
+        }));
+    }
+
+    /// Starts polling for the call on the phone's modem while the buds are in a call, for
+    /// answering and hanging up. Without ModemManager, nothing is found.
+    fn monitor_modem_call(&mut self, sender: &ComponentSender<Self>) {
+        self.stop_monitoring_modem_call();
+        // The D-Bus calls need the main loop, unlike the `pactl` ones of `monitor_calls`.
+        let input = sender.input_sender().clone();
+        self.modem_call_monitor = Some(relm4::spawn_local(async move {
+            let mut modem_call = None;
+            loop {
+                let found = ModemCall::find().await;
+                if found != modem_call {
+                    modem_call = found.clone();
+                    if input.send(PageManageInput::ModemCallChanged(found)).is_err() {
+                        break;
+                    }
                 }
+                gtk4::glib::timeout_future(CALL_POLL_INTERVAL).await;
             }
+        }));
+    }
+
+    /// Switches the device's audio card to A2DP in the background, dropping the microphone.
+    fn use_high_fidelity_profile(&self) {
+        let address = self.device.address.clone();
+        relm4::spawn(async move {
+            if let Some(audio_card) = AudioCard::find(&address).await {
+                if let Err(e) = audio_card.use_high_fidelity_profile().await {
+                    error!("Failed to switch to the A2DP profile: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Turns noise control off when both buds were just put in the case, if enabled.
+    fn apply_placement_policy(&self, was_in_case: bool, sender: &ComponentSender<Self>) {
+        let Some(buds_status) = &self.buds_status else {
+            return;
+        };
+        if !self.settings.boolean(NOISE_OFF_IN_CASE_KEY) {
+            return;
+        }
+        if let Some(mode) = policy::noise_control_on_placement(was_in_case, buds_status) {
+            info!(?mode, "Both buds are in the case, changing noise control");
+            sender.input(PageManageInput::SetNoiseMode(mode));
+        }
+    }
+
+    /// Resets the saved settings if they were for other buds, telling the user once.
+    fn check_model(&self, model: Model) {
+        if self.connections.check_model(&self.device.address, model) {
+            self.toast_overlay.add_toast(adw::Toast::new(
+                "Saved settings were for other buds with this address and were reset",
+            ));
         }
     }
+
+    fn last_connected_text(&self) -> String {
+        self.last_connected
+            .map(|time| format!("Last connected: {}", relative_time(time)))
+            .unwrap_or_default()
+    }
+
+    /// Reads the connection state out to screen readers, as the page only shows it otherwise.
+    fn announce_connection_state(&self) {
+        let (message, priority) = match &self.connection_state {
+            ConnectionState::Connected => (
+                format!("Connected to {}", self.device.name),
+                AccessibleAnnouncementPriority::Medium,
+            ),
+            ConnectionState::Disconnected => (
+                format!("{} disconnected", self.device.name),
+                AccessibleAnnouncementPriority::Medium,
+            ),
+            // Only the first attempt, the following ones would repeat it every few seconds.
+            ConnectionState::WaitingToReconnect if self.reconnect_backoff.attempt() <= 1 => (
+                "Connection lost, reconnecting soon".to_string(),
+                AccessibleAnnouncementPriority::Medium,
+            ),
+            ConnectionState::Unsupported => (
+                "This device doesn't appear to be a supported Galaxy Buds model".to_string(),
+                AccessibleAnnouncementPriority::High,
+            ),
+            ConnectionState::ProfileInUse => (
+                "Another app is connected to the buds".to_string(),
+                AccessibleAnnouncementPriority::High,
+            ),
+            ConnectionState::Error(err) => (
+                format!("Connection failed: {}", err),
+                AccessibleAnnouncementPriority::High,
+            ),
+            ConnectionState::WaitingToReconnect
+            | ConnectionState::Connecting(_)
+            | ConnectionState::Handshaking => return,
+        };
+        self.root.announce(&message, priority);
+    }
+
+    /// Tells about an error that isn't shown on the page, unless another one was just shown.
+    fn show_error_toast(&mut self, err: &str) {
+        let Some(suppressed) = self.error_toast_throttle.admit() else {
+            debug!("Not showing the error, one was shown recently");
+            return;
+        };
+        let title = match suppressed {
+            0 => format!("Connection failed: {}", err),
+            1 => format!("Connection failed: {} (1 more error suppressed)", err),
+            n => format!("Connection failed: {} ({} more errors suppressed)", err, n),
+        };
+        let toast = adw::Toast::new(&title);
+        toast.set_use_markup(false);
+        self.toast_overlay.add_toast(toast);
+    }
+
+    /// Tries connecting again after a backoff delay, if the connection was lost unexpectedly.
+    fn schedule_reconnect(&mut self, sender: &ComponentSender<Self>) {
+        if !self.auto_reconnect {
+            return;
+        }
+        let delay = self.reconnect_backoff.next_delay();
+        info!(attempt = self.reconnect_backoff.attempt(), ?delay, "Reconnecting later");
+        self.connection_state = ConnectionState::WaitingToReconnect;
+        self.cancel_reconnect();
+        let sender = sender.clone();
+        self.reconnect_source = Some(gtk4::glib::timeout_add_local_once(delay, move || {
+            sender.input(PageManageInput::ReconnectDue);
+        }));
+    }
+
+    fn cancel_reconnect(&mut self) {
+        let Some(source_id) = self.reconnect_source.take() else {
+            return;
+        };
+        // Removing a source that already fired panics, and it's gone once it has.
+        if let Some(source) = gtk4::glib::MainContext::default().find_source_by_id(&source_id) {
+            source.destroy();
+        }
+    }
+
+    fn stop_monitoring_calls(&mut self) {
+        if let Some(call_monitor) = self.call_monitor.take() {
+            call_monitor.abort();
+        }
+        self.stop_monitoring_modem_call();
+        self.in_call = false;
+    }
+
+    fn stop_monitoring_modem_call(&mut self) {
+        if let Some(modem_call_monitor) = self.modem_call_monitor.take() {
+            modem_call_monitor.abort();
+        }
+        self.modem_call = None;
+    }
+
+    fn is_call_incoming(&self) -> bool {
+        self.modem_call
+            .as_ref()
+            .is_some_and(|modem_call| modem_call.state == CallState::Incoming)
+    }
+
+    /// Applies a message received from the device to the model and open subpages.
+    fn handle_message(&mut self, message: &BudsMessage, sender: &ComponentSender<Self>) {
+        let previous_battery = self.buds_status.as_ref().and_then(BudsStatus::lowest_battery);
+        sender
+            .output_sender()
+            .send_or_log(PageManageOutput::MessageReceived(format!("{:?}", message)));
+
+        match message {
+            BudsMessage::StatusUpdate(status) => {
+                debug!("Status Update: {:?}", status);
+                if let Some(buds_status) = self.buds_status.as_mut() {
+                    let was_case_open = buds_status.is_case_open();
+                    let was_in_case = buds_status.are_both_in_case();
+                    buds_status.update(status);
+                    self.battery_stats.record(buds_status);
+                    notify_case_event(was_case_open, buds_status, sender);
+                    self.apply_placement_policy(was_in_case, sender);
+                    self.update_battery_health();
+                }
+            }
+            BudsMessage::ExtendedStatusUpdate(ext_status) => {
+                debug!("Extended Status Update: {:?}", ext_status);
+                self.pending_noise_mode = None;
+                // Don't react to the first update, the buds may have been in the case already.
+                let was_in_case = self
+                    .buds_status
+                    .as_ref()
+                    .is_none_or(BudsStatus::are_both_in_case);
+                // Update in place to keep the case charging state inferred from earlier updates.
+                let buds_status = match self.buds_status.as_mut() {
+                    Some(buds_status) => {
+                        let was_case_open = buds_status.is_case_open();
+                        buds_status.update(ext_status);
+                        notify_case_event(was_case_open, buds_status, sender);
+                        buds_status
+                    }
+                    None => {
+                        if let Some(page_id) = self.restore_page.take() {
+                            sender.input(PageManageInput::Navigate(page_id));
+                        }
+                        self.buds_status.insert(BudsStatus::from(ext_status))
+                    }
+                };
+                self.battery_stats.record(buds_status);
+                if let Some(Page::Noise(page)) = self.pages.get(&PageId::Noise) {
+                    if let Some(mode) = buds_status.noise_control_mode() {
+                        page.emit(PageNoiseInput::ModeUpdate(mode));
+                    }
+                }
+                self.apply_placement_policy(was_in_case, sender);
+                self.update_battery_health();
+            }
+            BudsMessage::NoiseControlsUpdate(noise_controls_updated) => {
+                debug!("Noise Controls Update: {:?}", noise_controls_updated);
+                self.pending_noise_mode = None;
+                if let Some(buds_status) = self.buds_status.as_mut() {
+                    buds_status.update(noise_controls_updated);
+                }
+                if let Some(Page::Noise(page)) = self.pages.get(&PageId::Noise) {
+                    page.emit(PageNoiseInput::ModeUpdate(
+                        noise_controls_updated.noise_control_mode,
+                    ));
+                }
+            }
+            BudsMessage::SessionControl(message) => {
+                trace!("Session control: {:?}", message);
+                sender
+                    .output_sender()
+                    .send_or_log(PageManageOutput::SessionControl(*message));
+                return;
+            }
+            BudsMessage::DebugData(data) => {
+                debug!("Debug data: {:?}", data);
+                if let Some(Page::Details(page)) = self.pages.get(&PageId::Details) {
+                    page.emit(PageDetailsInput::DebugDataUpdate(data.clone()));
+                }
+                return;
+            }
+            BudsMessage::Unknown { id, buffer } => {
+                debug!("Unknown message ID: {}", id);
+                sender
+                    .output_sender()
+                    .send_or_log(PageManageOutput::UnknownFrame {
+                        frame: buffer.clone(),
+                        session: self.session,
+                    });
+                return;
+            }
+        }
+
+        let battery = self.buds_status.as_ref().and_then(BudsStatus::lowest_battery);
+        if let Some(level) = policy::low_battery_crossed(previous_battery, battery) {
+            self.root.announce(
+                &format!("Buds battery below {}%", level),
+                AccessibleAnnouncementPriority::High,
+            );
+            sender
+                .output_sender()
+                .send_or_log(PageManageOutput::LowBattery {
+                    address: self.device.address.clone(),
+                    level,
+                });
+        }
+        self.emit_status(sender);
+    }
+
+    fn emit_status(&self, sender: &ComponentSender<Self>) {
+        if let Some(buds_status) = &self.buds_status {
+            sender
+                .output_sender()
+                .send_or_log(PageManageOutput::StatusChanged(buds_status.clone()));
+        }
+    }
+
+    /// Shows the latest battery stats on the details page, if it's open.
+    fn update_battery_health(&self) {
+        if let Some(Page::Details(page)) = self.pages.get(&PageId::Details) {
+            self.emit_battery_health(page);
+        }
+    }
+
+    fn emit_battery_health(&self, page: &Controller<PageDetailsModel>) {
+        page.emit(PageDetailsInput::DrainBalanceUpdate(
+            self.battery_stats.drain_balance_hint(),
+        ));
+        page.emit(PageDetailsInput::HistoryUpdate(
+            self.battery_stats.history_summary(),
+        ));
+    }
+
+    /// Launches the controller for a subpage.
+    ///
+    /// Returns `None` if the page can't be built yet, e.g. before the first status update.
+    fn create_page(&self, page_id: PageId, sender: &ComponentSender<Self>) -> Option<Page> {
+        match page_id {
+            PageId::Noise => {
+                let buds_status = self.buds_status.as_ref()?;
+                let capabilities = Capabilities::for_model(self.session?.model);
+                Some(Page::Noise(
+                    PageNoiseModel::builder()
+                        .launch(PageNoiseInit {
+                            mode: buds_status.noise_control_mode()?,
+                            // Never read from the device, so shown as off until set here.
+                            one_earbud: capabilities
+                                .noise_controls_with_one_earbud
+                                .then(|| buds_status.noise_controls_with_one_earbud().unwrap_or(false)),
+                            ambient_during_calls: capabilities
+                                .ambient_sound_during_calls
+                                .then(|| buds_status.ambient_sound_during_calls().unwrap_or(false)),
+                        })
+                        .forward(sender.input_sender(), |msg| match msg {
+                            PageNoiseOutput::SetMode(noise_control_mode) => {
+                                PageManageInput::SetNoiseMode(noise_control_mode)
+                            }
+                            PageNoiseOutput::SetOneEarbud(enabled) => {
+                                PageManageInput::SetNoiseControlsWithOneEarbud(enabled)
+                            }
+                            PageNoiseOutput::SetAmbientDuringCalls(enabled) => {
+                                PageManageInput::SetAmbientSoundDuringCalls(enabled)
+                            }
+                        }),
+                ))
+            }
+            PageId::Details => {
+                let page = PageDetailsModel::builder()
+                    .launch(self.device.clone())
+                    .forward(sender.input_sender(), |msg| match msg {
+                        PageDetailsOutput::RefreshDebugData => {
+                            PageManageInput::BluetoothCommand(BudsCommand::GetDebugData)
+                        }
+                        PageDetailsOutput::ExportHistory => PageManageInput::ChooseExportFile,
+                    });
+                self.emit_battery_health(&page);
+                Some(Page::Details(page))
+            }
+        }
+    }
+
+    /// Drops every cached subpage controller that isn't currently shown.
+    ///
+    /// The visible page is kept so the navigation view isn't left holding a dead widget;
+    /// it is released once popped.
+    fn clear_pages(&mut self) {
+        let active_page = self.active_page;
+        self.pages.retain(|id, _| Some(*id) == active_page);
+    }
+}
+
+/// Emits a `CaseEvent` if the case was opened or closed since `was_case_open` was read.
+fn notify_case_event(
+    was_case_open: Option<bool>,
+    buds_status: &BudsStatus,
+    sender: &ComponentSender<PageManageModel>,
+) {
+    if let (Some(was_open), Some(is_open)) = (was_case_open, buds_status.is_case_open()) {
+        if was_open != is_open {
+            sender
+                .output_sender()
+                .send_or_log(PageManageOutput::CaseEvent {
+                    is_open,
+                    battery_summary: buds_status.battery_summary(),
+                });
+        }
+    }
+}
+
+fn select_noise_mode(
+    sender: ComponentSender<PageManageModel>,
+    mode: NoiseControlMode,
+) -> impl Fn(&gtk4::ToggleButton) {
+    move |button: &gtk4::ToggleButton| {
+        if button.is_active() {
+            sender.input(PageManageInput::SetNoiseMode(mode));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galaxy_buds_rs::message::bud_property::Placement;
+
+    use super::*;
+
+    const WORN: (&Placement, &Placement) = (&Placement::Wearing, &Placement::Wearing);
+
+    fn view<'a>(
+        connection_state: &'a ConnectionState,
+        buds_status: Option<&'a BudsStatus>,
+    ) -> ManageView<'a> {
+        ManageView {
+            connection_state,
+            buds_status,
+            pending_noise_mode: None,
+            reconnect_attempt: 0,
+        }
+    }
+
+    #[test]
+    fn shows_na_before_the_first_status() {
+        let view = view(&ConnectionState::Disconnected, None);
+
+        assert!(!view.is_connected());
+        assert_eq!(
+            view.buds_chip(),
+            StatusChipState {
+                icon_name: "audio-headphones-symbolic",
+                label: "N/A".to_string(),
+                is_visible: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(view.case_chip().label, "N/A");
+        assert!(!view.wearing_chip().is_visible);
+        assert!(!view.missing_bud_chip().is_visible);
+        assert_eq!(view.noise_mode(), None);
+        assert_eq!(view.noise_mode_text(), "N/A");
+    }
+
+    #[test]
+    fn shows_the_battery_levels_of_worn_buds() {
+        let status = BudsStatus::from_readings((80, 75, 50), WORN, true);
+        let view = view(&ConnectionState::Connected, Some(&status));
+
+        assert_eq!(
+            view.buds_chip(),
+            StatusChipState {
+                icon_name: "audio-headphones-symbolic",
+                label: "L 80% / R 75%".to_string(),
+                is_visible: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            view.case_chip(),
+            StatusChipState {
+                icon_name: "printer-symbolic",
+                label: "50%".to_string(),
+                is_visible: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            view.wearing_chip(),
+            StatusChipState {
+                icon_name: "avatar-default-symbolic",
+                label: "In Ear".to_string(),
+                is_visible: true,
+                ..Default::default()
+            }
+        );
+        assert!(!view.missing_bud_chip().is_visible);
+    }
+
+    #[test]
+    fn merges_equal_bud_levels() {
+        let status = BudsStatus::from_readings((90, 90, 40), WORN, true);
+        assert_eq!(view(&ConnectionState::Connected, Some(&status)).battery_text(), "L / R 90%");
+    }
+
+    #[test]
+    fn marks_buds_in_the_case_as_charging() {
+        let in_case = (&Placement::InOpenCase, &Placement::InOpenCase);
+        let status = BudsStatus::from_readings((60, 100, 70), in_case, true);
+        let view = view(&ConnectionState::Connected, Some(&status));
+
+        assert!(view.buds_chip().is_charging);
+        assert_eq!(view.wearing_chip().label, "Not Worn");
+    }
+
+    #[test]
+    fn warns_about_a_missing_bud_only_while_connected() {
+        let status = BudsStatus::from_readings((0, 60, 50), WORN, false);
+
+        assert_eq!(
+            view(&ConnectionState::Connected, Some(&status)).missing_bud_chip(),
+            StatusChipState {
+                icon_name: "dialog-warning-symbolic",
+                label: "Left bud not connected — check the case".to_string(),
+                severity: Severity::Warning,
+                is_visible: true,
+                ..Default::default()
+            }
+        );
+        let disconnected = view(&ConnectionState::Disconnected, Some(&status));
+        assert!(!disconnected.missing_bud_chip().is_visible);
+    }
+
+    #[test]
+    fn shows_the_pending_noise_mode_and_locks_the_buttons() {
+        let mut status = BudsStatus::default();
+        status.update(NoiseControlMode::Off);
+        let mut view = view(&ConnectionState::Connected, Some(&status));
+        assert_eq!(view.noise_mode(), Some(NoiseControlMode::Off));
+        assert!(view.is_noise_mode_sensitive());
+
+        view.pending_noise_mode = Some(NoiseControlMode::AmbientSound);
+        assert_eq!(view.noise_mode(), Some(NoiseControlMode::AmbientSound));
+        assert!(!view.is_noise_mode_sensitive());
+        // The label only changes once the device confirms.
+        assert_eq!(view.noise_mode_text(), "Off");
+    }
+
+    #[test]
+    fn describes_the_connect_stage() {
+        let connecting = ConnectionState::Connecting(ConnectStage::WaitingForRequest);
+        assert_eq!(
+            view(&connecting, None).connect_stage_text(),
+            "Waiting for the device to respond..."
+        );
+        assert_eq!(
+            view(&ConnectionState::Handshaking, None).connect_stage_text(),
+            "Identifying device..."
+        );
+
+        let mut view = view(&ConnectionState::WaitingToReconnect, None);
+        view.reconnect_attempt = 2;
+        assert_eq!(view.connect_stage_text(), "Connection lost, reconnecting soon... (attempt 2)");
+    }
+
+    #[test]
+    fn offers_taking_over_after_an_error() {
+        let error = ConnectionState::Error("Connection refused".to_string());
+        assert!(view(&error, None).can_take_over());
+        assert!(view(&ConnectionState::WaitingToReconnect, None).can_take_over());
+        assert!(!view(&ConnectionState::Connected, None).can_take_over());
+        assert!(!view(&ConnectionState::Handshaking, None).can_take_over());
+    }
 }
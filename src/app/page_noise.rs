@@ -1,29 +1,90 @@
-use adw::prelude::{ActionRowExt, NavigationPageExt, PreferencesGroupExt, PreferencesRowExt};
+use std::time::Duration;
+
+use adw::prelude::{
+    ActionRowExt, NavigationPageExt, PreferencesGroupExt, PreferencesRowExt, SwitchRowExt,
+};
 use galaxy_buds_rs::message::bud_property::NoiseControlMode;
-use gtk4::prelude::CheckButtonExt;
+use gtk4::prelude::{CheckButtonExt, EventControllerExt, WidgetExt};
 use relm4::{ComponentParts, ComponentSender, SimpleComponent};
 use tracing::debug;
 
+use crate::sender_ext::SendOrLog;
+
+/// Ends a preview that never got its release, e.g. when the pointer was grabbed away.
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// What the page shows for the current settings, kept apart from the widgets.
+///
+/// Holds no GTK types, so the selection and sensitivity rules can be checked on their own.
+#[derive(Debug, Clone, Copy)]
+struct NoiseView {
+    mode: NoiseControlMode,
+    is_previewing: bool,
+    one_earbud: Option<bool>,
+    ambient_during_calls: Option<bool>,
+}
+
+impl NoiseView {
+    fn is_selected(&self, mode: NoiseControlMode) -> bool {
+        self.mode == mode
+    }
+
+    /// The preview can start unless ambient sound is on already, and end while it runs.
+    fn is_preview_sensitive(&self) -> bool {
+        self.mode != NoiseControlMode::AmbientSound || self.is_previewing
+    }
+
+    /// The advanced settings are only shown for models that support at least one of them.
+    fn is_advanced_visible(&self) -> bool {
+        self.one_earbud.is_some() || self.ambient_during_calls.is_some()
+    }
+}
+
 #[derive(Debug)]
 pub struct PageNoiseModel {
     mode: NoiseControlMode,
+    /// The mode to go back to once the ambient sound preview ends, while it's held.
+    preview_restore: Option<NoiseControlMode>,
+    /// Incremented when a preview ends, so the timeout of an earlier one is ignored.
+    preview_generation: u64,
+    /// `None` when the model doesn't support the setting.
+    one_earbud: Option<bool>,
+    /// `None` when the model doesn't support the setting.
+    ambient_during_calls: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct PageNoiseInit {
+    pub mode: NoiseControlMode,
+    /// `None` when the model doesn't support noise controls with one earbud.
+    pub one_earbud: Option<bool>,
+    /// `None` when the model doesn't support ambient sound during calls.
+    pub ambient_during_calls: Option<bool>,
 }
 
 #[derive(Debug)]
 pub enum PageNoiseInput {
     ModeUpdate(NoiseControlMode),
+    OneEarbudUpdate(bool),
+    AmbientDuringCallsUpdate(bool),
+    /// The preview button was pressed; switches to ambient sound until released.
+    PreviewStart,
+    PreviewEnd,
+    PreviewTimeout { generation: u64 },
 }
 
 #[derive(Debug)]
 pub enum PageNoiseOutput {
     SetMode(NoiseControlMode),
+    SetOneEarbud(bool),
+    SetAmbientDuringCalls(bool),
 }
 
 #[relm4::component(pub)]
 impl SimpleComponent for PageNoiseModel {
     type Input = PageNoiseInput;
     type Output = PageNoiseOutput;
-    type Init = NoiseControlMode;
+    type Init = PageNoiseInit;
 
     view! {
         #[root]
@@ -46,7 +107,7 @@ impl SimpleComponent for PageNoiseModel {
                                 #[name = "check_off"]
                                 add_prefix = &gtk4::CheckButton::new() {
                                     #[watch]
-                                    set_active: model.mode == NoiseControlMode::Off,
+                                    set_active: model.view().is_selected(NoiseControlMode::Off),
                                     connect_toggled: toggle(sender.clone(), NoiseControlMode::Off),
                                 },
                                 set_activatable_widget: Some(&check_off),
@@ -57,7 +118,7 @@ impl SimpleComponent for PageNoiseModel {
                                 add_prefix = &gtk4::CheckButton::new() {
                                     set_group: Some(&check_off),
                                     #[watch]
-                                    set_active: model.mode == NoiseControlMode::AmbientSound,
+                                    set_active: model.view().is_selected(NoiseControlMode::AmbientSound),
                                     connect_toggled: toggle(sender.clone(), NoiseControlMode::AmbientSound),
                                 },
                                 set_activatable_widget: Some(&check_ambient),
@@ -68,12 +129,72 @@ impl SimpleComponent for PageNoiseModel {
                                 add_prefix = &gtk4::CheckButton::new() {
                                     set_group: Some(&check_ambient),
                                     #[watch]
-                                    set_active: model.mode == NoiseControlMode::NoiseReduction,
+                                    set_active: model.view().is_selected(NoiseControlMode::NoiseReduction),
                                     connect_toggled: toggle(sender.clone(), NoiseControlMode::NoiseReduction),
                                 },
                                 set_activatable_widget: Some(&check_noise),
-                            }
-                        }
+                            },
+                            adw::ActionRow {
+                                set_title: "Preview ambient sound",
+                                set_subtitle: "Hear your surroundings while holding the button",
+                                #[name = "preview_button"]
+                                add_suffix = &gtk4::Button {
+                                    set_label: "Hold",
+                                    set_valign: gtk4::Align::Center,
+                                    #[watch]
+                                    set_sensitive: model.view().is_preview_sensitive(),
+                                    // Captured before the button's own gesture claims the click.
+                                    add_controller = gtk4::GestureClick {
+                                        set_propagation_phase: gtk4::PropagationPhase::Capture,
+                                        connect_pressed[sender] => move |_, _, _, _| {
+                                            sender.input(PageNoiseInput::PreviewStart);
+                                        },
+                                        connect_released[sender] => move |_, _, _, _| {
+                                            sender.input(PageNoiseInput::PreviewEnd);
+                                        },
+                                        connect_stopped[sender] => move |_| {
+                                            sender.input(PageNoiseInput::PreviewEnd);
+                                        },
+                                    },
+                                },
+                            },
+                        },
+
+                        adw::PreferencesGroup {
+                            set_title: "Advanced",
+                            #[watch]
+                            set_visible: model.view().is_advanced_visible(),
+
+                            adw::SwitchRow {
+                                #[watch]
+                                set_visible: model.one_earbud.is_some(),
+                                set_title: "Noise controls with one earbud",
+                                set_subtitle: "Allow noise reduction and ambient sound when wearing only one earbud",
+                                #[watch]
+                                #[block_signal(one_earbud_handler)]
+                                set_active: model.one_earbud.unwrap_or_default(),
+                                connect_active_notify[sender] => move |row| {
+                                    sender
+                                        .output_sender()
+                                        .send_or_log(PageNoiseOutput::SetOneEarbud(row.is_active()));
+                                } @one_earbud_handler,
+                            },
+
+                            adw::SwitchRow {
+                                set_title: "Ambient sound during calls",
+                                set_subtitle: "Hear your own voice while using the earbud microphone",
+                                #[watch]
+                                set_visible: model.ambient_during_calls.is_some(),
+                                #[watch]
+                                #[block_signal(ambient_during_calls_handler)]
+                                set_active: model.ambient_during_calls.unwrap_or_default(),
+                                connect_active_notify[sender] => move |row| {
+                                    sender
+                                        .output_sender()
+                                        .send_or_log(PageNoiseOutput::SetAmbientDuringCalls(row.is_active()));
+                                } @ambient_during_calls_handler,
+                            },
+                        },
                     }
                 }
             },
@@ -81,11 +202,17 @@ impl SimpleComponent for PageNoiseModel {
     }
 
     fn init(
-        mode: Self::Init,
+        init: Self::Init,
         root: Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
-        let model = PageNoiseModel { mode };
+        let model = PageNoiseModel {
+            mode: init.mode,
+            preview_restore: None,
+            preview_generation: 0,
+            one_earbud: init.one_earbud,
+            ambient_during_calls: init.ambient_during_calls,
+        };
         let widgets = view_output!();
         ComponentParts { model, widgets }
     }
@@ -96,6 +223,61 @@ impl SimpleComponent for PageNoiseModel {
                 debug!("Mode update: {:?}", mode);
                 self.mode = mode;
             }
+            PageNoiseInput::OneEarbudUpdate(enabled) => {
+                if self.one_earbud.is_some() {
+                    self.one_earbud = Some(enabled);
+                }
+            }
+            PageNoiseInput::AmbientDuringCallsUpdate(enabled) => {
+                if self.ambient_during_calls.is_some() {
+                    self.ambient_during_calls = Some(enabled);
+                }
+            }
+            PageNoiseInput::PreviewStart => {
+                if self.preview_restore.is_some() || self.mode == NoiseControlMode::AmbientSound {
+                    return;
+                }
+                self.preview_restore = Some(self.mode);
+                sender
+                    .output_sender()
+                    .send_or_log(PageNoiseOutput::SetMode(NoiseControlMode::AmbientSound));
+
+                let generation = self.preview_generation;
+                gtk4::glib::timeout_add_local_once(PREVIEW_TIMEOUT, move || {
+                    sender.input(PageNoiseInput::PreviewTimeout { generation });
+                });
+            }
+            PageNoiseInput::PreviewEnd => self.end_preview(sender.output_sender()),
+            PageNoiseInput::PreviewTimeout { generation } => {
+                if generation == self.preview_generation {
+                    debug!("Ambient sound preview timed out");
+                    self.end_preview(sender.output_sender());
+                }
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _widgets: &mut Self::Widgets, output: relm4::Sender<Self::Output>) {
+        // The page may be closed while the button is held.
+        self.end_preview(&output);
+    }
+}
+
+impl PageNoiseModel {
+    fn view(&self) -> NoiseView {
+        NoiseView {
+            mode: self.mode,
+            is_previewing: self.preview_restore.is_some(),
+            one_earbud: self.one_earbud,
+            ambient_during_calls: self.ambient_during_calls,
+        }
+    }
+
+    /// Switches back to the mode from before the preview, if one is running.
+    fn end_preview(&mut self, output: &relm4::Sender<PageNoiseOutput>) {
+        if let Some(mode) = self.preview_restore.take() {
+            self.preview_generation += 1;
+            output.send_or_log(PageNoiseOutput::SetMode(mode));
         }
     }
 }
@@ -106,7 +288,141 @@ fn toggle(
 ) -> impl Fn(&gtk4::CheckButton) {
     move |c: &gtk4::CheckButton| {
         if c.is_active() {
-            let _ = sender.output(PageNoiseOutput::SetMode(mode));
+            sender
+                .output_sender()
+                .send_or_log(PageNoiseOutput::SetMode(mode));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_harness;
+
+    fn init(mode: NoiseControlMode) -> PageNoiseInit {
+        PageNoiseInit {
+            mode,
+            one_earbud: None,
+            ambient_during_calls: None,
+        }
+    }
+
+    fn view(mode: NoiseControlMode) -> NoiseView {
+        NoiseView {
+            mode,
+            is_previewing: false,
+            one_earbud: None,
+            ambient_during_calls: None,
+        }
+    }
+
+    #[test]
+    fn selects_only_the_current_mode() {
+        let view = view(NoiseControlMode::NoiseReduction);
+        assert!(view.is_selected(NoiseControlMode::NoiseReduction));
+        assert!(!view.is_selected(NoiseControlMode::AmbientSound));
+        assert!(!view.is_selected(NoiseControlMode::Off));
+    }
+
+    #[test]
+    fn locks_the_preview_while_ambient_sound_is_on() {
+        assert!(view(NoiseControlMode::Off).is_preview_sensitive());
+        assert!(!view(NoiseControlMode::AmbientSound).is_preview_sensitive());
+
+        let previewing = NoiseView {
+            is_previewing: true,
+            ..view(NoiseControlMode::AmbientSound)
+        };
+        assert!(previewing.is_preview_sensitive());
+    }
+
+    #[test]
+    fn shows_the_advanced_settings_the_model_supports() {
+        assert!(!view(NoiseControlMode::Off).is_advanced_visible());
+
+        let one_earbud = NoiseView {
+            one_earbud: Some(false),
+            ..view(NoiseControlMode::Off)
+        };
+        assert!(one_earbud.is_advanced_visible());
+
+        let ambient_during_calls = NoiseView {
+            ambient_during_calls: Some(true),
+            ..view(NoiseControlMode::Off)
+        };
+        assert!(ambient_during_calls.is_advanced_visible());
+    }
+
+    #[test]
+    fn checks_the_mode_the_buds_report() {
+        test_harness::run(|| {
+            let (page, _) = test_harness::launch::<PageNoiseModel>(init(NoiseControlMode::Off));
+            assert!(page.widgets().check_off.is_active());
+
+            test_harness::emit(
+                &page,
+                PageNoiseInput::ModeUpdate(NoiseControlMode::NoiseReduction),
+            );
+            let widgets = page.widgets();
+            assert!(widgets.check_noise.is_active());
+            assert!(!widgets.check_off.is_active());
+            assert!(!widgets.check_ambient.is_active());
+        });
+    }
+
+    #[test]
+    fn asks_for_the_mode_that_was_clicked() {
+        test_harness::run(|| {
+            let (page, outputs) =
+                test_harness::launch::<PageNoiseModel>(init(NoiseControlMode::Off));
+            outputs.borrow_mut().clear();
+
+            page.widgets().check_ambient.set_active(true);
+            test_harness::flush_events();
+            assert!(matches!(
+                outputs.borrow().last(),
+                Some(PageNoiseOutput::SetMode(NoiseControlMode::AmbientSound))
+            ));
+        });
+    }
+
+    #[test]
+    fn locks_the_preview_button_while_ambient_sound_is_on() {
+        test_harness::run(|| {
+            let (page, _) =
+                test_harness::launch::<PageNoiseModel>(init(NoiseControlMode::AmbientSound));
+            assert!(!page.widgets().preview_button.is_sensitive());
+
+            test_harness::emit(&page, PageNoiseInput::ModeUpdate(NoiseControlMode::Off));
+            assert!(page.widgets().preview_button.is_sensitive());
+        });
+    }
+
+    #[test]
+    fn switches_back_once_the_preview_ends() {
+        test_harness::run(|| {
+            let (page, outputs) =
+                test_harness::launch::<PageNoiseModel>(init(NoiseControlMode::NoiseReduction));
+            outputs.borrow_mut().clear();
+
+            test_harness::emit(&page, PageNoiseInput::PreviewStart);
+            assert!(matches!(
+                outputs.borrow().last(),
+                Some(PageNoiseOutput::SetMode(NoiseControlMode::AmbientSound))
+            ));
+            // The buds confirm the switch, which must not lock the button being held.
+            test_harness::emit(
+                &page,
+                PageNoiseInput::ModeUpdate(NoiseControlMode::AmbientSound),
+            );
+            assert!(page.widgets().preview_button.is_sensitive());
+
+            test_harness::emit(&page, PageNoiseInput::PreviewEnd);
+            assert!(matches!(
+                outputs.borrow().last(),
+                Some(PageNoiseOutput::SetMode(NoiseControlMode::NoiseReduction))
+            ));
+        });
+    }
+}
@@ -0,0 +1,182 @@
+use relm4::{
+    ComponentController, Controller,
+    prelude::{AsyncComponentController, AsyncController},
+};
+
+use crate::{
+    app::{
+        page_connection::PageConnectionModel,
+        page_manage::{PageId, PageManageModel},
+    },
+    define_page_enum,
+};
+
+define_page_enum!(Page {
+    Connection(AsyncController<PageConnectionModel>),
+    Manage(Controller<PageManageModel>),
+});
+
+/// A place in the window's navigation, from the device list down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// The device list, always at the bottom of the navigation stack.
+    Connection,
+    /// The page of the device being managed.
+    Manage,
+    /// A page opened from the device page.
+    Subpage(PageId),
+}
+
+/// Tells what a navigation did, so the app can react to it in one place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteChange {
+    pub from: Route,
+    pub to: Route,
+}
+
+/// Owns the pages of the navigation view and keeps the view in sync with them.
+///
+/// Subpages are owned by the device page, which caches them; only their widget is kept here.
+#[derive(Debug)]
+pub struct Router {
+    connect_page: AsyncController<PageConnectionModel>,
+    active_page: Option<Page>,
+    active_subpage: Option<(PageId, adw::NavigationPage)>,
+}
+
+impl Router {
+    pub fn new(connect_page: AsyncController<PageConnectionModel>) -> Self {
+        Self {
+            connect_page,
+            active_page: None,
+            active_subpage: None,
+        }
+    }
+
+    pub fn connect_page(&self) -> &AsyncController<PageConnectionModel> {
+        &self.connect_page
+    }
+
+    /// The device page, if one is open, even under a subpage.
+    pub fn manage_page(&self) -> Option<&Controller<PageManageModel>> {
+        match &self.active_page {
+            Some(Page::Manage(page)) => Some(page),
+            _ => None,
+        }
+    }
+
+    /// The route shown, i.e. the last one of `history`.
+    pub fn route(&self) -> Route {
+        *self.history().last().unwrap_or(&Route::Connection)
+    }
+
+    /// Routes from the device list to the shown one, as stacked in the navigation view.
+    pub fn history(&self) -> Vec<Route> {
+        let mut history = vec![Route::Connection];
+        if let Some(page) = &self.active_page {
+            history.push(match page {
+                Page::Connection(_) => Route::Connection,
+                Page::Manage(_) => Route::Manage,
+            });
+        }
+        if let Some((page_id, _)) = &self.active_subpage {
+            history.push(Route::Subpage(*page_id));
+        }
+        history
+    }
+
+    /// Shows `page` in place of the open one, dropping it along with its subpage.
+    pub fn replace(&mut self, page: Page) -> RouteChange {
+        let from = self.route();
+        self.active_subpage = None;
+        self.active_page = Some(page);
+        RouteChange {
+            from,
+            to: self.route(),
+        }
+    }
+
+    /// Shows a subpage of the device page, in place of the open one.
+    pub fn push_subpage(&mut self, page_id: PageId, page: adw::NavigationPage) -> RouteChange {
+        let from = self.route();
+        self.active_subpage = Some((page_id, page));
+        RouteChange {
+            from,
+            to: self.route(),
+        }
+    }
+
+    /// Closes the subpage, returning its widget so the device page can be told about it.
+    pub fn pop_subpage(&mut self) -> Option<(RouteChange, adw::NavigationPage)> {
+        let from = self.route();
+        let (_, page) = self.active_subpage.take()?;
+        let change = RouteChange {
+            from,
+            to: self.route(),
+        };
+        Some((change, page))
+    }
+
+    /// Goes back to the device list, dropping the open pages.
+    pub fn pop_to_root(&mut self) -> RouteChange {
+        let from = self.route();
+        // Dropping the page also drops any subpage controllers it owns.
+        self.active_subpage = None;
+        self.active_page = None;
+        RouteChange {
+            from,
+            to: Route::Connection,
+        }
+    }
+
+    /// Follows a page popped from the navigation view, e.g. with the back button.
+    ///
+    /// Returns `None` for pages the router already let go of.
+    pub fn popped(&mut self, popped_page: &adw::NavigationPage) -> Option<RouteChange> {
+        if self
+            .active_subpage
+            .as_ref()
+            .is_some_and(|(_, page)| page == popped_page)
+        {
+            return self.pop_subpage().map(|(change, _)| change);
+        }
+        if self
+            .active_page
+            .as_ref()
+            .is_some_and(|page| page.widget() == popped_page)
+        {
+            return Some(self.pop_to_root());
+        }
+        None
+    }
+
+    /// Pushes or pops pages of the navigation view until it shows the current route.
+    pub fn sync(&self, nav_view: &adw::NavigationView) {
+        let Some(page) = &self.active_page else {
+            nav_view.pop_to_page(self.connect_page.widget());
+            return;
+        };
+
+        let visible_page = nav_view.visible_page();
+        match &self.active_subpage {
+            Some((_, subpage)) => {
+                if visible_page.as_ref() != Some(page.widget())
+                    && visible_page.as_ref() != Some(subpage)
+                {
+                    nav_view.push(page.widget());
+                }
+                if nav_view.visible_page().as_ref() != Some(subpage) {
+                    nav_view.push(subpage);
+                }
+            }
+            None => {
+                if visible_page.as_ref() != Some(page.widget()) {
+                    // Pops the subpage when there's one, pushes the page otherwise.
+                    if !nav_view.pop_to_page(page.widget()) {
+                        nav_view.push(page.widget());
+                    }
+                }
+            }
+        }
+    }
+}
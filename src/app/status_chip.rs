@@ -0,0 +1,102 @@
+use gtk4::prelude::{BoxExt, WidgetExt};
+use relm4::{ComponentParts, ComponentSender, SimpleComponent};
+
+/// How much attention a chip asks for, shown through its style class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Severity {
+    #[default]
+    Normal,
+    /// Known but not current, e.g. a reading that wasn't updated for a while.
+    Dimmed,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn css_classes(self) -> &'static [&'static str] {
+        match self {
+            Self::Normal => &[],
+            Self::Dimmed => &["dim-label"],
+            Self::Warning => &["warning"],
+            Self::Error => &["error"],
+        }
+    }
+}
+
+/// Everything a chip shows, computed by its owner from the state it keeps.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusChipState {
+    pub icon_name: &'static str,
+    pub label: String,
+    pub tooltip: Option<String>,
+    pub severity: Severity,
+    /// Adds the pulsing charging indicator after the label.
+    pub is_charging: bool,
+    pub is_visible: bool,
+}
+
+/// A small icon and label pair telling one thing about the device, e.g. a battery level.
+#[derive(Debug)]
+pub struct StatusChip {
+    state: StatusChipState,
+}
+
+#[derive(Debug)]
+pub enum StatusChipInput {
+    Set(StatusChipState),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for StatusChip {
+    type Init = StatusChipState;
+    type Input = StatusChipInput;
+    type Output = ();
+
+    view! {
+        #[root]
+        gtk4::Box {
+            set_spacing: 4,
+            #[watch]
+            set_visible: model.state.is_visible,
+            #[watch]
+            set_tooltip_text: model.state.tooltip.as_deref(),
+            #[watch]
+            set_css_classes: model.state.severity.css_classes(),
+
+            gtk4::Image {
+                #[watch]
+                set_icon_name: Some(model.state.icon_name),
+            },
+
+            gtk4::Label {
+                #[watch]
+                set_label: &model.state.label,
+                add_css_class: "heading",
+            },
+
+            gtk4::Image {
+                set_icon_name: Some("battery-full-charging-symbolic"),
+                set_tooltip_text: Some("Charging"),
+                add_css_class: "charging",
+                #[watch]
+                set_visible: model.state.is_charging,
+            },
+        }
+    }
+
+    fn init(
+        state: Self::Init,
+        root: Self::Root,
+        _sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = StatusChip { state };
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+        match message {
+            StatusChipInput::Set(state) => self.state = state,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Runs GUI tests against real widgets.
+//!
+//! GTK may only be used from the thread that initialized it, while the test runner spreads
+//! tests over its own threads, so every GUI test runs on one GTK thread started on first use.
+//! The tests need a display, e.g. `xvfb-run cargo test`.
+
+use std::{
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+    sync::{Mutex, OnceLock, mpsc},
+    thread,
+};
+
+use relm4::{Component, ComponentController, Controller};
+
+use crate::lock_ext::LockOrRecover;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Sends jobs to the GTK thread.
+static GTK_THREAD: OnceLock<Mutex<mpsc::Sender<Job>>> = OnceLock::new();
+
+/// Outputs a component sent so far, oldest first.
+pub type Outputs<T> = Rc<RefCell<Vec<T>>>;
+
+/// Runs `test` on the GTK thread, failing the calling test if it panics.
+pub fn run(test: impl FnOnce() + Send + 'static) {
+    let (result_sender, result_receiver) = mpsc::channel();
+    let job: Job = Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(test));
+        // Drained after each job, so the next test doesn't see this one's events.
+        flush_events();
+        let _ = result_sender.send(result);
+    });
+    gtk_thread()
+        .lock_or_recover()
+        .send(job)
+        .expect("The GTK thread stopped");
+    match result_receiver.recv() {
+        Ok(Ok(())) => {}
+        Ok(Err(panic)) => panic::resume_unwind(panic),
+        Err(_) => panic!("The GTK thread stopped, likely as GTK failed to initialize"),
+    }
+}
+
+/// Launches `C` with `init`, keeping its outputs for the test to check.
+///
+/// Must be called from `run`.
+pub fn launch<C: Component>(init: C::Init) -> (Controller<C>, Outputs<C::Output>) {
+    let outputs = Outputs::default();
+    let controller = C::builder().launch(init).connect_receiver({
+        let outputs = outputs.clone();
+        move |_, output| outputs.borrow_mut().push(output)
+    });
+    flush_events();
+    (controller, outputs)
+}
+
+/// Sends `input` to the component and waits until it's handled, and the view updated.
+pub fn emit<C: Component>(controller: &Controller<C>, input: C::Input) {
+    controller.emit(input);
+    flush_events();
+}
+
+/// Handles the events waiting on the GTK thread, e.g. component updates and outputs.
+pub fn flush_events() {
+    let context = gtk4::glib::MainContext::default();
+    while context.iteration(false) {}
+}
+
+fn gtk_thread() -> &'static Mutex<mpsc::Sender<Job>> {
+    GTK_THREAD.get_or_init(|| {
+        let (job_sender, job_receiver) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("gtk".to_string())
+            .spawn(move || {
+                // Initializes GTK too, and the styles the libadwaita widgets expect.
+                if let Err(e) = adw::init() {
+                    eprintln!("Failed to initialize GTK, is there a display? {}", e);
+                    return;
+                }
+                for job in job_receiver {
+                    job();
+                }
+            })
+            .expect("Failed to start the GTK thread");
+        Mutex::new(job_sender)
+    })
+}
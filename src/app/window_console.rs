@@ -0,0 +1,203 @@
+use adw::prelude::AdwWindowExt;
+use gtk4::prelude::{
+    BoxExt, EntryBufferExtManual, EntryExt, GtkWindowExt, OrientableExt, TextBufferExt,
+    TextViewExt, WidgetExt,
+};
+use relm4::{ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+
+use crate::{
+    model::{
+        buds_message::BudsCommand,
+        util::{parse_hex, to_hex},
+    },
+    sender_ext::SendOrLog,
+};
+
+/// Lines kept in the log; the oldest ones are dropped past this.
+const MAX_LOG_LINES: i32 = 1000;
+
+/// A developer console to send raw frames or named commands to the device.
+///
+/// The log and history live as long as the app, so closing the window doesn't lose them.
+/// Messages received while it's closed aren't logged, though.
+#[derive(Debug)]
+pub struct WindowConsole {
+    entry_buffer: gtk4::EntryBuffer,
+    log_buffer: gtk4::TextBuffer,
+    history: Vec<String>,
+    /// Position in `history` while browsing it with the arrow keys.
+    history_position: Option<usize>,
+    is_visible: bool,
+}
+
+#[derive(Debug)]
+pub enum WindowConsoleInput {
+    Show,
+    Hide,
+    Submit,
+    HistoryPrevious,
+    HistoryNext,
+    /// Appends a line describing something received from the device, or a send error.
+    Log(String),
+}
+
+#[derive(Debug)]
+pub enum WindowConsoleOutput {
+    SendData(Vec<u8>),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for WindowConsole {
+    type Input = WindowConsoleInput;
+    type Output = WindowConsoleOutput;
+    type Init = ();
+
+    view! {
+        #[root]
+        adw::Window {
+            set_title: Some("Developer Console"),
+            set_default_width: 640,
+            set_default_height: 420,
+            set_hide_on_close: true,
+            #[watch]
+            set_visible: model.is_visible,
+            connect_close_request[sender] => move |_| {
+                sender.input(WindowConsoleInput::Hide);
+                gtk4::glib::Propagation::Proceed
+            },
+
+            #[wrap(Some)]
+            set_content = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {},
+
+                #[wrap(Some)]
+                set_content = &gtk4::Box {
+                    set_orientation: gtk4::Orientation::Vertical,
+                    set_spacing: 6,
+                    set_margin_all: 6,
+
+                    gtk4::ScrolledWindow {
+                        set_vexpand: true,
+
+                        gtk4::TextView {
+                            set_editable: false,
+                            set_cursor_visible: false,
+                            set_monospace: true,
+                            set_buffer: Some(&model.log_buffer),
+                        },
+                    },
+
+                    gtk4::Entry {
+                        set_buffer: &model.entry_buffer,
+                        set_placeholder_text: Some("Hex frame (FD 03 00 ...) or command (find on, noise anc, manager-info)"),
+                        add_css_class: "monospace",
+                        connect_activate => WindowConsoleInput::Submit,
+                        add_controller = gtk4::EventControllerKey {
+                            connect_key_pressed[sender] => move |_, key, _, _| {
+                                match key {
+                                    gtk4::gdk::Key::Up => sender.input(WindowConsoleInput::HistoryPrevious),
+                                    gtk4::gdk::Key::Down => sender.input(WindowConsoleInput::HistoryNext),
+                                    _ => return gtk4::glib::Propagation::Proceed,
+                                }
+                                gtk4::glib::Propagation::Stop
+                            },
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = WindowConsole {
+            entry_buffer: gtk4::EntryBuffer::default(),
+            log_buffer: gtk4::TextBuffer::default(),
+            history: Vec::new(),
+            history_position: None,
+            is_visible: false,
+        };
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            WindowConsoleInput::Show => self.is_visible = true,
+            WindowConsoleInput::Hide => self.is_visible = false,
+            WindowConsoleInput::Submit => {
+                let input = self.entry_buffer.text().trim().to_string();
+                if input.is_empty() {
+                    return;
+                }
+                self.entry_buffer.set_text("");
+                self.history_position = None;
+                if self.history.last() != Some(&input) {
+                    self.history.push(input.clone());
+                }
+
+                match parse_input(&input) {
+                    Ok(data) => {
+                        self.log(&format!("> {}", to_hex(&data)));
+                        sender
+                            .output_sender()
+                            .send_or_log(WindowConsoleOutput::SendData(data));
+                    }
+                    Err(err) => self.log(&format!("! {}", err)),
+                }
+            }
+            WindowConsoleInput::HistoryPrevious => {
+                let position = match self.history_position {
+                    Some(position) => position.saturating_sub(1),
+                    None if !self.history.is_empty() => self.history.len() - 1,
+                    None => return,
+                };
+                self.history_position = Some(position);
+                self.entry_buffer.set_text(&self.history[position]);
+            }
+            WindowConsoleInput::HistoryNext => {
+                let Some(position) = self.history_position else {
+                    return;
+                };
+                if position + 1 < self.history.len() {
+                    self.history_position = Some(position + 1);
+                    self.entry_buffer.set_text(&self.history[position + 1]);
+                } else {
+                    self.history_position = None;
+                    self.entry_buffer.set_text("");
+                }
+            }
+            WindowConsoleInput::Log(line) => {
+                if self.is_visible {
+                    self.log(&format!("< {}", line));
+                }
+            }
+        }
+    }
+}
+
+impl WindowConsole {
+    fn log(&self, line: &str) {
+        self.log_buffer
+            .insert(&mut self.log_buffer.end_iter(), &format!("{}\n", line));
+        // The buffer ends with an empty line after the last newline.
+        let excess = self.log_buffer.line_count() - 1 - MAX_LOG_LINES;
+        if excess > 0 {
+            if let Some(mut end) = self.log_buffer.iter_at_line(excess) {
+                self.log_buffer.delete(&mut self.log_buffer.start_iter(), &mut end);
+            }
+        }
+    }
+}
+
+/// Parses console input as a named command, falling back to a raw hex frame.
+fn parse_input(input: &str) -> Result<Vec<u8>, String> {
+    match BudsCommand::parse(input) {
+        Ok(command) => Ok(command.to_bytes()),
+        Err(command_err) => parse_hex(input).map_err(|_| command_err),
+    }
+}
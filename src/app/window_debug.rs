@@ -0,0 +1,543 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use adw::prelude::{AdwDialogExt, AdwWindowExt, AlertDialogExt};
+use bytes::Bytes;
+use galaxy_buds_rs::model::Model;
+use gtk4::gio::prelude::{FileExt, SettingsExt};
+use gtk4::prelude::{
+    AdjustmentExt, BoxExt, ButtonExt, EditableExt, GtkWindowExt, ListBoxRowExt, OrientableExt,
+    TextBufferExt, TextViewExt, ToggleButtonExt, WidgetExt,
+};
+use relm4::{
+    ComponentParts, ComponentSender, FactorySender, RelmWidgetExt, SimpleComponent,
+    prelude::{DynamicIndex, FactoryComponent, FactoryVecDeque},
+};
+
+use tracing::{debug, error};
+
+use crate::{
+    btsnoop,
+    consts::{
+        INSPECTOR_AUTOSCROLL_KEY, INSPECTOR_DECODER_KEY, INSPECTOR_HEIGHT_KEY,
+        INSPECTOR_ID_FILTER_KEY, INSPECTOR_PANE_POSITION_KEY, INSPECTOR_WIDTH_KEY,
+    },
+    model::{
+        buds_message::{BudsMessage, DECODE_TABLE},
+        session_info::SessionInfo,
+        unknown_report::{MAX_REPORT_SAMPLES, UnknownIdReport},
+        util::to_hex,
+    },
+    settings,
+};
+
+/// Maximum number of frames kept in the inspector; older ones are dropped first.
+const MAX_FRAMES: usize = 500;
+
+/// Layout and filters of the inspector, saved when it's closed.
+#[derive(Debug, Clone, PartialEq)]
+struct InspectorState {
+    width: i32,
+    height: i32,
+    /// Height of the frame list, above the decoded frame.
+    pane_position: i32,
+    /// Ids of the frames to show, as typed, e.g. "61, 0x60". Empty shows every frame.
+    id_filter: String,
+    is_autoscroll: bool,
+    /// Name of the decoder in `DECODE_TABLE`.
+    decoder: String,
+}
+
+impl InspectorState {
+    fn load(settings: &gtk4::gio::Settings) -> Self {
+        Self {
+            width: settings.int(INSPECTOR_WIDTH_KEY),
+            height: settings.int(INSPECTOR_HEIGHT_KEY),
+            pane_position: settings.int(INSPECTOR_PANE_POSITION_KEY),
+            id_filter: settings.string(INSPECTOR_ID_FILTER_KEY).to_string(),
+            is_autoscroll: settings.boolean(INSPECTOR_AUTOSCROLL_KEY),
+            decoder: settings.string(INSPECTOR_DECODER_KEY).to_string(),
+        }
+    }
+
+    fn save(&self, settings: &gtk4::gio::Settings) {
+        let result = settings
+            .set_int(INSPECTOR_WIDTH_KEY, self.width)
+            .and_then(|_| settings.set_int(INSPECTOR_HEIGHT_KEY, self.height))
+            .and_then(|_| settings.set_int(INSPECTOR_PANE_POSITION_KEY, self.pane_position))
+            .and_then(|_| settings.set_string(INSPECTOR_ID_FILTER_KEY, &self.id_filter))
+            .and_then(|_| settings.set_boolean(INSPECTOR_AUTOSCROLL_KEY, self.is_autoscroll))
+            .and_then(|_| settings.set_string(INSPECTOR_DECODER_KEY, &self.decoder));
+        if let Err(e) = result {
+            error!("Failed to save the inspector state: {}", e);
+        }
+    }
+
+    /// Position of `decoder` in `DECODE_TABLE`, or the first one if it's gone.
+    fn decoder_index(&self) -> usize {
+        DECODE_TABLE
+            .iter()
+            .position(|(_, name, _)| *name == self.decoder)
+            .unwrap_or_default()
+    }
+}
+
+/// The ids typed in an id filter, in hex, skipping anything that isn't one.
+fn parse_ids(id_filter: &str) -> Vec<u8> {
+    id_filter
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|id| {
+            let id = id.trim_start_matches("0x").trim_start_matches("0X");
+            u8::from_str_radix(id, 16).ok()
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct FrameRow {
+    frame: Bytes,
+    is_shown: bool,
+}
+
+#[derive(Debug, Clone)]
+enum FrameRowInput {
+    /// Shows the row only if its id is one of these, or if there are none.
+    Filter(Vec<u8>),
+}
+
+#[relm4::factory]
+impl FactoryComponent for FrameRow {
+    /// The frame, and the ids it's filtered by.
+    type Init = (Bytes, Vec<u8>);
+    type Input = FrameRowInput;
+    type Output = ();
+    type CommandOutput = ();
+    type ParentWidget = gtk4::ListBox;
+
+    view! {
+        // A row rather than a bare label, so hiding it hides the whole row.
+        #[root]
+        gtk4::ListBoxRow {
+            #[watch]
+            set_visible: self.is_shown,
+
+            gtk4::Label {
+                set_label: &to_hex(&self.frame),
+                set_xalign: 0.0,
+                set_wrap: true,
+                set_wrap_mode: gtk4::pango::WrapMode::Char,
+                set_margin_all: 6,
+                add_css_class: "monospace",
+            },
+        }
+    }
+
+    fn init_model(
+        (frame, ids): Self::Init,
+        _index: &DynamicIndex,
+        _sender: FactorySender<Self>,
+    ) -> Self {
+        let is_shown = Self::matches(&frame, &ids);
+        Self { frame, is_shown }
+    }
+
+    fn update(&mut self, message: Self::Input, _sender: FactorySender<Self>) {
+        match message {
+            FrameRowInput::Filter(ids) => self.is_shown = Self::matches(&self.frame, &ids),
+        }
+    }
+}
+
+impl FrameRow {
+    fn matches(frame: &Bytes, ids: &[u8]) -> bool {
+        ids.is_empty() || frame.get(3).is_some_and(|id| ids.contains(id))
+    }
+}
+
+#[derive(Debug)]
+pub struct WindowDebug {
+    window: adw::Window,
+    settings: gtk4::gio::Settings,
+    /// Set once the widgets are built.
+    paned: gtk4::Paned,
+    frame_scroller: gtk4::ScrolledWindow,
+    id_filter: String,
+    is_autoscroll: bool,
+    frames: FactoryVecDeque<FrameRow>,
+    /// Copy of the frames in `frames`, so they can be decoded without going through the factory.
+    ///
+    /// Each comes with the session it was received in, `None` for imported ones.
+    raw_frames: VecDeque<(Bytes, Option<SessionInfo>)>,
+    selected_frame: Option<usize>,
+    selected_decoder: usize,
+    decoded: String,
+    is_visible: bool,
+}
+
+#[derive(Debug)]
+pub enum WindowDebugInput {
+    Show,
+    Hide,
+    AddFrame {
+        frame: Bytes,
+        session: Option<SessionInfo>,
+    },
+    SelectFrame(Option<usize>),
+    DecodeAs(usize),
+    FilterIds(String),
+    SetAutoscroll(bool),
+    ImportCapture(PathBuf),
+    /// Shows a report of the selected frame's id, to be filed upstream.
+    ReportUnknownId,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for WindowDebug {
+    type Input = WindowDebugInput;
+    type Output = ();
+    type Init = ();
+
+    view! {
+        #[root]
+        adw::Window {
+            set_title: Some("Protocol Inspector"),
+            set_default_width: state.width,
+            set_default_height: state.height,
+            set_hide_on_close: true,
+            #[watch]
+            set_visible: model.is_visible,
+            connect_close_request[sender] => move |_| {
+                sender.input(WindowDebugInput::Hide);
+                gtk4::glib::Propagation::Proceed
+            },
+
+            #[wrap(Some)]
+            set_content = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    pack_start = &gtk4::Button {
+                        set_icon_name: "document-open-symbolic",
+                        set_tooltip_text: Some("Import btsnoop capture"),
+                        connect_clicked[sender, root] => move |_| {
+                            let sender = sender.clone();
+                            gtk4::FileDialog::builder()
+                                .title("Import btsnoop Capture")
+                                .build()
+                                .open(Some(&root), gtk4::gio::Cancellable::NONE, move |result| {
+                                    if let Some(path) = result.ok().and_then(|file| file.path()) {
+                                        sender.input(WindowDebugInput::ImportCapture(path));
+                                    }
+                                });
+                        },
+                    },
+                    pack_end = &gtk4::Button {
+                        set_icon_name: "mail-send-symbolic",
+                        set_tooltip_text: Some("Report this message id"),
+                        #[watch]
+                        set_sensitive: model.selected_unknown_id().is_some(),
+                        connect_clicked => WindowDebugInput::ReportUnknownId,
+                    },
+                    pack_end = &gtk4::ToggleButton {
+                        set_icon_name: "go-bottom-symbolic",
+                        set_tooltip_text: Some("Scroll to new frames"),
+                        set_active: state.is_autoscroll,
+                        connect_toggled[sender] => move |button| {
+                            sender.input(WindowDebugInput::SetAutoscroll(button.is_active()));
+                        },
+                    },
+                },
+
+                add_top_bar = &gtk4::SearchEntry {
+                    set_placeholder_text: Some("Filter by message id, e.g. 61, 60"),
+                    set_margin_all: 6,
+                    set_text: &state.id_filter,
+                    connect_search_changed[sender] => move |entry| {
+                        sender.input(WindowDebugInput::FilterIds(entry.text().to_string()));
+                    },
+                },
+
+                #[wrap(Some)]
+                #[name = "paned"]
+                set_content = &gtk4::Paned {
+                    set_orientation: gtk4::Orientation::Vertical,
+                    set_position: state.pane_position,
+
+                    #[wrap(Some)]
+                    #[name = "frame_scroller"]
+                    set_start_child = &gtk4::ScrolledWindow {
+                        #[local_ref]
+                        frame_list -> gtk4::ListBox {
+                            set_selection_mode: gtk4::SelectionMode::Single,
+                            connect_row_selected[sender] => move |_, row| {
+                                let index = row.map(|row| row.index() as usize);
+                                sender.input(WindowDebugInput::SelectFrame(index));
+                            },
+                        },
+                    },
+
+                    #[wrap(Some)]
+                    set_end_child = &gtk4::Box {
+                        set_orientation: gtk4::Orientation::Vertical,
+                        set_spacing: 6,
+                        set_margin_all: 6,
+
+                        gtk4::Box {
+                            set_spacing: 6,
+
+                            gtk4::Label {
+                                set_label: "Decode as",
+                            },
+                            gtk4::DropDown {
+                                set_model: Some(&gtk4::StringList::new(&decoder_names)),
+                                set_selected: model.selected_decoder as u32,
+                                connect_selected_notify[sender] => move |dropdown| {
+                                    sender.input(WindowDebugInput::DecodeAs(dropdown.selected() as usize));
+                                },
+                            },
+                        },
+
+                        gtk4::ScrolledWindow {
+                            set_vexpand: true,
+
+                            gtk4::TextView {
+                                set_editable: false,
+                                set_monospace: true,
+                                #[wrap(Some)]
+                                set_buffer = &gtk4::TextBuffer {
+                                    #[watch]
+                                    set_text: &model.decoded,
+                                },
+                            },
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let frames = FactoryVecDeque::builder()
+            .launch(gtk4::ListBox::default())
+            .detach();
+
+        let settings = settings::get_settings();
+        let state = InspectorState::load(&settings);
+        let mut model = WindowDebug {
+            window: root.clone(),
+            settings,
+            paned: gtk4::Paned::default(),
+            frame_scroller: gtk4::ScrolledWindow::default(),
+            id_filter: state.id_filter.clone(),
+            is_autoscroll: state.is_autoscroll,
+            frames,
+            raw_frames: VecDeque::new(),
+            selected_frame: None,
+            selected_decoder: state.decoder_index(),
+            decoded: String::new(),
+            is_visible: false,
+        };
+
+        let decoder_names: Vec<&str> = DECODE_TABLE.iter().map(|(_, name, _)| *name).collect();
+        let frame_list = model.frames.widget();
+        let widgets = view_output!();
+        model.paned = widgets.paned.clone();
+        model.frame_scroller = widgets.frame_scroller.clone();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, _sender: ComponentSender<Self>) {
+        match message {
+            WindowDebugInput::Show => self.is_visible = true,
+            WindowDebugInput::Hide => {
+                self.is_visible = false;
+                self.state().save(&self.settings);
+            }
+            WindowDebugInput::AddFrame { frame, session } => self.add_frame(frame, session),
+            WindowDebugInput::SelectFrame(index) => {
+                self.selected_frame = index;
+                self.decode();
+            }
+            WindowDebugInput::DecodeAs(index) => {
+                self.selected_decoder = index;
+                self.decode();
+            }
+            WindowDebugInput::FilterIds(id_filter) => {
+                self.id_filter = id_filter;
+                self.frames.broadcast(FrameRowInput::Filter(parse_ids(&self.id_filter)));
+            }
+            WindowDebugInput::SetAutoscroll(is_autoscroll) => self.is_autoscroll = is_autoscroll,
+            WindowDebugInput::ImportCapture(path) => {
+                let result = std::fs::read(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|data| btsnoop::parse_capture(&data));
+                self.decoded = match result {
+                    Ok(report) => {
+                        let summary = report.summary();
+                        for frame in report.frames {
+                            self.add_frame(frame, None);
+                        }
+                        format!("Imported {}\n\n{}", path.display(), summary)
+                    }
+                    Err(err) => {
+                        error!("Failed to import {}: {}", path.display(), err);
+                        format!("Failed to import {}: {}", path.display(), err)
+                    }
+                };
+            }
+            WindowDebugInput::ReportUnknownId => {
+                if let Some(report) = self.unknown_id_report() {
+                    self.show_report(report);
+                }
+            }
+        }
+    }
+}
+
+impl WindowDebug {
+    fn add_frame(&mut self, frame: Bytes, session: Option<SessionInfo>) {
+        let ids = parse_ids(&self.id_filter);
+        let mut guard = self.frames.guard();
+        if guard.len() >= MAX_FRAMES {
+            guard.pop_front();
+            self.raw_frames.pop_front();
+        }
+        guard.push_back((frame.clone(), ids));
+        drop(guard);
+        self.raw_frames.push_back((frame, session));
+
+        if self.is_autoscroll {
+            // After the row is laid out, so the list has grown to include it.
+            let adjustment = self.frame_scroller.vadjustment();
+            gtk4::glib::idle_add_local_once(move || {
+                adjustment.set_value(adjustment.upper() - adjustment.page_size());
+            });
+        }
+    }
+
+    /// The current layout and filters, to be saved.
+    fn state(&self) -> InspectorState {
+        let (width, height) = self.window.default_size();
+        InspectorState {
+            width,
+            height,
+            pane_position: self.paned.position(),
+            id_filter: self.id_filter.clone(),
+            is_autoscroll: self.is_autoscroll,
+            decoder: DECODE_TABLE
+                .get(self.selected_decoder)
+                .map(|(_, name, _)| name.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Decodes the selected frame with the selected decoder.
+    fn decode(&mut self) {
+        let frame = self
+            .selected_frame
+            .and_then(|i| self.raw_frames.get(i))
+            .map(|(frame, _)| frame);
+        let decoder = DECODE_TABLE.get(self.selected_decoder);
+
+        self.decoded = match (frame, decoder) {
+            (Some(frame), Some((id, name, decoder))) => {
+                let header = format!(
+                    "Frame id: {}\nDecoded as: {} ({})\n\n",
+                    frame.get(3).copied().unwrap_or_default(),
+                    name,
+                    id
+                );
+                match BudsMessage::decode_as(frame, *decoder) {
+                    Ok(fields) => header + &fields,
+                    Err(err) => header + &err,
+                }
+            }
+            _ => String::new(),
+        };
+    }
+
+    /// The id of the selected frame, if the app can't parse messages with it.
+    fn selected_unknown_id(&self) -> Option<u8> {
+        let (frame, _) = self.raw_frames.get(self.selected_frame?)?;
+        // Only the id matters here, which is in the same place for every model.
+        match BudsMessage::from_bytes(frame, Model::BudsLive)? {
+            BudsMessage::Unknown { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Gathers the latest frames with the selected frame's id into a report.
+    fn unknown_id_report(&self) -> Option<UnknownIdReport> {
+        let id = self.selected_unknown_id()?;
+        let mut matching = self
+            .raw_frames
+            .iter()
+            .rev()
+            .filter(|(frame, _)| frame.get(3) == Some(&id))
+            .take(MAX_REPORT_SAMPLES)
+            .collect::<Vec<_>>();
+        matching.reverse();
+
+        Some(UnknownIdReport {
+            id,
+            session: matching.iter().find_map(|(_, session)| *session),
+            samples: matching.into_iter().map(|(frame, _)| frame.clone()).collect(),
+        })
+    }
+
+    /// Walks through filing the report: shows what will be sent and lets it be opened as a
+    /// prefilled issue, or copied to be pasted somewhere else.
+    fn show_report(&self, report: UnknownIdReport) {
+        let markdown = report.markdown();
+        let dialog = adw::AlertDialog::new(
+            Some(&report.title()),
+            Some(&format!(
+                "The app can't read these messages yet. Reporting them helps support them.\n\n\
+                 {} samples will be included. Check the report for anything you don't want to \
+                 share, and add the firmware version if you know it.",
+                report.samples.len()
+            )),
+        );
+
+        let text_view = gtk4::TextView::builder()
+            .editable(false)
+            .monospace(true)
+            .wrap_mode(gtk4::WrapMode::WordChar)
+            .build();
+        text_view.buffer().set_text(&markdown);
+        let scrolled = gtk4::ScrolledWindow::builder()
+            .child(&text_view)
+            .min_content_height(200)
+            .build();
+        dialog.set_extra_child(Some(&scrolled));
+
+        dialog.add_responses(&[
+            ("cancel", "Cancel"),
+            ("copy", "Copy Markdown"),
+            ("open", "Open Issue"),
+        ]);
+        dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("open"));
+        dialog.set_close_response("cancel");
+        dialog.connect_response(Some("copy"), move |dialog, _| {
+            dialog.clipboard().set_text(&markdown);
+        });
+        let url = report.issue_url();
+        let window = self.window.clone();
+        dialog.connect_response(Some("open"), move |_, _| {
+            gtk4::UriLauncher::new(&url).launch(
+                Some(&window),
+                gtk4::gio::Cancellable::NONE,
+                |result| {
+                    if let Err(e) = result {
+                        error!("Failed to open the issue page: {}", e);
+                    }
+                },
+            );
+        });
+        debug!(id = report.id, "Reporting unknown message id");
+        dialog.present(Some(&self.window));
+    }
+}
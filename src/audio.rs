@@ -0,0 +1,218 @@
+//! Integration with the system audio server, through `pactl`.
+//!
+//! `pactl` talks to both PulseAudio and PipeWire (via `pipewire-pulse`), which saves
+//! linking against either.
+
+use std::{io, time::Duration};
+
+use serde_json::Value;
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// How many times to look for a sink or card, as they only show up once the audio profile connects.
+const FIND_ATTEMPTS: u32 = 5;
+const FIND_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// The system audio sink that plays through a Bluetooth device.
+#[derive(Debug, Clone)]
+pub struct AudioSink {
+    name: String,
+}
+
+impl AudioSink {
+    /// Finds the sink of the Bluetooth device with the given address, waiting a bit for it to show up.
+    ///
+    /// Bluetooth sinks are named after the address on both audio servers, e.g.
+    /// `bluez_output.AA_BB_CC_DD_EE_FF.1` or `bluez_sink.AA_BB_CC_DD_EE_FF.a2dp_sink`.
+    pub async fn find(address: &str) -> Option<Self> {
+        let name = find_object("sinks", address).await?;
+        Some(Self { name })
+    }
+
+    /// The volume of the sink in percent, from its first channel.
+    pub async fn volume(&self) -> io::Result<u32> {
+        // e.g. "Volume: front-left: 32768 /  50% / -18.06 dB,   front-right: ..."
+        let output = pactl(&["get-sink-volume", &self.name]).await?;
+        output
+            .split('/')
+            .nth(1)
+            .and_then(|percent| percent.trim().trim_end_matches('%').parse().ok())
+            .ok_or_else(|| io::Error::other(format!("Unexpected volume output: {}", output)))
+    }
+
+    pub async fn set_volume(&self, percent: u32) -> io::Result<()> {
+        pactl(&["set-sink-volume", &self.name, &format!("{}%", percent)]).await?;
+        Ok(())
+    }
+}
+
+/// The card of a Bluetooth device, which switches between its audio profiles.
+#[derive(Debug, Clone)]
+pub struct AudioCard {
+    name: String,
+}
+
+impl AudioCard {
+    /// Finds the card of the Bluetooth device with the given address, e.g.
+    /// `bluez_card.AA_BB_CC_DD_EE_FF`, waiting a bit for it to show up.
+    pub async fn find(address: &str) -> Option<Self> {
+        let name = find_object("cards", address).await?;
+        Some(Self { name })
+    }
+
+    pub async fn active_profile(&self) -> io::Result<String> {
+        self.description()
+            .await?
+            .get("active_profile")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| io::Error::other(format!("No active profile for {}", self.name)))
+    }
+
+    /// Switches to the A2DP profile, which drops the microphone for better playback quality.
+    pub async fn use_high_fidelity_profile(&self) -> io::Result<()> {
+        let description = self.description().await?;
+        let profile = high_fidelity_profile(&description)
+            .ok_or_else(|| io::Error::other(format!("No A2DP profile for {}", self.name)))?;
+        pactl(&["set-card-profile", &self.name, profile]).await?;
+        Ok(())
+    }
+
+    /// The entry of `pactl -f json list cards` about this card.
+    async fn description(&self) -> io::Result<Value> {
+        let output = pactl(&["-f", "json", "list", "cards"]).await?;
+        let cards: Value = serde_json::from_str(&output)
+            .map_err(|e| io::Error::other(format!("Unexpected card list: {}", e)))?;
+        find_card(cards, &self.name)
+            .ok_or_else(|| io::Error::other(format!("Card {} not found", self.name)))
+    }
+
+    /// Whether the card uses the hands-free profile, which is only active during calls.
+    ///
+    /// PipeWire names it e.g. `headset-head-unit-msbc`, PulseAudio `handsfree_head_unit`.
+    pub async fn is_in_call(&self) -> io::Result<bool> {
+        let profile = self.active_profile().await?;
+        Ok(profile.starts_with("headset") || profile.starts_with("handsfree"))
+    }
+}
+
+/// Finds the name of the Bluetooth sink or card (`kind`) of a device by its address.
+///
+/// Retries for a bit, as they only show up once the audio profile connects.
+async fn find_object(kind: &str, address: &str) -> Option<String> {
+    let needle = address.replace(':', "_");
+    for attempt in 1..=FIND_ATTEMPTS {
+        match pactl(&["list", "short", kind]).await {
+            Ok(objects) => {
+                // Each line starts with the id and the name, separated by tabs.
+                let name = objects
+                    .lines()
+                    .filter_map(|line| line.split('\t').nth(1))
+                    .find(|name| name.starts_with("bluez_") && name.contains(&needle));
+                if let Some(name) = name {
+                    debug!(name, "Found audio {}", kind);
+                    return Some(name.to_string());
+                }
+            }
+            Err(e) => {
+                warn!("Failed to list audio {}: {}", kind, e);
+                return None;
+            }
+        }
+        if attempt < FIND_ATTEMPTS {
+            tokio::time::sleep(FIND_RETRY_DELAY).await;
+        }
+    }
+    debug!(address, "No audio {} for device", kind);
+    None
+}
+
+/// Takes the card named `name` out of the JSON card list.
+fn find_card(cards: Value, name: &str) -> Option<Value> {
+    let Value::Array(cards) = cards else {
+        return None;
+    };
+    cards
+        .into_iter()
+        .find(|card| card.get("name").and_then(Value::as_str) == Some(name))
+}
+
+/// Picks the A2DP profile of a card to switch to, the available one with the highest priority.
+///
+/// PipeWire lists one per codec, e.g. `a2dp-sink-sbc` and `a2dp-sink-aac`, PulseAudio only
+/// `a2dp_sink`.
+fn high_fidelity_profile(card: &Value) -> Option<&str> {
+    card.get("profiles")?
+        .as_object()?
+        .iter()
+        .filter(|(name, profile)| {
+            let is_available = profile.get("available").and_then(Value::as_bool) != Some(false);
+            name.starts_with("a2dp") && is_available
+        })
+        .max_by_key(|(_, profile)| profile.get("priority").and_then(Value::as_u64))
+        .map(|(name, _)| name.as_str())
+}
+
+/// Runs `pactl` with the given arguments and returns its standard output.
+///
+/// Runs it in the C locale, as parts of the output are translated otherwise.
+async fn pactl(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "pactl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn card() -> Value {
+        json!({
+            "name": "bluez_card.AA_BB_CC_DD_EE_FF",
+            "active_profile": "headset-head-unit",
+            "profiles": {
+                "off": { "description": "Off", "priority": 0, "available": true },
+                "a2dp-sink-sbc": { "description": "SBC", "priority": 18, "available": true },
+                "a2dp-sink-aac": { "description": "AAC", "priority": 19, "available": true },
+                "a2dp-sink-ldac": { "description": "LDAC", "priority": 20, "available": false },
+                "headset-head-unit": { "description": "HSP/HFP", "priority": 1, "available": true },
+            },
+        })
+    }
+
+    #[test]
+    fn finds_a_card_by_name() {
+        let cards = json!([{ "name": "alsa_card.pci" }, card()]);
+        let card = find_card(cards, "bluez_card.AA_BB_CC_DD_EE_FF").unwrap();
+        assert_eq!(card["active_profile"], "headset-head-unit");
+    }
+
+    #[test]
+    fn doesnt_find_a_missing_card() {
+        assert!(find_card(json!([card()]), "bluez_card.11_22_33_44_55_66").is_none());
+        assert!(find_card(json!({}), "bluez_card.AA_BB_CC_DD_EE_FF").is_none());
+    }
+
+    #[test]
+    fn picks_the_available_a2dp_profile_with_the_highest_priority() {
+        assert_eq!(high_fidelity_profile(&card()), Some("a2dp-sink-aac"));
+    }
+
+    #[test]
+    fn picks_no_profile_without_a2dp() {
+        let card = json!({ "profiles": { "off": { "priority": 0, "available": true } } });
+        assert_eq!(high_fidelity_profile(&card), None);
+    }
+}
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+/// Largest share of a delay that jitter takes off.
+const MAX_JITTER: f64 = 0.25;
+
+/// Exponential backoff between retries: the delay doubles after each attempt, up to `max`.
+///
+/// Each delay is shortened by a random amount of up to a quarter, so that retries started
+/// together, e.g. after a Bluetooth adapter reset, don't keep happening at the same time.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    /// Delays handed out since the last reset.
+    attempt: u32,
+    /// Source of the jitter, returning a number in `0.0..1.0`.
+    jitter: fn() -> f64,
+}
+
+impl Backoff {
+    pub const fn new(initial: Duration, max: Duration) -> Self {
+        Self::with_jitter(initial, max, gtk4::glib::random_double)
+    }
+
+    /// Like `new`, taking the jitter from `jitter` instead, e.g. a fixed value in tests.
+    pub const fn with_jitter(initial: Duration, max: Duration, jitter: fn() -> f64) -> Self {
+        Self {
+            initial,
+            max,
+            attempt: 0,
+            jitter,
+        }
+    }
+
+    /// Number of retries since the last reset, starting at 1 for the first retry.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// The delay to wait before the next retry, which counts as one more attempt.
+    pub fn next_delay(&mut self) -> Duration {
+        // Anything past 2^16 times the initial delay is over any sensible cap anyway.
+        let factor = 1 << self.attempt.min(16);
+        self.attempt += 1;
+        let delay = self.initial.saturating_mul(factor).min(self.max);
+        delay.mul_f64(1.0 - (self.jitter)() * MAX_JITTER)
+    }
+
+    /// Starts over from the initial delay, e.g. after an attempt succeeded.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INITIAL: Duration = Duration::from_secs(2);
+    const MAX: Duration = Duration::from_secs(60);
+
+    fn without_jitter() -> Backoff {
+        Backoff::with_jitter(INITIAL, MAX, || 0.0)
+    }
+
+    fn delays(backoff: &mut Backoff, count: usize) -> Vec<u64> {
+        (0..count)
+            .map(|_| backoff.next_delay().as_secs())
+            .collect()
+    }
+
+    #[test]
+    fn doubles_the_delay_after_each_attempt() {
+        let mut backoff = without_jitter();
+        assert_eq!(delays(&mut backoff, 4), [2, 4, 8, 16]);
+        assert_eq!(backoff.attempt(), 4);
+    }
+
+    #[test]
+    fn caps_the_delay() {
+        let mut backoff = without_jitter();
+        assert_eq!(delays(&mut backoff, 7), [2, 4, 8, 16, 32, 60, 60]);
+    }
+
+    #[test]
+    fn stays_capped_after_many_attempts() {
+        let mut backoff = without_jitter();
+        delays(&mut backoff, 100);
+        assert_eq!(backoff.next_delay(), MAX);
+    }
+
+    #[test]
+    fn starts_over_after_a_reset() {
+        let mut backoff = without_jitter();
+        delays(&mut backoff, 3);
+        backoff.reset();
+        assert_eq!(backoff.attempt(), 0);
+        assert_eq!(delays(&mut backoff, 2), [2, 4]);
+    }
+
+    #[test]
+    fn jitter_shortens_the_delay_by_up_to_a_quarter() {
+        let mut backoff = Backoff::with_jitter(INITIAL, MAX, || 0.5);
+        assert_eq!(backoff.next_delay(), Duration::from_millis(1750));
+
+        // Just under 1.0, the most the source returns.
+        let mut backoff = Backoff::with_jitter(INITIAL, MAX, || 0.999_999);
+        assert!(backoff.next_delay() > INITIAL.mul_f64(0.75));
+    }
+}
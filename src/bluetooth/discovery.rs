@@ -0,0 +1,133 @@
+use bluer::{Address, Session};
+use futures::{future, join};
+use galaxy_buds_rs::model::Model;
+use tracing::debug;
+
+use crate::model::device_info::{DeviceInfo, matching_spp_uuid};
+
+/// Major and minor classes of loudspeakers in the Bluetooth Class of Device field.
+const CLASS_MAJOR_AUDIO: u32 = 0x04;
+const CLASS_MINOR_LOUDSPEAKER: u32 = 0x05;
+
+/// Whether the app can manage a device that exposes the Galaxy Buds SPP service.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Support {
+    Supported,
+    /// Other Samsung audio devices, e.g. some speakers, expose the same service but don't speak
+    /// the Buds protocol.
+    Unsupported { reason: &'static str },
+}
+
+/// A known Bluetooth device that exposes the Galaxy Buds SPP service.
+#[derive(Debug, Clone)]
+pub struct DiscoveredDevice {
+    pub info: DeviceInfo,
+    /// Best guess of the model, based on the device name.
+    pub model: Model,
+    pub support: Support,
+}
+
+impl DiscoveredDevice {
+    async fn from_device(device: bluer::Device) -> Self {
+        let (class, info) = join!(device.class(), DeviceInfo::from_device(device.clone()));
+        let class = class.ok().flatten();
+        let model = info.model();
+        let support = classify(&info.name, class);
+
+        Self {
+            info,
+            model,
+            support,
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.support == Support::Supported
+    }
+}
+
+/// Returns the devices known to the default adapter that match the Galaxy Buds SPP UUID.
+pub async fn discover_galaxy_buds() -> bluer::Result<Vec<DiscoveredDevice>> {
+    let session = Session::new().await?;
+    let adapter = session.default_adapter().await?;
+    adapter.set_powered(true).await?;
+
+    // Get all known device addresses and create a future to check each one.
+    let device_addrs = adapter.device_addresses().await?;
+    let check_futures = device_addrs
+        .into_iter()
+        .filter_map(|addr| adapter.device(addr).ok())
+        .map(|device| async move {
+            // Check for the specific UUID. If found, return the device.
+            if has_buds_service(&device).await {
+                Some(DiscoveredDevice::from_device(device).await)
+            } else {
+                None
+            }
+        });
+
+    // Run all checks concurrently and filter out the `None` results.
+    let found_devices: Vec<DiscoveredDevice> = future::join_all(check_futures)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+
+    // Log the found devices.
+    for device in &found_devices {
+        debug!(device = ?device, "Found device");
+    }
+
+    Ok(found_devices)
+}
+
+/// Checks whether the device known to the default adapter under `address` is a pair of buds.
+///
+/// Returns `None` if the adapter doesn't know the address at all.
+pub async fn is_galaxy_buds(address: &str) -> bluer::Result<Option<bool>> {
+    let Ok(address) = address.parse::<Address>() else {
+        return Ok(None);
+    };
+    let session = Session::new().await?;
+    let adapter = session.default_adapter().await?;
+    if !adapter.device_addresses().await?.contains(&address) {
+        return Ok(None);
+    }
+
+    let device = adapter.device(address)?;
+    if !has_buds_service(&device).await {
+        return Ok(Some(false));
+    }
+    Ok(Some(DiscoveredDevice::from_device(device).await.is_supported()))
+}
+
+/// Tells the buds apart from the other devices exposing their SPP service.
+///
+/// Every Buds model has "Buds" in its name, so that's what is checked. The device class only
+/// makes the reason more specific.
+fn classify(name: &str, class: Option<u32>) -> Support {
+    if name.to_lowercase().contains("buds") {
+        return Support::Supported;
+    }
+
+    let is_loudspeaker = class.is_some_and(|class| {
+        (class >> 8) & 0x1f == CLASS_MAJOR_AUDIO && (class >> 2) & 0x3f == CLASS_MINOR_LOUDSPEAKER
+    });
+    Support::Unsupported {
+        reason: if is_loudspeaker {
+            "Speakers aren't supported"
+        } else {
+            "Not a pair of Galaxy Buds"
+        },
+    }
+}
+
+/// Whether the device advertises one of the SPP services of the buds.
+pub(super) async fn has_buds_service(device: &bluer::Device) -> bool {
+    let (uuids, name) = join!(device.uuids(), device.name());
+    let name = name.ok().flatten().unwrap_or_default();
+    match uuids {
+        Ok(Some(uuids)) => matching_spp_uuid(&uuids, &name).is_some(),
+        _ => false,
+    }
+}
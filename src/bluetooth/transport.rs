@@ -0,0 +1,78 @@
+//! Picks the device object to open the SPP channel on.
+//!
+//! With LE Audio enabled, which BlueZ still hides behind `Experimental = true`, newer buds can
+//! show up as two device objects: one for classic BR/EDR and one for LE, with a random address.
+//! RFCOMM only works over BR/EDR, so the LE one can't carry the channel.
+
+use bluer::{AddressType, Device, Session, Uuid};
+use tracing::{debug, info};
+
+use super::discovery::has_buds_service;
+
+/// Published Audio Capabilities Service, which every LE Audio device exposes.
+const PACS_UUID: Uuid = Uuid::from_u128(0x0000_1850_0000_1000_8000_0080_5f9b_34fb);
+
+/// How the SPP channel can reach the buds.
+#[derive(Debug, Clone)]
+pub enum Transport {
+    /// The device object to open the channel on, which may not be the one asked for.
+    BrEdr(Device),
+    /// The buds are only known over LE, e.g. paired with LE Audio alone.
+    LeOnly {
+        /// Whether they expose LE Audio, to tell the user why.
+        is_le_audio: bool,
+    },
+}
+
+impl Transport {
+    /// Explains why the app can't connect, for `Transport::LeOnly`.
+    pub fn diagnostic(&self) -> Option<&'static str> {
+        match self {
+            Transport::BrEdr(_) => None,
+            Transport::LeOnly { is_le_audio: true } => Some(
+                "The buds are only connected over LE Audio, which can't carry their settings. \
+                 Pair them over classic Bluetooth, or turn off the experimental LE Audio \
+                 support of BlueZ.",
+            ),
+            Transport::LeOnly { is_le_audio: false } => Some(
+                "The buds are only known over Bluetooth Low Energy, which can't carry their \
+                 settings. Pair them over classic Bluetooth.",
+            ),
+        }
+    }
+}
+
+/// Finds the classic BR/EDR device object for `device`, preferring `device` itself.
+///
+/// The SPP service is found through SDP, which only runs over BR/EDR, so an object listing it
+/// is reachable over BR/EDR. LE objects have no such record, so the classic one is looked up
+/// by name.
+pub async fn classic_transport(device: &Device) -> bluer::Result<Transport> {
+    let (address_type, uuids) = (device.address_type().await?, device.uuids().await?);
+    let is_le_audio = uuids.is_some_and(|uuids| uuids.contains(&PACS_UUID));
+    debug!(?address_type, is_le_audio, "Checking the device transport");
+    if has_buds_service(device).await {
+        return Ok(Transport::BrEdr(device.clone()));
+    }
+
+    let Some(name) = device.name().await? else {
+        return Ok(Transport::LeOnly { is_le_audio });
+    };
+    let session = Session::new().await?;
+    let adapter = session.adapter(device.adapter_name())?;
+    for address in adapter.device_addresses().await? {
+        if address == device.address() {
+            continue;
+        }
+        let candidate = adapter.device(address)?;
+        // Random addresses are only used over LE.
+        if candidate.address_type().await? == AddressType::LeRandom {
+            continue;
+        }
+        if candidate.name().await?.as_ref() == Some(&name) && has_buds_service(&candidate).await {
+            info!(%address, "Using the classic device object of the buds");
+            return Ok(Transport::BrEdr(candidate));
+        }
+    }
+    Ok(Transport::LeOnly { is_le_audio })
+}
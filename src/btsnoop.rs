@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::{Bytes, BytesMut};
+use galaxy_buds_rs::model::Model;
+
+use crate::{buds_worker::process_buffer, model::buds_message::BudsMessage};
+
+const MAGIC: &[u8; 8] = b"btsnoop\0";
+const HEADER_SIZE: usize = 16;
+const RECORD_HEADER_SIZE: usize = 24;
+/// Datalink type for HCI UART (H4), where every packet starts with a packet type byte.
+const DATALINK_H4: u32 = 1002;
+/// Datalink type for un-encapsulated HCI, where the packet type is only in the record flags.
+const DATALINK_HCI: u32 = 1001;
+const H4_ACL_DATA: u8 = 0x02;
+/// Record flag bit set when the packet was received by the host (i.e. sent by the buds).
+const FLAG_RECEIVED: u32 = 0x01;
+/// Record flag bit set for commands and events rather than data packets.
+const FLAG_COMMAND_OR_EVENT: u32 = 0x02;
+/// First dynamically allocated L2CAP channel id; lower ones are reserved for signaling.
+const L2CAP_FIRST_DYNAMIC_CID: u16 = 0x0040;
+/// RFCOMM UIH frame control field, ignoring the poll/final bit.
+const RFCOMM_UIH: u8 = 0xEF;
+
+/// Frames found in a capture and how well `BudsMessage` covers them.
+#[derive(Debug, Default)]
+pub struct CaptureReport {
+    /// Every frame found, in capture order.
+    pub frames: Vec<Bytes>,
+    /// Number of frames per message id that `BudsMessage` could parse.
+    pub known_ids: BTreeMap<u8, usize>,
+    /// Number of frames per message id that `BudsMessage` doesn't support.
+    pub unknown_ids: BTreeMap<u8, usize>,
+}
+
+impl CaptureReport {
+    /// Summarizes the message id statistics in a human-readable form.
+    pub fn summary(&self) -> String {
+        let known: usize = self.known_ids.values().sum();
+        let unknown: usize = self.unknown_ids.values().sum();
+        let mut summary = format!(
+            "{} frames: {} known, {} unknown\n",
+            self.frames.len(),
+            known,
+            unknown
+        );
+
+        if !self.unknown_ids.is_empty() {
+            summary.push_str("\nUnknown ids:\n");
+            for (id, count) in &self.unknown_ids {
+                summary.push_str(&format!("  {:3} (0x{:02X}): {}\n", id, id, count));
+            }
+        }
+
+        summary
+    }
+
+    fn add_frame(&mut self, frame: Bytes) {
+        // Captures don't say which model they're from; only the ids are counted anyway.
+        match BudsMessage::from_bytes(&frame, Model::BudsLive) {
+            Some(BudsMessage::Unknown { id, .. }) => {
+                *self.unknown_ids.entry(id).or_default() += 1;
+            }
+            Some(_) => {
+                *self.known_ids.entry(frame[3]).or_default() += 1;
+            }
+            // Malformed frames
+            None => return,
+        }
+        self.frames.push(frame);
+    }
+}
+
+/// Parses a btsnoop capture and runs every Galaxy Buds frame in it through `BudsMessage`.
+///
+/// Works with the `btsnoop_hci.log` produced by Android's "Bluetooth HCI snoop log"
+/// developer option, to check message coverage against real phone traffic.
+pub fn parse_capture(data: &[u8]) -> Result<CaptureReport, String> {
+    if data.len() < HEADER_SIZE || &data[..8] != MAGIC {
+        return Err("Not a btsnoop capture".to_string());
+    }
+
+    let datalink = u32::from_be_bytes(data[12..16].try_into().unwrap());
+    if datalink != DATALINK_H4 && datalink != DATALINK_HCI {
+        return Err(format!("Unsupported btsnoop datalink type {}", datalink));
+    }
+
+    // RFCOMM payloads are accumulated per direction, since frames can span several packets.
+    let mut streams: [BytesMut; 2] = Default::default();
+    // Partial L2CAP PDUs, per direction and ACL connection handle.
+    let mut fragments: HashMap<(bool, u16), Vec<u8>> = HashMap::new();
+    let mut report = CaptureReport::default();
+
+    let mut offset = HEADER_SIZE;
+    while offset + RECORD_HEADER_SIZE <= data.len() {
+        let record = &data[offset..];
+        let included_length = u32::from_be_bytes(record[4..8].try_into().unwrap()) as usize;
+        let flags = u32::from_be_bytes(record[8..12].try_into().unwrap());
+        offset += RECORD_HEADER_SIZE;
+
+        let Some(packet) = data.get(offset..offset + included_length) else {
+            break;
+        };
+        offset += included_length;
+
+        let acl = match datalink {
+            DATALINK_H4 if packet.first() == Some(&H4_ACL_DATA) => &packet[1..],
+            DATALINK_HCI if flags & FLAG_COMMAND_OR_EVENT == 0 => packet,
+            _ => continue,
+        };
+
+        let is_received = flags & FLAG_RECEIVED != 0;
+        let Some(pdu) = reassemble_acl(acl, is_received, &mut fragments) else {
+            continue;
+        };
+
+        if let Some(payload) = rfcomm_payload(&pdu) {
+            let stream = &mut streams[is_received as usize];
+            stream.extend_from_slice(payload);
+            for frame in process_buffer(stream) {
+                report.add_frame(frame);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Reassembles fragmented ACL packets, returning a complete L2CAP PDU once available.
+fn reassemble_acl(
+    acl: &[u8],
+    is_received: bool,
+    fragments: &mut HashMap<(bool, u16), Vec<u8>>,
+) -> Option<Vec<u8>> {
+    if acl.len() < 4 {
+        return None;
+    }
+
+    let handle_and_flags = u16::from_le_bytes([acl[0], acl[1]]);
+    let handle = handle_and_flags & 0x0FFF;
+    let is_continuation = (handle_and_flags >> 12) & 0b11 == 0b01;
+    let data = &acl[4..];
+
+    let key = (is_received, handle);
+    let buffer = if is_continuation {
+        let buffer = fragments.get_mut(&key)?;
+        buffer.extend_from_slice(data);
+        buffer
+    } else {
+        fragments.insert(key, data.to_vec());
+        fragments.get_mut(&key)?
+    };
+
+    if buffer.len() < 4 {
+        return None;
+    }
+    let l2cap_length = u16::from_le_bytes([buffer[0], buffer[1]]) as usize;
+    if buffer.len() < l2cap_length + 4 {
+        return None;
+    }
+
+    fragments.remove(&key)
+}
+
+/// Extracts the information field of an RFCOMM UIH frame carried in an L2CAP PDU.
+///
+/// Returns `None` for other channels, control frames and the RFCOMM multiplexer channel.
+fn rfcomm_payload(pdu: &[u8]) -> Option<&[u8]> {
+    let l2cap_length = u16::from_le_bytes([*pdu.first()?, *pdu.get(1)?]) as usize;
+    let cid = u16::from_le_bytes([*pdu.get(2)?, *pdu.get(3)?]);
+    if cid < L2CAP_FIRST_DYNAMIC_CID {
+        return None;
+    }
+    let frame = pdu.get(4..4 + l2cap_length)?;
+
+    let address = *frame.first()?;
+    let control = *frame.get(1)?;
+    let dlci = address >> 2;
+    if dlci == 0 || control & !0x10 != RFCOMM_UIH {
+        return None;
+    }
+
+    // The length field takes one byte, or two when its extension bit is clear.
+    let (length, mut start) = if frame.get(2)? & 0x01 == 1 {
+        ((frame[2] >> 1) as usize, 3)
+    } else {
+        (
+            ((frame[2] >> 1) as usize) | ((*frame.get(3)? as usize) << 7),
+            4,
+        )
+    };
+
+    // With credit based flow control, a set poll/final bit means a credit byte precedes the data.
+    if control & 0x10 != 0 {
+        start += 1;
+    }
+
+    frame.get(start..start + length)
+}
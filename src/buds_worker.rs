@@ -1,56 +1,174 @@
 use bluer::{
-    Session, Uuid,
-    rfcomm::{
-        Profile, Role, Stream,
-        stream::{OwnedReadHalf, OwnedWriteHalf},
-    },
+    ErrorKind, Session, Uuid,
+    id::ServiceClass,
+    rfcomm::{Profile, Role, Stream},
 };
+use bytes::{Buf, Bytes, BytesMut};
 use futures::StreamExt;
-use galaxy_buds_rs::message;
+use galaxy_buds_rs::{message, model::Model};
+use gtk4::gio::prelude::SettingsExt;
 use relm4::{Sender, Worker, prelude::*};
-use std::sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering},
+use std::{
+    fmt::Debug,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
     runtime::Runtime,
     sync::Mutex,
 };
 use tracing::{debug, debug_span, error, info, trace, trace_span, warn};
 
 use crate::{
-    consts::SAMSUNG_SPP_UUID,
+    bluetooth::transport::{Transport, classic_transport},
+    consts::{FAULTS_KEY, SAMSUNG_SPP_UUID, TCP_BRIDGE_KEY},
+    faults::Faults,
+    features::Feature,
+    lock_ext::LockOrRecover,
     model::{
         buds_message::{BudsCommand, BudsMessage},
+        capabilities::Capabilities,
+        confirmation::{ConfirmationToken, Confirmations, DestructiveOperation},
         device_info::DeviceInfo,
     },
+    sender_ext::SendOrLog,
+    settings,
+    tuning::{self, Tuning},
 };
 
-const READ_BUFFER_SIZE: usize = 2048;
+/// Audio profiles to claim from another device when taking over the connection, in order.
+///
+/// The buds only open the SPP channel to the device that holds their audio, so these go
+/// first; media before calls, which is the order phones connect them in.
+const TAKE_OVER_PROFILES: [ServiceClass; 2] = [ServiceClass::AudioSink, ServiceClass::Handsfree];
+/// How many times to try each profile, as the buds may still be busy releasing the other device.
+const TAKE_OVER_ATTEMPTS: u32 = 3;
+const TAKE_OVER_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// How to handle a request to connect, given the state of the connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectAction {
+    /// Open a new connection.
+    Connect,
+    /// Repeat the handshake over the open connection, so the new listener gets the device state.
+    Resume,
+    /// Wait for the answer to the handshake under way, rather than starting another.
+    JoinHandshake,
+}
+
+impl ConnectAction {
+    /// Connecting again while connected would register a second profile, racing the first for
+    /// the channel.
+    fn for_state(is_running: bool, is_handshake_pending: bool) -> Self {
+        match (is_running, is_handshake_pending) {
+            (false, _) => Self::Connect,
+            (true, false) => Self::Resume,
+            (true, true) => Self::JoinHandshake,
+        }
+    }
+}
+
+/// Counts what was started so far, e.g. connections, so the tasks started for an earlier one
+/// can tell they're stale and stop.
+#[derive(Debug, Clone, Default)]
+struct Generation(Arc<AtomicU64>);
+
+impl Generation {
+    /// Starts the next generation, making the tasks of the previous ones stale, and returns it.
+    fn advance(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.0.load(Ordering::Relaxed) == generation
+    }
+}
+
+/// Read half of the connection to the device, either RFCOMM or the TCP bridge.
+type Reader = Box<dyn AsyncRead + Send + Unpin>;
+/// Write half of the connection to the device, either RFCOMM or the TCP bridge.
+type Writer = Box<dyn WriteHalf>;
+
+/// Lets `Writer` be `Debug`, which a plain `dyn AsyncWrite` isn't.
+trait WriteHalf: AsyncWrite + Debug + Send + Unpin {}
+
+impl<T: AsyncWrite + Debug + Send + Unpin> WriteHalf for T {}
 
 /// Input messages for the `BluetoothWorker`.
 #[derive(Debug)]
 pub enum BudsWorkerInput {
     /// Starts the discovery and connection process.
     Connect,
+    /// Connects the audio profiles to this machine first, taking them from
+    /// another device if needed, then connects as `Connect` does.
+    ///
+    /// Only carried out with a token the user confirmed `DestructiveOperation::TakeOver` with.
+    TakeOver(ConfirmationToken),
     /// Disconnects from the current device.
     Disconnect,
+    /// Disconnects as `Disconnect` does, then removes the Bluetooth pairing if `unpair` is set.
+    ///
+    /// Only carried out with a token the user confirmed `DestructiveOperation::Forget` with.
+    Forget {
+        token: ConfirmationToken,
+        unpair: bool,
+    },
     /// Sends a raw byte payload to the device.
     SendData(Vec<u8>),
     /// Encodes and sends a `BudsCommand` to the device.
     SendCommand(BudsCommand),
+    /// Stops polling the device, e.g. while the window is hidden, until `ResumePolling`.
+    PausePolling,
+    ResumePolling,
+}
+
+/// Steps of the connection process, in the order they happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStage {
+    /// Connecting to the device itself (or to the TCP bridge).
+    Baseband,
+    /// Registering the SPP profile with BlueZ.
+    RegisteringProfile,
+    /// Waiting for the device to open the RFCOMM channel.
+    WaitingForRequest,
+    /// Waiting for the device to answer the manager info request.
+    Handshake,
+}
+
+impl ConnectStage {
+    pub fn description(&self) -> &'static str {
+        match self {
+            ConnectStage::Baseband => "Connecting to device...",
+            ConnectStage::RegisteringProfile => "Registering profile...",
+            ConnectStage::WaitingForRequest => "Waiting for the device to respond...",
+            ConnectStage::Handshake => "Identifying device...",
+        }
+    }
 }
 
 /// Output messages from the `BluetoothWorker`.
 #[derive(Debug)]
 pub enum BudsWorkerOutput {
+    /// Emitted when the connection process moves on to the next stage.
+    Progress(ConnectStage),
     /// Emitted when a connection is successfully established.
     Connected,
+    /// Emitted when the device answered the manager info request, after which it takes commands.
+    HandshakeDone,
     /// Emitted when the device is disconnected.
     Disconnected,
     /// Emitted when a `BudsMessage` is received from the device.
     DataReceived(BudsMessage),
+    /// Emitted when the device didn't send any known message within `Tuning::handshake_timeout`.
+    HandshakeTimeout,
+    /// Emitted when another app already registered the Galaxy Buds SPP profile with BlueZ,
+    /// e.g. GalaxyBudsClient running in Wine, so the device can't open the channel to this one.
+    ProfileInUse,
     /// Emitted when an error occurs.
     Error(String),
 }
@@ -60,17 +178,41 @@ pub enum BudsWorkerOutput {
 #[derive(Debug)]
 pub struct BluetoothWorker {
     device: DeviceInfo,
-    writer: Arc<Mutex<Option<OwnedWriteHalf>>>,
+    /// Confirmations issued by the UI, redeemed by the destructive inputs.
+    confirmations: Confirmations,
+    /// Address of a TCP bridge to use instead of RFCOMM, from a hidden developer setting.
+    /// Only used while the `Feature::TcpBridge` experimental feature is enabled.
+    ///
+    /// The bridge must forward the raw SPP byte stream, e.g. from a phone via `adb forward`.
+    tcp_bridge: Option<String>,
+    /// Faults to inject, from a hidden developer setting. Empty unless
+    /// `Feature::FaultInjection` is enabled.
+    faults: Faults,
+    tuning: Tuning,
+    /// Whether a connection was attempted yet, for `Faults::fail_first_connect`.
+    has_tried_connecting: AtomicBool,
+    writer: Arc<Mutex<Option<Writer>>>,
     runtime: Arc<Runtime>,
     is_running: Arc<AtomicBool>,
+    is_handshake_done: Arc<AtomicBool>,
+    /// When the manager info request was last sent, to tell whether a handshake is under way.
+    handshake_started_at: std::sync::Mutex<Option<Instant>>,
+    /// Advanced on each handshake, so only the timeout of the latest one fires.
+    handshake_generation: Generation,
+    /// Advanced on each connection and disconnection, so polling stops with its connection.
+    connection_generation: Generation,
+    /// When the device last sent a status update, used to decide whether to poll.
+    last_status_update: Arc<std::sync::Mutex<Instant>>,
+    /// Whether polling is paused. Outlives connections, so a reconnect doesn't resume it.
+    is_polling_paused: Arc<AtomicBool>,
 }
 
 impl Worker for BluetoothWorker {
-    type Init = DeviceInfo;
+    type Init = (DeviceInfo, Confirmations);
     type Input = BudsWorkerInput;
     type Output = BudsWorkerOutput;
 
-    fn init(device: Self::Init, _sender: ComponentSender<Self>) -> Self {
+    fn init((device, confirmations): Self::Init, _sender: ComponentSender<Self>) -> Self {
         let runtime = Arc::new(
             tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
@@ -80,12 +222,39 @@ impl Worker for BluetoothWorker {
 
         let writer = Arc::new(Mutex::new(None));
         let is_running = Arc::new(AtomicBool::new(false));
+        let is_handshake_done = Arc::new(AtomicBool::new(false));
+        let last_status_update = Arc::new(std::sync::Mutex::new(Instant::now()));
+        let settings = settings::get_settings();
+        let tcp_bridge = Some(settings.string(TCP_BRIDGE_KEY).to_string())
+            .filter(|address| !address.is_empty() && Feature::TcpBridge.is_enabled(&settings));
+        let faults = if Feature::FaultInjection.is_enabled(&settings) {
+            Faults::parse(&settings.string(FAULTS_KEY)).unwrap_or_else(|e| {
+                warn!("Ignoring the injected faults: {}", e);
+                Faults::default()
+            })
+        } else {
+            Faults::default()
+        };
+        if faults != Faults::default() {
+            warn!(?faults, "Injecting faults into the connection");
+        }
 
         Self {
             device,
+            confirmations,
+            tcp_bridge,
+            faults,
+            tuning: *tuning::get(),
+            has_tried_connecting: AtomicBool::new(false),
             writer,
             runtime,
             is_running,
+            is_handshake_done,
+            handshake_started_at: std::sync::Mutex::new(None),
+            handshake_generation: Generation::default(),
+            connection_generation: Generation::default(),
+            last_status_update,
+            is_polling_paused: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -103,69 +272,211 @@ impl BluetoothWorker {
         debug!(parent: &span, "start handle");
 
         match msg {
-            BudsWorkerInput::Connect => self.connect(sender).await,
-            BudsWorkerInput::Disconnect => {
-                self.is_running.store(false, Ordering::Relaxed);
-                // Dropping the writer will close the connection, causing the read task to terminate.
-                *self.writer.lock().await = None;
-                if sender.send(BudsWorkerOutput::Disconnected).is_err() {
-                    warn!("UI receiver dropped, could not send Disconnected message.");
+            BudsWorkerInput::Connect => self.connect_or_join(false, sender).await,
+            BudsWorkerInput::TakeOver(token) => {
+                match self.confirmations.redeem(token, DestructiveOperation::TakeOver) {
+                    Ok(()) => self.connect_or_join(true, sender).await,
+                    Err(e) => {
+                        warn!("Not taking over the connection: {}", e);
+                        sender.send_or_log(BudsWorkerOutput::Error(e));
+                    }
+                }
+            }
+            BudsWorkerInput::Disconnect => self.disconnect(sender).await,
+            BudsWorkerInput::Forget { token, unpair } => {
+                match self.confirmations.redeem(token, DestructiveOperation::Forget) {
+                    Ok(()) => self.forget(unpair, sender).await,
+                    Err(e) => {
+                        warn!("Not forgetting the device: {}", e);
+                        sender.send_or_log(BudsWorkerOutput::Error(e));
+                    }
                 }
             }
             BudsWorkerInput::SendData(data) => self.send_data(sender, data).await,
             BudsWorkerInput::SendCommand(cmd) => self.send_data(sender, cmd.to_bytes()).await,
+            BudsWorkerInput::PausePolling => self.is_polling_paused.store(true, Ordering::Relaxed),
+            BudsWorkerInput::ResumePolling => {
+                self.is_polling_paused.store(false, Ordering::Relaxed)
+            }
         }
         debug!(parent: &span, "end handle");
     }
 
+    /// Connects, taking the audio profiles from another device first if `take_over` is set,
+    /// unless a connection is already up or being set up.
+    async fn connect_or_join(&self, take_over: bool, sender: &Sender<BudsWorkerOutput>) {
+        let action = ConnectAction::for_state(
+            self.is_running.load(Ordering::Relaxed),
+            self.is_handshake_pending(),
+        );
+        match action {
+            ConnectAction::Connect => {
+                if take_over {
+                    self.take_over_profiles(sender).await;
+                }
+                self.connect(sender).await;
+            }
+            ConnectAction::Resume => self.resume(sender).await,
+            ConnectAction::JoinHandshake => {
+                debug!("A handshake is already under way, not starting another.");
+                sender.send_or_log(BudsWorkerOutput::Progress(ConnectStage::Handshake));
+                sender.send_or_log(BudsWorkerOutput::Connected);
+            }
+        }
+    }
+
+    async fn disconnect(&self, sender: &Sender<BudsWorkerOutput>) {
+        self.is_running.store(false, Ordering::Relaxed);
+        self.connection_generation.advance();
+        // Dropping the writer will close the connection, causing the read task to terminate.
+        *self.writer.lock().await = None;
+        sender.send_or_log(BudsWorkerOutput::Disconnected);
+    }
+
+    /// Disconnects, then removes the Bluetooth pairing if `unpair` is set.
+    async fn forget(&self, unpair: bool, sender: &Sender<BudsWorkerOutput>) {
+        self.disconnect(sender).await;
+        if !unpair {
+            return;
+        }
+        match self.device.unpair().await {
+            Ok(()) => debug!(address = %self.device.address, "Device unpaired"),
+            Err(e) => error!("Failed to unpair device: {}", e),
+        }
+    }
+
     /// Establishes a connection and spawns the reading task.
     async fn connect(&self, sender: &Sender<BudsWorkerOutput>) {
-        match self.connect_and_get_stream().await {
-            Ok(stream) => {
-                // Split reader and writer streams
+        sender.send_or_log(BudsWorkerOutput::Progress(ConnectStage::Baseband));
+        let is_first_attempt = !self.has_tried_connecting.swap(true, Ordering::Relaxed);
+        let result = match &self.tcp_bridge {
+            _ if is_first_attempt && self.faults.fail_first_connect => {
+                Err("Injected fault: first connection attempt".into())
+            }
+            Some(address) => connect_tcp_bridge(address).await,
+            None => self.connect_and_get_stream(sender).await.map(|stream| {
                 let (reader, writer) = stream.into_split();
+                (Box::new(reader) as Reader, Box::new(writer) as Writer)
+            }),
+        };
+
+        match result {
+            Ok((reader, writer)) => {
                 *self.writer.lock().await = Some(writer);
 
                 // Run reader loop in background
 
                 self.is_running.store(true, Ordering::Relaxed);
+                let connection = self.connection_generation.advance();
+                self.is_handshake_done.store(false, Ordering::Relaxed);
+                *self.last_status_update.lock_or_recover() = Instant::now();
                 relm4::spawn(read_task(
                     reader,
+                    self.device.model(),
+                    self.tuning.read_buffer_size,
+                    self.faults.clone(),
                     sender.clone(),
                     Arc::clone(&self.is_running),
+                    Arc::clone(&self.is_handshake_done),
+                    Arc::clone(&self.last_status_update),
                 ));
 
                 // Request manager info after connecting
-                self.send_data(&sender, BudsCommand::ManagerInfo.to_bytes())
-                    .await;
-
-                if sender.send(BudsWorkerOutput::Connected).is_err() {
-                    warn!("UI receiver dropped, could not send Connected message.");
+                sender.send_or_log(BudsWorkerOutput::Progress(ConnectStage::Handshake));
+                self.start_handshake(sender).await;
+
+                let capabilities = Capabilities::for_model(self.device.model());
+                if let Some(interval) = capabilities.status_poll_interval {
+                    relm4::spawn(poll_status_task(
+                        interval,
+                        Arc::clone(&self.writer),
+                        Arc::clone(&self.is_running),
+                        self.connection_generation.clone(),
+                        connection,
+                        Arc::clone(&self.is_polling_paused),
+                        Arc::clone(&self.last_status_update),
+                    ));
                 }
+
+                sender.send_or_log(BudsWorkerOutput::Connected);
+            }
+            Err(e) if is_profile_in_use(e.as_ref()) => {
+                warn!("The SPP profile is already registered by another app: {}", e);
+                sender.send_or_log(BudsWorkerOutput::ProfileInUse);
             }
             Err(e) => {
                 let err_msg = format!("Connection failed: {}", e);
                 error!("{}", err_msg);
-                if sender.send(BudsWorkerOutput::Error(err_msg)).is_err() {
-                    warn!("UI receiver dropped, could not send Error message.");
-                }
+                sender.send_or_log(BudsWorkerOutput::Error(err_msg));
             }
         }
     }
 
+    /// Hands the open connection to a new listener, e.g. a device page opened again.
+    ///
+    /// Repeats the handshake rather than connecting again, so the listener still gets
+    /// the full device state. While a handshake is already under way, e.g. when auto-connect
+    /// and a click both asked to connect, the listener gets its answer instead, see
+    /// `ConnectAction::JoinHandshake`.
+    async fn resume(&self, sender: &Sender<BudsWorkerOutput>) {
+        sender.send_or_log(BudsWorkerOutput::Progress(ConnectStage::Handshake));
+        info!("Reusing the open connection.");
+        self.is_handshake_done.store(false, Ordering::Relaxed);
+        self.start_handshake(sender).await;
+        sender.send_or_log(BudsWorkerOutput::Connected);
+    }
+
+    /// Asks the device to identify itself, reporting a timeout unless it answers in time or
+    /// another handshake starts first.
+    async fn start_handshake(&self, sender: &Sender<BudsWorkerOutput>) {
+        *self.handshake_started_at.lock_or_recover() = Some(Instant::now());
+        let handshake = self.handshake_generation.advance();
+        self.send_data(sender, BudsCommand::ManagerInfo.to_bytes()).await;
+        relm4::spawn(handshake_timeout(
+            self.tuning.handshake_timeout,
+            sender.clone(),
+            Arc::clone(&self.is_running),
+            Arc::clone(&self.is_handshake_done),
+            self.handshake_generation.clone(),
+            handshake,
+        ));
+    }
+
+    /// Whether the device was asked to identify itself and may still answer.
+    fn is_handshake_pending(&self) -> bool {
+        is_handshake_pending(
+            self.is_handshake_done.load(Ordering::Relaxed),
+            *self.handshake_started_at.lock_or_recover(),
+            self.tuning.handshake_timeout,
+        )
+    }
+
     /// Performs the full Bluetooth connection and profile registration dance.
+    ///
+    /// Reports each `ConnectStage` after `Baseband` through `sender`.
     async fn connect_and_get_stream(
         &self,
+        sender: &Sender<BudsWorkerOutput>,
     ) -> Result<Stream, Box<dyn std::error::Error + Send + Sync>> {
         let session = Session::new().await?;
-        let device = self.device.device.clone();
+        let transport = classic_transport(&self.device.device).await?;
+        let Transport::BrEdr(device) = transport else {
+            let diagnostic = transport.diagnostic().unwrap_or_default();
+            warn!("{}", diagnostic);
+            return Err(diagnostic.into());
+        };
 
         debug!("Connecting to device {}...", device.address());
         device.connect().await?;
         info!("Device connected.");
+        sender.send_or_log(BudsWorkerOutput::Progress(ConnectStage::RegisteringProfile));
 
-        // let spp_uuid = bluer::id::ServiceClass::SerialPort.into();
-        let spp_uuid: Uuid = SAMSUNG_SPP_UUID.parse()?;
+        // Buds found before they exposed a known service are most likely newer ones.
+        let spp_uuid = match self.device.spp_uuid.or(self.tuning.spp_uuid) {
+            Some(spp_uuid) => spp_uuid,
+            None => SAMSUNG_SPP_UUID.parse()?,
+        };
+        debug!(%spp_uuid, "Registering the SPP profile");
         let profile = Profile {
             uuid: spp_uuid,
             role: Some(Role::Client),
@@ -176,6 +487,7 @@ impl BluetoothWorker {
         };
         let mut handle = session.register_profile(profile).await?;
         debug!("SPP Profile registered. Waiting for connection...");
+        sender.send_or_log(BudsWorkerOutput::Progress(ConnectStage::WaitingForRequest));
 
         if let Some(req) = handle.next().await {
             debug!("Connection request from {:?} accepted.", req.device());
@@ -187,22 +499,56 @@ impl BluetoothWorker {
         }
     }
 
-    /// Sends a byte payload to the device via the RFCOMM stream.
+    /// Asks BlueZ to connect `TAKE_OVER_PROFILES` to this machine, retrying each a few times.
+    ///
+    /// Failures are only logged: the SPP session may still work without them, and
+    /// `connect` reports the error if it doesn't.
+    async fn take_over_profiles(&self, sender: &Sender<BudsWorkerOutput>) {
+        if self.tcp_bridge.is_some() {
+            return;
+        }
+
+        sender.send_or_log(BudsWorkerOutput::Progress(ConnectStage::Baseband));
+        let device = &self.device.device;
+        for service in TAKE_OVER_PROFILES {
+            let uuid: Uuid = service.into();
+            for attempt in 1..=TAKE_OVER_ATTEMPTS {
+                match device.connect_profile(&uuid).await {
+                    Ok(()) => {
+                        info!("{:?} profile connected.", service);
+                        break;
+                    }
+                    Err(e) if e.kind == ErrorKind::AlreadyConnected => {
+                        debug!("{:?} profile already connected.", service);
+                        break;
+                    }
+                    Err(e) => {
+                        warn!(attempt, "Failed to connect {:?} profile: {}", service, e);
+                        if attempt < TAKE_OVER_ATTEMPTS {
+                            tokio::time::sleep(TAKE_OVER_RETRY_DELAY).await;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sends a byte payload to the device via the RFCOMM stream or TCP bridge.
     async fn send_data(&self, sender: &Sender<<BluetoothWorker as Worker>::Output>, data: Vec<u8>) {
+        if let Some(delay) = self.faults.write_delay {
+            trace!("Injected fault: delaying the write by {:?}", delay);
+            tokio::time::sleep(delay).await;
+        }
         if let Some(stream) = self.writer.lock().await.as_mut() {
             if let Err(e) = stream.write_all(&data).await {
                 let err_msg = format!("Send data failed: {}", e);
                 error!("{}", err_msg);
-                if sender.send(BudsWorkerOutput::Error(err_msg)).is_err() {
-                    warn!("UI receiver dropped, could not send Error message.");
-                }
+                sender.send_or_log(BudsWorkerOutput::Error(err_msg));
             }
         } else {
             let err_msg = "Cannot send data: Not connected".to_string();
             error!("{}", err_msg);
-            if sender.send(BudsWorkerOutput::Error(err_msg)).is_err() {
-                warn!("UI receiver dropped, could not send Error message.");
-            }
+            sender.send_or_log(BudsWorkerOutput::Error(err_msg));
         }
     }
 }
@@ -213,35 +559,57 @@ impl BluetoothWorker {
 /// and sending them to the UI. The loop terminates when the `is_running` flag
 /// is set to false or a fatal error occurs.
 async fn read_task(
-    mut stream: OwnedReadHalf,
+    mut stream: Reader,
+    model: Model,
+    read_buffer_size: usize,
+    faults: Faults,
     sender: Sender<BudsWorkerOutput>,
     is_running: Arc<AtomicBool>,
+    is_handshake_done: Arc<AtomicBool>,
+    last_status_update: Arc<std::sync::Mutex<Instant>>,
 ) {
     let span = trace_span!("Stream read loop");
     let _enter = span.enter();
     debug!("Start reading");
-    let mut read_buffer: Vec<u8> = Vec::new();
+    let mut read_buffer = BytesMut::with_capacity(read_buffer_size);
+    let mut frame_count: u64 = 0;
 
-    while is_running.load(Ordering::Relaxed) {
-        let mut temp_buffer = [0u8; READ_BUFFER_SIZE];
+    'read: while is_running.load(Ordering::Relaxed) {
+        read_buffer.reserve(read_buffer_size);
 
-        match stream.read(&mut temp_buffer).await {
+        match stream.read_buf(&mut read_buffer).await {
             Ok(0) => {
                 info!("Stream closed by peer");
                 break;
             }
             Ok(n) => {
-                read_buffer.extend_from_slice(&temp_buffer[..n]);
                 trace!(
                     "Read {} bytes. Current buffer size: {}",
                     n,
                     read_buffer.len()
                 );
                 for message_frame in process_buffer(&mut read_buffer) {
-                    if let Some(msg) = BudsMessage::from_bytes(&message_frame) {
+                    frame_count += 1;
+                    if faults.drops_frame(frame_count) {
+                        debug!(frame_count, "Injected fault: dropping the frame");
+                        continue;
+                    }
+                    if let Some(msg) = BudsMessage::from_bytes(&message_frame, model) {
+                        // Only the response to the manager info request completes the handshake.
+                        if let BudsMessage::ExtendedStatusUpdate(_) = msg {
+                            if !is_handshake_done.swap(true, Ordering::Relaxed) {
+                                sender.send_or_log(BudsWorkerOutput::HandshakeDone);
+                            }
+                        }
+                        if let BudsMessage::StatusUpdate(_) | BudsMessage::ExtendedStatusUpdate(_) =
+                            msg
+                        {
+                            *last_status_update.lock_or_recover() = Instant::now();
+                        }
                         if sender.send(BudsWorkerOutput::DataReceived(msg)).is_err() {
+                            // Nobody is listening anymore, so there's no point in reading on.
                             warn!("UI receiver dropped, could not send DataReceived message.");
-                            break;
+                            break 'read;
                         }
                     }
                 }
@@ -251,9 +619,7 @@ async fn read_task(
                 if is_running.load(Ordering::Relaxed) {
                     error!(parent: &span, "Read error: {}", e);
                     let err_msg = format!("Read error: {}", e);
-                    if sender.send(BudsWorkerOutput::Error(err_msg)).is_err() {
-                        warn!("UI receiver dropped, could not send Error message.");
-                    }
+                    sender.send_or_log(BudsWorkerOutput::Error(err_msg));
                 }
                 break;
             }
@@ -261,14 +627,102 @@ async fn read_task(
     }
 
     // Ensure we always send a disconnected message on exit.
-    if sender.send(BudsWorkerOutput::Disconnected).is_err() {
-        warn!("UI receiver dropped, could not send final Disconnected message.");
-    }
+    sender.send_or_log(BudsWorkerOutput::Disconnected);
     is_running.store(false, Ordering::Relaxed);
     debug!(parent: &span, "Stop reading");
 }
 
-fn process_buffer(buffer: &mut Vec<u8>) -> Vec<Vec<u8>> {
+/// Whether a handshake started at `started_at` is unanswered, but may still be within `timeout`.
+fn is_handshake_pending(is_done: bool, started_at: Option<Instant>, timeout: Duration) -> bool {
+    !is_done && started_at.is_some_and(|started_at| started_at.elapsed() < timeout)
+}
+
+/// Waits for `timeout` and reports a timeout if the device is still
+/// connected but hasn't sent any known message yet.
+async fn handshake_timeout(
+    timeout: Duration,
+    sender: Sender<BudsWorkerOutput>,
+    is_running: Arc<AtomicBool>,
+    is_handshake_done: Arc<AtomicBool>,
+    generations: Generation,
+    handshake: u64,
+) {
+    tokio::time::sleep(timeout).await;
+
+    if !generations.is_current(handshake) {
+        debug!("Another handshake started since, not timing this one out");
+        return;
+    }
+    if is_running.load(Ordering::Relaxed) && !is_handshake_done.load(Ordering::Relaxed) {
+        warn!("No known message received after {:?}", timeout);
+        sender.send_or_log(BudsWorkerOutput::HandshakeTimeout);
+    }
+}
+
+/// Periodically requests a status update from devices that don't push them reliably.
+///
+/// A request is only sent when no status update arrived within the last `interval`, and
+/// polling isn't paused. The task ends once the connection stops running, or `connection`
+/// is no longer the current one, as it was replaced by a new connection in the meantime.
+async fn poll_status_task(
+    interval: Duration,
+    writer: Arc<Mutex<Option<Writer>>>,
+    is_running: Arc<AtomicBool>,
+    generations: Generation,
+    connection: u64,
+    is_polling_paused: Arc<AtomicBool>,
+    last_status_update: Arc<std::sync::Mutex<Instant>>,
+) {
+    debug!("Polling status every {:?}", interval);
+    loop {
+        tokio::time::sleep(interval).await;
+        if !is_running.load(Ordering::Relaxed) || !generations.is_current(connection) {
+            break;
+        }
+        if is_polling_paused.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        let elapsed = last_status_update.lock_or_recover().elapsed();
+        if elapsed < interval {
+            continue;
+        }
+
+        trace!("No status update for {:?}, requesting one", elapsed);
+        // The device answers the manager info request with an extended status update.
+        if let Some(stream) = writer.lock().await.as_mut() {
+            if let Err(e) = stream.write_all(&BudsCommand::ManagerInfo.to_bytes()).await {
+                warn!("Status poll failed: {}", e);
+            }
+        }
+    }
+    debug!("Stop polling status");
+}
+
+/// Whether connecting failed because BlueZ already has a profile registered for the SPP UUID.
+///
+/// Only one app can register it at a time, and that's the only step failing this way.
+fn is_profile_in_use(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    error
+        .downcast_ref::<bluer::Error>()
+        .is_some_and(|e| e.kind == ErrorKind::AlreadyExists)
+}
+
+/// Connects to a TCP bridge that forwards the device's SPP byte stream.
+async fn connect_tcp_bridge(
+    address: &str,
+) -> Result<(Reader, Writer), Box<dyn std::error::Error + Send + Sync>> {
+    debug!("Connecting to TCP bridge at {}...", address);
+    let stream = TcpStream::connect(address).await?;
+    info!("TCP bridge connected.");
+    let (reader, writer) = stream.into_split();
+    Ok((Box::new(reader), Box::new(writer)))
+}
+
+/// Splits complete message frames off the front of `buffer`, leaving any incomplete one.
+///
+/// The frames share the memory of `buffer`, so no bytes are copied.
+pub(crate) fn process_buffer(buffer: &mut BytesMut) -> Vec<Bytes> {
     let span = trace_span!("Process buffer");
     let _enter = span.enter();
 
@@ -287,20 +741,17 @@ fn process_buffer(buffer: &mut Vec<u8>) -> Vec<Vec<u8>> {
                     trace!("Discarding {} bytes of garbage data.", start);
                 }
 
-                let message_frame = &buffer[start..=end];
-                trace!("Found message with {} bytes.", message_frame.len());
-                messages_frames.push(message_frame.to_vec());
-
-                // Remove the processed message and any preceding garbage,
+                // Remove any preceding garbage and split off the message,
                 // and continue loop
-                buffer.drain(..=end);
+                buffer.advance(start);
+                let message_frame = buffer.split_to(end - start + 1).freeze();
+                trace!("Found message with {} bytes.", message_frame.len());
+                messages_frames.push(message_frame);
             }
             // Found only beginning of message; message is incomplete.
             (Some(start), _) => {
                 // Discard any garbage before the first valid BOM we found.
-                if start > 0 {
-                    buffer.drain(..start);
-                }
+                buffer.advance(start);
                 trace!("Found incomplete message with {} bytes.", buffer.len());
                 // Break the loop and keep buffer with incomplete message.
                 break;
@@ -317,3 +768,63 @@ fn process_buffer(buffer: &mut Vec<u8>) -> Vec<Vec<u8>> {
     }
     return messages_frames;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn started_ago(age: Duration) -> Option<Instant> {
+        Some(Instant::now().checked_sub(age).expect("uptime is too short"))
+    }
+
+    #[test]
+    fn connects_while_not_connected() {
+        assert_eq!(ConnectAction::for_state(false, false), ConnectAction::Connect);
+        // A handshake can't be answered over a connection that is gone.
+        assert_eq!(ConnectAction::for_state(false, true), ConnectAction::Connect);
+    }
+
+    #[test]
+    fn joins_the_handshake_of_rapid_connect_inputs() {
+        // The first input connected and sent the handshake; the others came right after, e.g.
+        // auto-connect racing a click, or a double click.
+        let started_at = Some(Instant::now());
+        for _ in 0..3 {
+            let is_pending = is_handshake_pending(false, started_at, TIMEOUT);
+            assert_eq!(ConnectAction::for_state(true, is_pending), ConnectAction::JoinHandshake);
+        }
+    }
+
+    #[test]
+    fn repeats_the_handshake_once_answered() {
+        let is_pending = is_handshake_pending(true, Some(Instant::now()), TIMEOUT);
+        assert_eq!(ConnectAction::for_state(true, is_pending), ConnectAction::Resume);
+    }
+
+    #[test]
+    fn repeats_the_handshake_once_timed_out() {
+        let is_pending = is_handshake_pending(false, started_ago(TIMEOUT * 2), TIMEOUT);
+        assert_eq!(ConnectAction::for_state(true, is_pending), ConnectAction::Resume);
+    }
+
+    #[test]
+    fn has_no_handshake_pending_before_one_was_sent() {
+        assert!(!is_handshake_pending(false, None, TIMEOUT));
+    }
+
+    #[test]
+    fn makes_the_tasks_of_earlier_generations_stale() {
+        let generations = Generation::default();
+        let first = generations.advance();
+        assert!(generations.is_current(first));
+
+        // E.g. disconnected and connected again before the first connection's poll woke up.
+        let tasks = generations.clone();
+        generations.advance();
+        let second = generations.advance();
+        assert!(!tasks.is_current(first));
+        assert!(tasks.is_current(second));
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use galaxy_buds_rs::{message::bud_property::NoiseControlMode, model::Model};
+use gtk4::gio::prelude::SettingsExt;
+use relm4::{Component, Sender, WorkerController};
+use tracing::{debug, info};
+
+use crate::{
+    buds_worker::{BluetoothWorker, BudsWorkerInput, BudsWorkerOutput},
+    consts::{DEVICE_LAST_CONNECTED_KEY, DEVICE_MODEL_KEY, DEVICE_PRIORITY_KEY},
+    model::{buds_message::BudsCommand, confirmation::Confirmations, device_info::DeviceInfo},
+    sender_ext::SendOrLog,
+    settings::{self, SettingsWriter},
+};
+
+/// Where the worker output goes, as each output is shared by everything following the device.
+type Subscribers = Rc<RefCell<Vec<Sender<Arc<BudsWorkerOutput>>>>>;
+
+/// What to do with a command sent while the device isn't connected, e.g. by an automation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendPolicy {
+    /// Sends right away, failing if the device isn't connected.
+    Immediate,
+    /// Keeps the command until the device connects, then sends it.
+    QueueUntilConnected,
+    /// Connects to send the command, then disconnects again.
+    ///
+    /// Meant for when the app isn't showing the device, as its page would see the connection
+    /// drop right after.
+    WakeAndSend,
+}
+
+/// The connection state as followed from the worker output, with the commands waiting for it.
+#[derive(Debug, Default)]
+struct Link {
+    /// Whether the device completed the handshake, and so takes commands.
+    is_connected: bool,
+    queued: Vec<BudsCommand>,
+    /// Whether the connection was only opened to send the queued commands.
+    is_woken: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FindTone {
+    Stopped,
+    Playing,
+    /// Was playing when the connection dropped, so it's stopped once connected again.
+    StopOnReconnect,
+}
+
+/// Tracks the find tone of a device, so it can't be left running.
+///
+/// Dropping it while the tone plays stops it, e.g. when the app quits. That's only a best
+/// effort, as the worker may be gone before it gets to send the command.
+#[derive(Debug)]
+struct FindSession {
+    worker: Sender<BudsWorkerInput>,
+    tone: FindTone,
+}
+
+impl FindSession {
+    fn set_playing(&mut self, is_playing: bool) {
+        self.worker
+            .send_or_log(BudsWorkerInput::SendCommand(BudsCommand::Find(is_playing)));
+        self.tone = if is_playing {
+            FindTone::Playing
+        } else {
+            FindTone::Stopped
+        };
+    }
+
+    /// Keeps a playing tone to be stopped later, as nothing can be sent until reconnected.
+    fn connection_lost(&mut self) {
+        if self.tone == FindTone::Playing {
+            self.tone = FindTone::StopOnReconnect;
+        }
+    }
+
+    fn connected(&mut self) {
+        if self.tone == FindTone::StopOnReconnect {
+            debug!("Stopping the find tone left playing before the connection dropped");
+            self.set_playing(false);
+        }
+    }
+}
+
+impl Link {
+    /// Sends the queued commands, then disconnects if the connection was only opened for them.
+    fn connected(&mut self, worker: &Sender<BudsWorkerInput>) {
+        self.is_connected = true;
+        for command in self.queued.drain(..) {
+            worker.send_or_log(BudsWorkerInput::SendCommand(command));
+        }
+        if std::mem::take(&mut self.is_woken) {
+            debug!("Sent the queued commands, disconnecting again");
+            worker.send_or_log(BudsWorkerInput::Disconnect);
+        }
+    }
+}
+
+impl Drop for FindSession {
+    fn drop(&mut self) {
+        if self.tone == FindTone::Playing {
+            self.set_playing(false);
+        }
+    }
+}
+
+/// Owns the `BluetoothWorker` of each device, so that everything talking to a device shares
+/// one connection, instead of each racing to register the SPP profile.
+#[derive(Debug, Clone)]
+pub struct ConnectionManager {
+    connections: Rc<RefCell<HashMap<String, Connection>>>,
+    /// Address of the device last connected to, kept once it disconnects, until forgotten.
+    last_used: Rc<RefCell<Option<String>>>,
+    settings_writer: SettingsWriter,
+}
+
+#[derive(Debug)]
+struct Connection {
+    address: String,
+    /// Shared with the task forwarding the worker output, which follows the connection state.
+    find_session: Rc<RefCell<FindSession>>,
+    link: Rc<RefCell<Link>>,
+    worker: WorkerController<BluetoothWorker>,
+    /// Shared with the worker, which only takes over or forgets with a token issued here.
+    confirmations: Confirmations,
+    subscribers: Subscribers,
+    /// Task forwarding the worker's output to `subscribers`.
+    forwarder: gtk4::glib::JoinHandle<()>,
+}
+
+impl ConnectionManager {
+    pub fn new(settings_writer: SettingsWriter) -> Self {
+        Self {
+            connections: Rc::default(),
+            last_used: Rc::default(),
+            settings_writer,
+        }
+    }
+
+    /// Sends the output of the device's worker to `output`, along with everything else
+    /// subscribed, starting the worker if needed. Returns the worker's input.
+    ///
+    /// The output stops once its receiver is dropped, e.g. when the device page is closed.
+    pub fn subscribe(
+        &self,
+        device: &DeviceInfo,
+        output: Sender<Arc<BudsWorkerOutput>>,
+    ) -> Sender<BudsWorkerInput> {
+        let mut connections = self.connections.borrow_mut();
+        let connection = connections
+            .entry(device.address.clone())
+            .or_insert_with(|| self.start_connection(device));
+        connection.subscribers.borrow_mut().push(output);
+        connection.worker.sender().clone()
+    }
+
+    /// Where the confirmation dialog issues the tokens the worker of the device asks for
+    /// before a destructive operation, starting the worker if needed.
+    pub fn confirmations(&self, device: &DeviceInfo) -> Confirmations {
+        let mut connections = self.connections.borrow_mut();
+        connections
+            .entry(device.address.clone())
+            .or_insert_with(|| self.start_connection(device))
+            .confirmations
+            .clone()
+    }
+
+    /// Starts or stops the find tone of the device, if it has a connection.
+    pub fn set_find(&self, device: &DeviceInfo, is_playing: bool) {
+        if let Some(connection) = self.connections.borrow().get(&device.address) {
+            connection.find_session.borrow_mut().set_playing(is_playing);
+        }
+    }
+
+    /// Sends a command to the device at `address`, following `policy` if it isn't connected.
+    ///
+    /// Fails if the app has no connection to the device in this session, as it needs one to
+    /// know the device, or if `policy` is `Immediate` and the device isn't connected.
+    pub fn send_with_policy(
+        &self,
+        address: &str,
+        command: BudsCommand,
+        policy: SendPolicy,
+    ) -> Result<(), String> {
+        let connections = self.connections.borrow();
+        let connection = connections
+            .get(address)
+            .ok_or_else(|| format!("No connection to {} in this session", address))?;
+        let worker = connection.worker.sender();
+        let mut link = connection.link.borrow_mut();
+        if link.is_connected {
+            worker.send_or_log(BudsWorkerInput::SendCommand(command));
+            return Ok(());
+        }
+        match policy {
+            SendPolicy::Immediate => return Err("The device isn't connected".to_string()),
+            SendPolicy::QueueUntilConnected => {}
+            SendPolicy::WakeAndSend => {
+                // Already connecting if something was queued for waking before.
+                if !link.is_woken {
+                    link.is_woken = true;
+                    worker.send_or_log(BudsWorkerInput::Connect);
+                }
+            }
+        }
+        debug!(?command, ?policy, "Queueing command until connected");
+        link.queued.push(command);
+        Ok(())
+    }
+
+    /// The address of the device last connected to in this session, even if it disconnected
+    /// since, for commands that don't name a device.
+    pub fn last_used(&self) -> Option<String> {
+        self.last_used.borrow().clone()
+    }
+
+    /// Lists the noise modes queued for the device at `address` that weren't sent yet.
+    pub fn queued_noise_modes(&self, address: &str) -> Vec<NoiseControlMode> {
+        let connections = self.connections.borrow();
+        let Some(connection) = connections.get(address) else {
+            return Vec::new();
+        };
+        let link = connection.link.borrow();
+        link.queued
+            .iter()
+            .filter_map(|command| match command {
+                BudsCommand::SetNoiseControlMode(mode) => Some(*mode),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Stops the worker of the device at `address` and drops everything kept for it, e.g. the
+    /// queued commands, once the device is forgotten.
+    pub fn remove(&self, address: &str) {
+        let Some(connection) = self.connections.borrow_mut().remove(address) else {
+            return;
+        };
+        debug!(address, "Removing the connection");
+        if self.last_used.borrow().as_deref() == Some(address) {
+            self.last_used.replace(None);
+        }
+        let (output, output_receiver) = relm4::channel();
+        connection.subscribers.borrow_mut().push(output);
+        connection.worker.sender().send_or_log(BudsWorkerInput::Disconnect);
+        // Dropping the worker before it handled `Disconnect` would leave the device connected.
+        relm4::spawn_local(async move {
+            while let Some(output) = output_receiver.recv().await {
+                if let BudsWorkerOutput::Disconnected = *output {
+                    break;
+                }
+            }
+            debug!(address = %connection.address, "Connection removed");
+            // It holds senders to the worker, which would keep it alive.
+            connection.forwarder.abort();
+        });
+    }
+
+    /// Stops every find tone still playing, e.g. before quitting.
+    pub fn stop_find(&self) {
+        for connection in self.connections.borrow().values() {
+            let mut find_session = connection.find_session.borrow_mut();
+            if find_session.tone == FindTone::Playing {
+                find_session.set_playing(false);
+            }
+        }
+    }
+
+    /// Makes sure the saved settings of the device at `address` were saved for `model`.
+    ///
+    /// When they were saved for another model, e.g. new buds paired with the address of the
+    /// old ones, they're reset so they aren't applied to buds they weren't meant for. Returns
+    /// whether that happened. Settings saved before the model was recorded are kept.
+    pub fn check_model(&self, address: &str, model: Model) -> bool {
+        let model = format!("{:?}", model);
+        let saved_model = settings::get_device_settings(address).string(DEVICE_MODEL_KEY);
+        if saved_model == model {
+            return false;
+        }
+        let is_mismatch = !saved_model.is_empty();
+        if is_mismatch {
+            info!(%saved_model, %model, "Device model changed, resetting its settings");
+            // Where it's listed and when it was connected are about the address, not the buds.
+            settings::reset_device_settings(
+                address,
+                &[DEVICE_PRIORITY_KEY, DEVICE_LAST_CONNECTED_KEY],
+            );
+        }
+        self.settings_writer
+            .for_device(address)
+            .set_string(DEVICE_MODEL_KEY, &model);
+        is_mismatch
+    }
+
+    fn start_connection(&self, device: &DeviceInfo) -> Connection {
+        let device_settings = self.settings_writer.for_device(&device.address);
+        Connection::start(device.clone(), self.last_used.clone(), device_settings)
+    }
+}
+
+/// When the app was last connected to the device at `address`, if ever.
+pub fn last_connected(address: &str) -> Option<SystemTime> {
+    let secs = settings::get_device_settings(address).int64(DEVICE_LAST_CONNECTED_KEY);
+    (secs > 0).then(|| UNIX_EPOCH + Duration::from_secs(secs as u64))
+}
+
+fn record_last_connected(device_settings: &SettingsWriter) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    device_settings.set_int64(DEVICE_LAST_CONNECTED_KEY, secs as i64);
+}
+
+impl Connection {
+    fn start(
+        device: DeviceInfo,
+        last_used: Rc<RefCell<Option<String>>>,
+        device_settings: SettingsWriter,
+    ) -> Self {
+        debug!(address = %device.address, "Starting Bluetooth worker");
+        let address = device.address.clone();
+        let (output_sender, output_receiver) = relm4::channel();
+        let confirmations = Confirmations::default();
+        let worker = BluetoothWorker::builder()
+            .detach_worker((device, confirmations.clone()))
+            .forward(&output_sender, |output| output);
+
+        let find_session = Rc::new(RefCell::new(FindSession {
+            worker: worker.sender().clone(),
+            tone: FindTone::Stopped,
+        }));
+        let link = Rc::new(RefCell::new(Link::default()));
+        let subscribers = Subscribers::default();
+        let forwarder = relm4::spawn_local({
+            let find_session = find_session.clone();
+            let link = link.clone();
+            let worker = worker.sender().clone();
+            let subscribers = subscribers.clone();
+            let address = address.clone();
+            async move {
+                while let Some(output) = output_receiver.recv().await {
+                    match &output {
+                        BudsWorkerOutput::Connected => {
+                            record_last_connected(&device_settings);
+                            *last_used.borrow_mut() = Some(address.clone());
+                        }
+                        // Commands sent before this would go to a device that isn't listening.
+                        BudsWorkerOutput::HandshakeDone => {
+                            find_session.borrow_mut().connected();
+                            link.borrow_mut().connected(&worker);
+                        }
+                        // Until now, it was still connected.
+                        BudsWorkerOutput::Disconnected => {
+                            record_last_connected(&device_settings);
+                            find_session.borrow_mut().connection_lost();
+                            link.borrow_mut().is_connected = false;
+                        }
+                        BudsWorkerOutput::ProfileInUse | BudsWorkerOutput::Error(_) => {
+                            find_session.borrow_mut().connection_lost();
+                            let mut link = link.borrow_mut();
+                            link.is_connected = false;
+                            // Waking failed; what's queued waits for the next connection.
+                            link.is_woken = false;
+                        }
+                        _ => {}
+                    }
+                    let output = Arc::new(output);
+                    let mut subscribers = subscribers.borrow_mut();
+                    subscribers.retain(|subscriber| subscriber.send(output.clone()).is_ok());
+                    if subscribers.is_empty() {
+                        debug!("No subscriber for {:?}", output);
+                    }
+                }
+            }
+        });
+
+        Self {
+            address,
+            find_session,
+            link,
+            worker,
+            confirmations,
+            subscribers,
+            forwarder,
+        }
+    }
+}
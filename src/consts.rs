@@ -1,3 +1,41 @@
 pub const APP_ID: &str = "com.github.rodrigost23.GalaxyBudsGui";
+pub const DEVICE_SCHEMA_ID: &str = "com.github.rodrigost23.GalaxyBudsGui.Device";
 pub const DEVICE_ADDRESS_KEY: &str = "device-address";
+pub const DEVICE_LABEL_KEY: &str = "label";
+pub const DEVICE_EMOJI_KEY: &str = "emoji";
+pub const DEVICE_COLOR_KEY: &str = "color";
+pub const DEVICE_PRIORITY_KEY: &str = "priority";
+pub const DEVICE_LAST_CONNECTED_KEY: &str = "last-connected";
+pub const DEVICE_MODEL_KEY: &str = "model";
+pub const MANUAL_CONNECT_DEVICES_KEY: &str = "manual-connect-devices";
+pub const ONBOARDING_DONE_KEY: &str = "onboarding-done";
+pub const LAST_SEEN_VERSION_KEY: &str = "last-seen-version";
+pub const NOTIFY_CASE_OPEN_KEY: &str = "notify-case-open";
+pub const WINDOW_MAXIMIZED_KEY: &str = "window-maximized";
+pub const WINDOW_MONITOR_KEY: &str = "window-monitor";
+pub const LAST_SUBPAGE_KEY: &str = "last-subpage";
+pub const BLOCK_MICROPHONE_KEY: &str = "block-microphone";
+pub const NOISE_OFF_IN_CASE_KEY: &str = "noise-off-in-case";
+pub const ANC_SHORTCUT_KEY: &str = "anc-shortcut";
+pub const SIDEBAR_LAYOUT_KEY: &str = "sidebar-layout";
+pub const LOG_TO_FILE_KEY: &str = "log-to-file";
+pub const LOG_LEVEL_KEY: &str = "log-level";
+pub const REDACT_ADDRESSES_KEY: &str = "redact-addresses";
+pub const EXPERIMENTAL_FEATURES_KEY: &str = "experimental-features";
+pub const TCP_BRIDGE_KEY: &str = "debug-tcp-bridge";
+pub const FAULTS_KEY: &str = "debug-faults";
+pub const INSPECTOR_WIDTH_KEY: &str = "inspector-width";
+pub const INSPECTOR_HEIGHT_KEY: &str = "inspector-height";
+pub const INSPECTOR_PANE_POSITION_KEY: &str = "inspector-pane-position";
+pub const INSPECTOR_ID_FILTER_KEY: &str = "inspector-id-filter";
+pub const INSPECTOR_AUTOSCROLL_KEY: &str = "inspector-autoscroll";
+pub const INSPECTOR_DECODER_KEY: &str = "inspector-decoder";
 pub const SAMSUNG_SPP_UUID: &str = "2e73a4ad-332d-41fc-90e2-16bef06523f2";
+/// SPP services the buds can be reached by, in the order they're looked for: the UUID, and
+/// for UUIDs other devices use too, what the device name must contain, in lowercase.
+pub const KNOWN_SPP_SERVICES: &[(&str, Option<&str>)] = &[
+    (SAMSUNG_SPP_UUID, None),
+    // The original Galaxy Buds use the standard serial port service.
+    ("00001101-0000-1000-8000-00805f9b34fb", Some("galaxy buds (")),
+];
+pub const ISSUES_URL: &str = "https://github.com/rodrigost23/galaxy-buds-gui-rs/issues";
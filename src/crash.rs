@@ -0,0 +1,172 @@
+use std::{
+    backtrace::Backtrace,
+    cell::Cell,
+    panic::{PanicHookInfo, UnwindSafe},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use adw::prelude::{AdwDialogExt, AlertDialogExt, GtkApplicationExt};
+use tracing::{debug, error};
+
+use crate::{
+    consts::ISSUES_URL,
+    logging::{redacts_addresses, state_dir},
+    redact, session_snapshot,
+};
+
+/// Longest report body put in the issue URL; browsers and servers reject very long URLs.
+const MAX_URL_REPORT_LENGTH: usize = 4000;
+
+thread_local! {
+    /// Set while running code whose panics are caught and handled as errors.
+    static IS_PANIC_EXPECTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f`, returning an error instead of unwinding if it panics, without reporting a crash.
+///
+/// For parsers that index into their input without checking its length.
+pub fn catch_expected_panic<R>(f: impl FnOnce() -> R + UnwindSafe) -> std::thread::Result<R> {
+    IS_PANIC_EXPECTED.set(true);
+    let result = std::panic::catch_unwind(f);
+    IS_PANIC_EXPECTED.set(false);
+    result
+}
+
+/// Installs a panic hook that saves a crash report and offers to report it upstream.
+///
+/// The default hook still runs first, so the panic is printed to the terminal as before.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if IS_PANIC_EXPECTED.get() {
+            debug!("Caught an expected panic: {}", info);
+            return;
+        }
+        default_hook(info);
+
+        let mut report = crash_report(info);
+        if redacts_addresses() {
+            report = redact::identifiers(&report).into_owned();
+        }
+        let path = write_report(&report);
+        error!("Crashed, report saved to {:?}", path);
+        // So the next launch can offer to restore the session as it was right now.
+        session_snapshot::save();
+
+        if gtk4::is_initialized_main_thread() {
+            // The panic will abort once it unwinds into GTK, so keep the process
+            // alive until the user has seen the dialog.
+            show_crash_dialog(report, path, true);
+        } else if gtk4::is_initialized() {
+            // Worker threads can die without taking the app down; show it asynchronously.
+            gtk4::glib::MainContext::default()
+                .invoke(move || show_crash_dialog(report, path, false));
+        }
+    }));
+}
+
+fn crash_report(info: &PanicHookInfo) -> String {
+    let thread = std::thread::current();
+    format!(
+        "Version: {} ({})\nThread: {}\n\n{}\n\nBacktrace:\n{}",
+        env!("CARGO_PKG_VERSION"),
+        env!("GIT_COMMIT"),
+        thread.name().unwrap_or("<unnamed>"),
+        info,
+        Backtrace::force_capture()
+    )
+}
+
+fn write_report(report: &str) -> Option<PathBuf> {
+    let dir = state_dir().join("crashes");
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = dir.join(format!("crash-{}.txt", timestamp));
+
+    let result = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, report));
+    match result {
+        Ok(()) => Some(path),
+        Err(e) => {
+            error!("Failed to write crash report: {}", e);
+            None
+        }
+    }
+}
+
+/// Builds a new issue URL with the report prefilled.
+fn issue_url(report: &str) -> String {
+    let body = if report.len() > MAX_URL_REPORT_LENGTH {
+        let mut end = MAX_URL_REPORT_LENGTH;
+        while !report.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("```\n{}\n```\n(truncated)", &report[..end])
+    } else {
+        format!("```\n{}\n```", report)
+    };
+    format!(
+        "{}/new?title={}&body={}",
+        ISSUES_URL,
+        gtk4::glib::Uri::escape_string("Crash report", None, false),
+        gtk4::glib::Uri::escape_string(&body, None, false)
+    )
+}
+
+/// Shows the crash dialog, blocking in a nested main loop until it is closed if `wait` is set.
+fn show_crash_dialog(report: String, path: Option<PathBuf>, wait: bool) {
+    let body = match &path {
+        Some(path) => format!(
+            "A crash report was saved to {}. Reporting it helps get the problem fixed.",
+            path.display()
+        ),
+        None => "Reporting the crash helps get the problem fixed.".to_string(),
+    };
+
+    let dialog = adw::AlertDialog::new(Some("Galaxy Buds Manager Crashed"), Some(&body));
+    dialog.add_response("close", "Close");
+    dialog.add_response("report", "Report Issue");
+    dialog.set_response_appearance("report", adw::ResponseAppearance::Suggested);
+    dialog.set_close_response("close");
+
+    let main_loop = gtk4::glib::MainLoop::new(None, false);
+    let window = relm4::main_application().active_window();
+    dialog.connect_response(None, {
+        let main_loop = main_loop.clone();
+        let window = window.clone();
+        move |_, response| {
+            if response == "report" {
+                gtk4::UriLauncher::new(&issue_url(&report)).launch(
+                    window.as_ref(),
+                    gtk4::gio::Cancellable::NONE,
+                    |_| {},
+                );
+            }
+            main_loop.quit();
+        }
+    });
+    dialog.present(window.as_ref());
+
+    if wait {
+        main_loop.run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_panics_are_returned_as_errors() {
+        let result = catch_expected_panic(|| -> u8 { panic!("Frame too short") });
+        assert!(result.is_err());
+        assert!(!IS_PANIC_EXPECTED.get());
+    }
+
+    #[test]
+    fn results_pass_through() {
+        assert_eq!(catch_expected_panic(|| 1).ok(), Some(1));
+    }
+}
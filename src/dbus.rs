@@ -0,0 +1,316 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use galaxy_buds_rs::message::bud_property::NoiseControlMode;
+use gtk4::{
+    gio::{self, prelude::ApplicationExt},
+    glib::{Variant, prelude::ToVariant},
+};
+use relm4::Sender;
+use tracing::{debug, error, warn};
+
+use crate::{
+    app::main::AppInput, connection_manager::SendPolicy, lock_ext::LockOrRecover,
+    metrics::SharedMetrics, model::buds_status::BudsStatus, sender_ext::SendOrLog,
+};
+
+const METRICS_PATH: &str = "/com/github/rodrigost23/GalaxyBudsGui/Metrics";
+const METRICS_INTERFACE: &str = "com.github.rodrigost23.GalaxyBudsGui.Metrics";
+const METRICS_XML: &str = r#"
+<node>
+  <interface name="com.github.rodrigost23.GalaxyBudsGui.Metrics">
+    <!-- Seconds since the current connection was established, 0 when disconnected -->
+    <property name="ConnectionUptime" type="t" access="read"/>
+    <!-- Connections established after the first one in this session -->
+    <property name="ReconnectCount" type="u" access="read"/>
+    <!-- Keep-alive messages received from the buds in this session -->
+    <property name="KeepAliveCount" type="u" access="read"/>
+    <!-- Request acknowledgements received from the buds in this session -->
+    <property name="AcknowledgementCount" type="u" access="read"/>
+  </interface>
+</node>
+"#;
+
+/// Exports the connection metrics on the application's session bus connection.
+///
+/// Must be called after the application is registered, e.g. from a component's `init`.
+pub fn export_metrics(metrics: SharedMetrics) {
+    let Some(connection) = relm4::main_application().dbus_connection() else {
+        warn!("No D-Bus connection, connection metrics won't be exported");
+        return;
+    };
+
+    let interface = gio::DBusNodeInfo::for_xml(METRICS_XML)
+        .ok()
+        .and_then(|node| node.lookup_interface(METRICS_INTERFACE))
+        .expect("Metrics interface is defined in the XML string");
+
+    let result = connection
+        .register_object(METRICS_PATH, &interface)
+        .property(move |_, _, _, _, property| metrics_property(&metrics, property))
+        .build();
+
+    match result {
+        Ok(_) => debug!("Connection metrics exported at {}", METRICS_PATH),
+        Err(e) => error!("Failed to export connection metrics: {}", e),
+    }
+}
+
+fn metrics_property(metrics: &SharedMetrics, property: &str) -> Variant {
+    let metrics = metrics.lock_or_recover();
+    match property {
+        "ConnectionUptime" => metrics.uptime().as_secs().to_variant(),
+        "ReconnectCount" => metrics.reconnect_count().to_variant(),
+        "KeepAliveCount" => metrics.keep_alive_count().to_variant(),
+        "AcknowledgementCount" => metrics.acknowledgement_count().to_variant(),
+        _ => unknown_property(property),
+    }
+}
+
+/// Answers a read of a property that isn't in the interface.
+///
+/// GDBus rejects those before asking, so this is only a fallback. The callback can't return
+/// an error, but the empty tuple matches no property type, so GDBus still fails the call.
+fn unknown_property(property: &str) -> Variant {
+    warn!(property, "Asked for a property that isn't in the interface");
+    ().to_variant()
+}
+
+const BUDS_PATH: &str = "/com/github/rodrigost23/GalaxyBudsGui/Buds";
+const BUDS_INTERFACE: &str = "com.github.rodrigost23.GalaxyBudsGui.Buds";
+const BUDS_XML: &str = include_str!("../data/com.github.rodrigost23.GalaxyBudsGui.Buds.xml");
+
+/// Values of the properties of the Buds interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudsProperties {
+    pub connected: bool,
+    pub battery_left: i32,
+    pub battery_right: i32,
+    pub battery_case: i32,
+    pub noise_mode: Option<NoiseControlMode>,
+}
+
+impl Default for BudsProperties {
+    fn default() -> Self {
+        Self {
+            connected: false,
+            battery_left: -1,
+            battery_right: -1,
+            battery_case: -1,
+            noise_mode: None,
+        }
+    }
+}
+
+impl From<&BudsStatus> for BudsProperties {
+    fn from(status: &BudsStatus) -> Self {
+        Self {
+            connected: true,
+            battery_left: status.battery_left().map_or(-1, Into::into),
+            battery_right: status.battery_right().map_or(-1, Into::into),
+            battery_case: status.battery_case().map_or(-1, Into::into),
+            noise_mode: status.noise_control_mode(),
+        }
+    }
+}
+
+impl BudsProperties {
+    /// The value of `property`, or `None` if it isn't in the interface.
+    fn get(&self, property: &str) -> Option<Variant> {
+        let value = match property {
+            "Connected" => self.connected.to_variant(),
+            "BatteryLeft" => self.battery_left.to_variant(),
+            "BatteryRight" => self.battery_right.to_variant(),
+            "BatteryCase" => self.battery_case.to_variant(),
+            "NoiseMode" => self.noise_mode.map_or("", noise_mode_name).to_variant(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Lists the properties whose values differ from `other`, with their new values.
+    fn changes(&self, other: &BudsProperties) -> HashMap<String, Variant> {
+        ["Connected", "BatteryLeft", "BatteryRight", "BatteryCase", "NoiseMode"]
+            .into_iter()
+            .filter_map(|property| Some((property, self.get(property)?, other.get(property)?)))
+            .filter(|(_, value, old_value)| value != old_value)
+            .map(|(property, value, _)| (property.to_string(), value))
+            .collect()
+    }
+}
+
+/// The Buds interface exported on the session bus.
+#[derive(Debug)]
+pub struct BudsService {
+    connection: gio::DBusConnection,
+    properties: Arc<Mutex<BudsProperties>>,
+}
+
+impl BudsService {
+    /// Exports the Buds interface, forwarding `SetNoiseMode` calls to `sender`.
+    ///
+    /// Must be called after the application is registered, e.g. from a component's `init`.
+    pub fn export(sender: Sender<AppInput>) -> Option<Self> {
+        let Some(connection) = relm4::main_application().dbus_connection() else {
+            warn!("No D-Bus connection, the Buds interface won't be exported");
+            return None;
+        };
+
+        let interface = gio::DBusNodeInfo::for_xml(BUDS_XML)
+            .ok()
+            .and_then(|node| node.lookup_interface(BUDS_INTERFACE))
+            .expect("Buds interface is defined in the XML file");
+
+        let properties = Arc::new(Mutex::new(BudsProperties::default()));
+        let result = connection
+            .register_object(BUDS_PATH, &interface)
+            .method_call({
+                let properties = properties.clone();
+                move |_, _, _, _, method, parameters, invocation| {
+                    buds_method_call(&sender, &properties, method, parameters, invocation)
+                }
+            })
+            .property({
+                let properties = properties.clone();
+                move |_, _, _, _, property| {
+                    let value = properties.lock_or_recover().get(property);
+                    value.unwrap_or_else(|| unknown_property(property))
+                }
+            })
+            .build();
+
+        match result {
+            Ok(_) => {
+                debug!("Buds interface exported at {}", BUDS_PATH);
+                Some(Self {
+                    connection,
+                    properties,
+                })
+            }
+            Err(e) => {
+                error!("Failed to export the Buds interface: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Updates the exported properties, emitting `PropertiesChanged` for the ones that changed.
+    pub fn update(&self, new_properties: BudsProperties) {
+        let changes = {
+            let mut properties = self.properties.lock_or_recover();
+            let changes = new_properties.changes(&properties);
+            *properties = new_properties;
+            changes
+        };
+        if changes.is_empty() {
+            return;
+        }
+
+        let parameters = (BUDS_INTERFACE, changes, Vec::<String>::new()).to_variant();
+        if let Err(e) = self.connection.emit_signal(
+            None,
+            BUDS_PATH,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            Some(&parameters),
+        ) {
+            error!("Failed to emit PropertiesChanged: {}", e);
+        }
+    }
+}
+
+fn buds_method_call(
+    sender: &Sender<AppInput>,
+    properties: &Mutex<BudsProperties>,
+    method: &str,
+    parameters: Variant,
+    invocation: gio::DBusMethodInvocation,
+) {
+    match method {
+        "SetNoiseMode" => {
+            let Some((mode,)) = parameters.get::<(String,)>() else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    "Expected a single string argument",
+                );
+                return;
+            };
+            let Some(mode) = parse_noise_mode(&mode) else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    &format!("Unknown noise mode \"{}\", expected off, ambient or anc", mode),
+                );
+                return;
+            };
+            if !properties.lock_or_recover().connected {
+                invocation
+                    .return_dbus_error("org.freedesktop.DBus.Error.Failed", "No buds connected");
+                return;
+            }
+            sender.send_or_log(AppInput::SetNoiseMode(mode));
+            invocation.return_value(None);
+        }
+        "SendNoiseMode" => {
+            let Some((mode, policy)) = parameters.get::<(String, String)>() else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    "Expected a mode and a policy",
+                );
+                return;
+            };
+            let Some(mode) = parse_noise_mode(&mode) else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    &format!("Unknown noise mode \"{}\", expected off, ambient or anc", mode),
+                );
+                return;
+            };
+            let Some(policy) = parse_send_policy(&policy) else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    &format!("Unknown policy \"{}\", expected immediate, queue or wake", policy),
+                );
+                return;
+            };
+            // Answered once the command was handed to the connection.
+            sender.send_or_log(AppInput::SendNoiseMode {
+                mode,
+                policy,
+                invocation,
+            });
+        }
+        // GDBus rejects those before calling, so this is only a fallback.
+        _ => invocation.return_dbus_error(
+            "org.freedesktop.DBus.Error.UnknownMethod",
+            &format!("No method {} in the Buds interface", method),
+        ),
+    }
+}
+
+pub fn noise_mode_name(mode: NoiseControlMode) -> &'static str {
+    match mode {
+        NoiseControlMode::Off => "off",
+        NoiseControlMode::AmbientSound => "ambient",
+        NoiseControlMode::NoiseReduction => "anc",
+    }
+}
+
+fn parse_send_policy(name: &str) -> Option<SendPolicy> {
+    match name {
+        "immediate" => Some(SendPolicy::Immediate),
+        "queue" => Some(SendPolicy::QueueUntilConnected),
+        "wake" => Some(SendPolicy::WakeAndSend),
+        _ => None,
+    }
+}
+
+pub fn parse_noise_mode(name: &str) -> Option<NoiseControlMode> {
+    match name {
+        "off" => Some(NoiseControlMode::Off),
+        "ambient" => Some(NoiseControlMode::AmbientSound),
+        "anc" => Some(NoiseControlMode::NoiseReduction),
+        _ => None,
+    }
+}
@@ -0,0 +1,59 @@
+//! Faults injected into the connection to the device, so the reconnect logic and the error
+//! paths of the UI can be tested on demand.
+//!
+//! Set in the hidden debug-faults GSettings key, while `Feature::FaultInjection` is enabled.
+
+use std::time::Duration;
+
+/// The faults to inject, parsed from a comma separated list such as
+/// `drop-every=5,write-delay-ms=500,fail-first-connect`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Faults {
+    /// Drops every nth frame received from the device, before it's parsed.
+    pub drop_every: Option<u32>,
+    /// Waits this long before every write to the device.
+    pub write_delay: Option<Duration>,
+    /// Fails the first connection attempt of the worker, as if the device didn't answer.
+    pub fail_first_connect: bool,
+}
+
+impl Faults {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut faults = Self::default();
+        for fault in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|fault| !fault.is_empty())
+        {
+            let (name, value) = match fault.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim())),
+                None => (fault, None),
+            };
+            match (name, value) {
+                ("drop-every", Some(value)) => {
+                    let every = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|every| *every > 0)
+                        .ok_or_else(|| format!("Invalid frame count: {}", value))?;
+                    faults.drop_every = Some(every);
+                }
+                ("write-delay-ms", Some(value)) => {
+                    let millis = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("Invalid delay: {}", value))?;
+                    faults.write_delay = Some(Duration::from_millis(millis));
+                }
+                ("fail-first-connect", None) => faults.fail_first_connect = true,
+                _ => return Err(format!("Unknown fault: {}", fault)),
+            }
+        }
+        Ok(faults)
+    }
+
+    /// Whether the `index`th received frame, counting from 1, is to be dropped.
+    pub fn drops_frame(&self, index: u64) -> bool {
+        self.drop_every
+            .is_some_and(|every| index % u64::from(every) == 0)
+    }
+}
@@ -0,0 +1,73 @@
+//! Experimental features, which ship disabled and can be turned on without rebuilding.
+//!
+//! The enabled ones are listed in a hidden GSettings key, toggled from the developer
+//! section of the preferences.
+
+use gtk4::gio::prelude::SettingsExt;
+use tracing::error;
+
+use crate::consts::EXPERIMENTAL_FEATURES_KEY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Connecting through a TCP bridge instead of RFCOMM, see `BluetoothWorker`.
+    TcpBridge,
+    /// Injecting the faults in the debug-faults key into the connection, see `faults`.
+    FaultInjection,
+}
+
+impl Feature {
+    pub const ALL: &[Feature] = &[Feature::TcpBridge, Feature::FaultInjection];
+
+    /// The name of the feature in the settings.
+    fn id(self) -> &'static str {
+        match self {
+            Feature::TcpBridge => "tcp-bridge",
+            Feature::FaultInjection => "fault-injection",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Feature::TcpBridge => "TCP Bridge Transport",
+            Feature::FaultInjection => "Fault Injection",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Feature::TcpBridge => {
+                "Connect to the address in the debug-tcp-bridge key instead of Bluetooth"
+            }
+            Feature::FaultInjection => {
+                "Drop frames, delay writes or fail connecting, as set in the debug-faults key"
+            }
+        }
+    }
+
+    pub fn is_enabled(self, settings: &gtk4::gio::Settings) -> bool {
+        settings
+            .strv(EXPERIMENTAL_FEATURES_KEY)
+            .iter()
+            .any(|id| id.as_str() == self.id())
+    }
+
+    pub fn set_enabled(self, settings: &gtk4::gio::Settings, enabled: bool) {
+        let mut ids: Vec<String> = settings
+            .strv(EXPERIMENTAL_FEATURES_KEY)
+            .iter()
+            .map(|id| id.to_string())
+            .filter(|id| id != self.id())
+            .collect();
+        if enabled {
+            ids.push(self.id().to_string());
+        }
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+        if let Err(e) = settings.set_strv(EXPERIMENTAL_FEATURES_KEY, ids.as_slice()) {
+            error!(
+                feature = self.id(),
+                "Failed to save the experimental features: {}", e
+            );
+        }
+    }
+}
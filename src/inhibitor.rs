@@ -0,0 +1,32 @@
+use gtk4::prelude::{GtkApplicationExt, IsA};
+use tracing::warn;
+
+/// Keeps the system from suspending for as long as it is alive.
+#[derive(Debug)]
+pub struct SuspendInhibitor {
+    cookie: u32,
+}
+
+impl SuspendInhibitor {
+    /// Asks the session not to suspend, giving `reason` to the user if they try anyway.
+    ///
+    /// Returns `None` if the session doesn't support inhibiting.
+    pub fn new(window: Option<&impl IsA<gtk4::Window>>, reason: &str) -> Option<Self> {
+        let cookie = relm4::main_application().inhibit(
+            window,
+            gtk4::ApplicationInhibitFlags::SUSPEND,
+            Some(reason),
+        );
+        if cookie == 0 {
+            warn!("Failed to inhibit suspend: {}", reason);
+            return None;
+        }
+        Some(Self { cookie })
+    }
+}
+
+impl Drop for SuspendInhibitor {
+    fn drop(&mut self) {
+        relm4::main_application().uninhibit(self.cookie);
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Extension for mutexes shared with threads that may panic while holding them.
+pub trait LockOrRecover<T> {
+    /// Locks the mutex, taking the data back if a thread panicked while holding it.
+    ///
+    /// What's shared this way is plain state that's valid after every write, e.g. counters
+    /// and timestamps, so a panic elsewhere isn't worth crashing the app over.
+    fn lock_or_recover(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> LockOrRecover<T> for Mutex<T> {
+    fn lock_or_recover(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn recovers_the_data_after_a_panic_while_locked() {
+        let counter = Arc::new(Mutex::new(1));
+        let result = std::thread::spawn({
+            let counter = counter.clone();
+            move || {
+                let _guard = counter.lock_or_recover();
+                panic!("Panicking while holding the lock");
+            }
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert!(counter.is_poisoned());
+        *counter.lock_or_recover() += 1;
+        assert_eq!(*counter.lock_or_recover(), 2);
+    }
+}
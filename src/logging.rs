@@ -0,0 +1,192 @@
+use std::{
+    io,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use adw::gio::prelude::SettingsExt;
+use tracing::{error, level_filters::LevelFilter, warn};
+use tracing_appender::{
+    non_blocking::{NonBlocking, WorkerGuard},
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry,
+    fmt::{
+        self,
+        writer::{EitherWriter, MakeWriter},
+    },
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt,
+};
+
+use crate::{
+    consts::{APP_ID, LOG_LEVEL_KEY, LOG_TO_FILE_KEY, REDACT_ADDRESSES_KEY},
+    lock_ext::LockOrRecover,
+    redact, settings,
+};
+
+/// Number of daily log files kept before the oldest is deleted.
+const MAX_LOG_FILES: usize = 7;
+
+/// Levels offered for the log file, in the order shown in the preferences.
+pub const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// The log file, opened and closed as the settings change.
+struct FileLog {
+    /// `None` while logging to a file is turned off. Dropping the guard flushes the file.
+    file: Arc<Mutex<Option<(NonBlocking, WorkerGuard)>>>,
+    filter: reload::Handle<EnvFilter, Registry>,
+}
+
+static FILE_LOG: OnceLock<FileLog> = OnceLock::new();
+
+/// Mirrors the redact-addresses setting, for writers that can't read the settings themselves.
+static REDACT_ADDRESSES: AtomicBool = AtomicBool::new(false);
+
+/// Whether Bluetooth addresses are to be hashed in whatever is written for the user to share.
+pub fn redacts_addresses() -> bool {
+    REDACT_ADDRESSES.load(Ordering::Relaxed)
+}
+
+/// Writes to the log file while it's open, and nowhere otherwise.
+#[derive(Clone)]
+struct FileWriter(Arc<Mutex<Option<(NonBlocking, WorkerGuard)>>>);
+
+impl<'a> MakeWriter<'a> for FileWriter {
+    type Writer = RedactingWriter<EitherWriter<NonBlocking, io::Sink>>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(match &*self.0.lock_or_recover() {
+            Some((writer, _)) => EitherWriter::A(writer.clone()),
+            None => EitherWriter::B(io::sink()),
+        })
+    }
+}
+
+/// Hashes the addresses and serial numbers in each line before passing it on, while enabled
+/// in the settings.
+///
+/// Relies on every event being written at once, which is what the fmt layer does.
+struct RedactingWriter<W>(W);
+
+impl<W: io::Write> io::Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !redacts_addresses() {
+            return self.0.write(buf);
+        }
+        let line = String::from_utf8_lossy(buf);
+        self.0.write_all(redact::identifiers(&line).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Flushes and closes the log file when dropped, so it must be kept until exit.
+pub struct LogGuard;
+
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        if let Some(file_log) = FILE_LOG.get() {
+            file_log.file.lock_or_recover().take();
+        }
+    }
+}
+
+/// Sets up logging to the terminal and, if enabled in the settings, to a rotating log file.
+pub fn init() -> LogGuard {
+    let console_filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env()
+        .unwrap()
+        .add_directive("relm4=error".parse().unwrap());
+    let console_layer = fmt::layer().compact().with_filter(console_filter);
+
+    let file = Arc::new(Mutex::new(None));
+    let (file_filter, filter) = reload::Layer::new(EnvFilter::new("off"));
+    let file_layer = fmt::layer()
+        .with_writer(FileWriter(file.clone()))
+        .with_ansi(false)
+        .with_filter(file_filter);
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(console_layer)
+        .init();
+
+    let _ = FILE_LOG.set(FileLog { file, filter });
+    apply_settings(&settings::get_settings());
+
+    LogGuard
+}
+
+/// Opens or closes the log file and sets its level as in the settings, e.g. after they changed.
+pub fn apply_settings(settings: &gtk4::gio::Settings) {
+    let Some(file_log) = FILE_LOG.get() else {
+        return;
+    };
+
+    REDACT_ADDRESSES.store(settings.boolean(REDACT_ADDRESSES_KEY), Ordering::Relaxed);
+    let enabled = settings.boolean(LOG_TO_FILE_KEY);
+    let open_result = {
+        // Nothing may be logged while this is locked, as writing a log line locks it too.
+        let mut file = file_log.file.lock_or_recover();
+        if !enabled {
+            *file = None;
+            Ok(())
+        } else if file.is_none() {
+            file_appender().map(|appender| *file = Some(tracing_appender::non_blocking(appender)))
+        } else {
+            Ok(())
+        }
+    };
+    // With no file open, this only reaches the terminal.
+    if let Err(e) = open_result {
+        error!("Could not open the log file: {}", e);
+    }
+
+    let filter = if enabled {
+        let level = settings
+            .string(LOG_LEVEL_KEY)
+            .parse()
+            .unwrap_or(LevelFilter::INFO);
+        EnvFilter::default()
+            .add_directive(level.into())
+            .add_directive("relm4=error".parse().unwrap())
+    } else {
+        EnvFilter::new("off")
+    };
+    if let Err(e) = file_log.filter.reload(filter) {
+        warn!("Could not change the log file level: {}", e);
+    }
+}
+
+/// Directory for the app's state files (logs, crash reports), under the XDG state directory.
+pub fn state_dir() -> PathBuf {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| gtk4::glib::home_dir().join(".local/state"));
+    state_dir.join(APP_ID)
+}
+
+/// Directory the log files are written to.
+pub fn log_dir() -> PathBuf {
+    state_dir().join("logs")
+}
+
+fn file_appender() -> Result<RollingFileAppender, tracing_appender::rolling::InitError> {
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("galaxy-buds-gui")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir())
+}
@@ -12,11 +12,19 @@ macro_rules! define_page_enum {
     ) => {
         define_page_enum!($enum_name { $($page_name($controller_type)),+ });
 
-        #[derive(Debug)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         pub enum $identifier_enum {
             $($page_name),+
         }
 
+        impl $enum_name {
+            pub fn id(&self) -> $identifier_enum {
+                match self {
+                    $($enum_name::$page_name(_) => $identifier_enum::$page_name),+
+                }
+            }
+        }
+
     };
     // This pattern expects `Name { Page(Type), ... }` inside the macro's parentheses.
     ($enum_name:ident { $($page_name:ident($controller_type:ty)),+ $(,)? }) => {
@@ -1,27 +1,41 @@
 mod app;
+mod audio;
+mod backoff;
+mod bluetooth;
+mod btsnoop;
 mod buds_worker;
+mod connection_manager;
 mod consts;
+mod crash;
+mod dbus;
+mod faults;
+mod features;
+mod inhibitor;
+mod lock_ext;
+mod logging;
 mod macros;
+mod metrics;
 mod model;
+mod notifications;
+mod redact;
+mod sender_ext;
+mod session_snapshot;
 mod settings;
+mod shortcuts;
+mod telephony;
+mod throttle;
+mod tuning;
+mod window_state;
 
 use crate::app::main::{AppInit, AppModel};
 use relm4::RelmApp;
-use tracing::level_filters::LevelFilter;
-use tracing_subscriber::EnvFilter;
 
 fn main() {
-    let filter = EnvFilter::builder()
-        .with_default_directive(LevelFilter::INFO.into())
-        .from_env()
-        .unwrap()
-        .add_directive("relm4=error".parse().unwrap());
-
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .compact()
-        .init();
+    let _log_guard = logging::init();
+    crash::install_panic_hook();
+    tuning::get();
 
     let app = RelmApp::new(consts::APP_ID);
+    relm4::set_global_css(include_str!("style.css"));
     app.run::<AppModel>(AppInit {});
 }
@@ -0,0 +1,59 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::model::buds_message::SessionControl;
+
+/// Connection statistics kept for the whole session, across reconnects and device pages.
+#[derive(Debug, Default)]
+pub struct ConnectionMetrics {
+    connected_since: Option<Instant>,
+    connection_count: u32,
+    keep_alive_count: u32,
+    acknowledgement_count: u32,
+}
+
+pub type SharedMetrics = Arc<Mutex<ConnectionMetrics>>;
+
+impl ConnectionMetrics {
+    pub fn record_connected(&mut self) {
+        if self.connected_since.is_none() {
+            self.connected_since = Some(Instant::now());
+            self.connection_count += 1;
+        }
+    }
+
+    pub fn record_disconnected(&mut self) {
+        self.connected_since = None;
+    }
+
+    pub fn record_session_control(&mut self, message: SessionControl) {
+        match message {
+            SessionControl::KeepAlive => self.keep_alive_count += 1,
+            SessionControl::Acknowledgement { .. } => self.acknowledgement_count += 1,
+        }
+    }
+
+    /// How long the current connection has been up, or zero when disconnected.
+    pub fn uptime(&self) -> Duration {
+        self.connected_since
+            .map(|since| since.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// Number of connections established after the first one.
+    pub fn reconnect_count(&self) -> u32 {
+        self.connection_count.saturating_sub(1)
+    }
+
+    /// Number of keep-alives received in this session.
+    pub fn keep_alive_count(&self) -> u32 {
+        self.keep_alive_count
+    }
+
+    /// Number of request acknowledgements received in this session.
+    pub fn acknowledgement_count(&self) -> u32 {
+        self.acknowledgement_count
+    }
+}
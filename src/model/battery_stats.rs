@@ -0,0 +1,483 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{Value, json};
+use tracing::error;
+
+use crate::{
+    logging::state_dir,
+    model::{buds_status::BudsStatus, device_info::DeviceInfo},
+};
+
+/// Minimum drop, in percentage points, of the faster-draining bud before comparing drain rates.
+const MIN_DRAIN_FOR_HINT: i8 = 10;
+/// How much faster, in percent, one bud must drain before it's worth pointing out.
+const IMBALANCE_THRESHOLD: i32 = 20;
+/// Maximum number of samples kept in the history; older ones are dropped first.
+const MAX_SAMPLES: usize = 10_000;
+/// How far back the discharge rate is measured when estimating the time left.
+const ESTIMATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// Fewest samples in `ESTIMATE_WINDOW` for the discharge rate to mean anything.
+const MIN_ESTIMATE_SAMPLES: usize = 3;
+
+/// Battery levels at a point in time.
+#[derive(Debug, Clone)]
+pub struct BatterySample {
+    pub time: SystemTime,
+    pub left: i8,
+    pub right: i8,
+    pub case: i8,
+}
+
+impl BatterySample {
+    fn unix_time(&self) -> u64 {
+        self.time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    fn timestamp(&self) -> String {
+        let secs = self.unix_time();
+        gtk4::glib::DateTime::from_unix_local(secs as i64)
+            .and_then(|time| time.format_iso8601())
+            .map(|time| time.to_string())
+            .unwrap_or_else(|_| secs.to_string())
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "time": self.unix_time(),
+            "left": self.left,
+            "right": self.right,
+            "case": self.case,
+        })
+    }
+
+    fn from_json(json: &Value) -> Option<Self> {
+        let level = |key: &str| i8::try_from(json[key].as_i64()?).ok();
+        Some(Self {
+            time: UNIX_EPOCH + Duration::from_secs(json["time"].as_u64()?),
+            left: level("left")?,
+            right: level("right")?,
+            case: level("case")?,
+        })
+    }
+}
+
+/// File formats the battery history can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// Picks the format from the file extension, defaulting to CSV.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Tracks battery levels across a discharge cycle to compare how fast each bud drains.
+///
+/// Both buds are sampled at the same moments, so comparing how much each one dropped
+/// since the start of the cycle is the same as comparing their discharge rates.
+#[derive(Debug, Default)]
+pub struct BatteryStats {
+    /// Left and right levels at the start of the current discharge cycle.
+    baseline: Option<(i8, i8)>,
+    latest: Option<(i8, i8)>,
+    /// Every change in battery levels seen, across sessions when persisted.
+    history: VecDeque<BatterySample>,
+    /// File the history is saved to, `None` to keep it in memory only.
+    path: Option<PathBuf>,
+}
+
+/// File the battery history of the device at `address` is saved to.
+fn history_path(address: &str) -> PathBuf {
+    state_dir()
+        .join("battery-history")
+        .join(format!("{}.json", address.replace(':', "")))
+}
+
+/// Deletes the saved battery history of the device at `address`, e.g. when it's forgotten.
+pub fn forget(address: &str) {
+    match std::fs::remove_file(history_path(address)) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            error!("Failed to remove the battery history: {}", e)
+        }
+        _ => {}
+    }
+}
+
+impl BatteryStats {
+    /// Loads the saved history of the device at `address`, and keeps saving it as it changes.
+    pub fn load(address: &str) -> Self {
+        let path = history_path(address);
+        let mut stats = Self::default();
+        match std::fs::read_to_string(&path) {
+            Ok(json) => match serde_json::from_str::<Value>(&json) {
+                Ok(Value::Array(samples)) => {
+                    samples
+                        .iter()
+                        .filter_map(BatterySample::from_json)
+                        .for_each(|sample| stats.push(sample));
+                }
+                _ => error!("Failed to parse the battery history"),
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => error!("Failed to read the battery history: {}", e),
+        }
+        stats.path = Some(path);
+        stats
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let json = Value::Array(self.history.iter().map(BatterySample::to_json).collect());
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, json.to_string()));
+        if let Err(e) = result {
+            error!("Failed to save the battery history: {}", e);
+        }
+    }
+
+    pub fn record(&mut self, status: &BudsStatus) {
+        let (Some(left), Some(right), Some(case)) = (
+            status.battery_left(),
+            status.battery_right(),
+            status.battery_case(),
+        ) else {
+            return;
+        };
+        let is_changed = self.history.back().is_none_or(|last| {
+            (last.left, last.right, last.case) != (left, right, case)
+        });
+        if is_changed {
+            self.push(BatterySample {
+                time: SystemTime::now(),
+                left,
+                right,
+                case,
+            });
+            self.save();
+        }
+    }
+
+    fn push(&mut self, sample: BatterySample) {
+        let levels = (sample.left, sample.right);
+
+        // Any increase means the buds were charged, which starts a new cycle.
+        match self.latest {
+            Some((left, right)) if levels.0 <= left && levels.1 <= right => {}
+            _ => self.baseline = Some(levels),
+        }
+        self.latest = Some(levels);
+
+        if self.history.len() >= MAX_SAMPLES {
+            self.history.pop_front();
+        }
+        self.history.push_back(sample);
+    }
+
+    /// Serializes the battery history, including the identity of the device it came from.
+    pub fn export(&self, device: &DeviceInfo, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Csv => {
+                let mut csv = String::from("device,address,timestamp,left,right,case\n");
+                let name = csv_field(&device.name);
+                let address = csv_field(&device.address);
+                for sample in &self.history {
+                    csv.push_str(&format!(
+                        "{},{},{},{},{},{}\n",
+                        name,
+                        address,
+                        sample.timestamp(),
+                        sample.left,
+                        sample.right,
+                        sample.case
+                    ));
+                }
+                csv
+            }
+            ExportFormat::Json => {
+                let samples: Vec<Value> = self
+                    .history
+                    .iter()
+                    .map(|sample| {
+                        json!({
+                            "timestamp": sample.timestamp(),
+                            "left": sample.left,
+                            "right": sample.right,
+                            "case": sample.case,
+                        })
+                    })
+                    .collect();
+                let json = json!({
+                    "device": { "name": device.name, "address": device.address },
+                    "samples": samples,
+                });
+                // Pretty printing a `Value` can't fail.
+                serde_json::to_string_pretty(&json).unwrap_or_default() + "\n"
+            }
+        }
+    }
+
+    /// Describes the recorded history, e.g. "120 samples since 3 Oct 2026, 14:05".
+    pub fn history_summary(&self) -> Option<String> {
+        let first = self.history.front()?;
+        let since = gtk4::glib::DateTime::from_unix_local(first.unix_time() as i64)
+            .and_then(|time| time.format("%-d %b %Y, %H:%M"))
+            .ok()?;
+        Some(format!("{} samples since {}", self.history.len(), since))
+    }
+
+    /// Describes a noticeable imbalance between the buds' discharge rates, if any.
+    pub fn drain_balance_hint(&self) -> Option<String> {
+        let (start_left, start_right) = self.baseline?;
+        let (left, right) = self.latest?;
+        let drain_left = i32::from(start_left - left);
+        let drain_right = i32::from(start_right - right);
+
+        let (side, fast, slow) = if drain_left > drain_right {
+            ("Left", drain_left, drain_right)
+        } else {
+            ("Right", drain_right, drain_left)
+        };
+
+        if fast < i32::from(MIN_DRAIN_FOR_HINT) || slow == 0 {
+            return None;
+        }
+
+        let percent_faster = (fast - slow) * 100 / slow;
+        if percent_faster < IMBALANCE_THRESHOLD {
+            return None;
+        }
+
+        Some(format!("{} bud drains {}% faster", side, percent_faster))
+    }
+
+    /// Estimates how long the emptier bud lasts, from how fast it drained over the last hour.
+    ///
+    /// `None` until there are enough samples since the buds were last charged, or while the
+    /// level doesn't go down.
+    pub fn time_remaining(&self) -> Option<Duration> {
+        let now = SystemTime::now();
+        // Only the current discharge cycle, i.e. the samples since the level last went up.
+        let mut samples = Vec::new();
+        for sample in self.history.iter().rev() {
+            let level = sample.left.min(sample.right);
+            if samples.last().is_some_and(|(_, later)| level < *later)
+                || now.duration_since(sample.time).unwrap_or_default() > ESTIMATE_WINDOW
+            {
+                break;
+            }
+            samples.push((sample.time, level));
+        }
+        if samples.len() < MIN_ESTIMATE_SAMPLES {
+            return None;
+        }
+
+        let points: Vec<(f64, f64)> = samples
+            .iter()
+            .map(|(time, level)| {
+                let age = now.duration_since(*time).unwrap_or_default().as_secs_f64();
+                (-age, f64::from(*level))
+            })
+            .collect();
+        let rate = slope(&points)?;
+        if rate >= 0.0 {
+            return None;
+        }
+        let (_, level) = samples[0];
+        Some(Duration::from_secs_f64(f64::from(level) / -rate))
+    }
+
+    /// The time left, e.g. "≈3h 40m left".
+    pub fn time_remaining_text(&self) -> Option<String> {
+        let minutes = self.time_remaining()?.as_secs() / 60;
+        Some(match (minutes / 60, minutes % 60) {
+            (0, minutes) => format!("≈{}m left", minutes),
+            (hours, minutes) => format!("≈{}h {}m left", hours, minutes),
+        })
+    }
+}
+
+/// Slope of the least squares line through `points`, or `None` if they're all at one `x`.
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        let dx = x - mean_x;
+        (cov + dx * (y - mean_y), var + dx * dx)
+    });
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+/// Quotes a CSV field if it has separators, quotes or line breaks in it (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use galaxy_buds_rs::message::bud_property::Placement;
+
+    use super::*;
+
+    /// History made of `(minutes ago, level of both buds)`, oldest first.
+    fn history(samples: &[(u64, i8)]) -> BatteryStats {
+        let now = SystemTime::now();
+        let mut stats = BatteryStats::default();
+        for &(minutes_ago, level) in samples {
+            stats.push(BatterySample {
+                time: now - Duration::from_secs(minutes_ago * 60),
+                left: level,
+                right: level,
+                case: 50,
+            });
+        }
+        stats
+    }
+
+    /// History of the left and right levels, in the order they were received.
+    fn levels(samples: &[(i8, i8)]) -> BatteryStats {
+        let mut stats = BatteryStats::default();
+        for &(left, right) in samples {
+            stats.push(BatterySample {
+                time: SystemTime::now(),
+                left,
+                right,
+                case: 50,
+            });
+        }
+        stats
+    }
+
+    fn minutes_remaining(stats: &BatteryStats) -> Option<u64> {
+        stats.time_remaining().map(|remaining| remaining.as_secs() / 60)
+    }
+
+    #[test]
+    fn estimates_nothing_while_the_level_stays_flat() {
+        let stats = history(&[(40, 80), (20, 80), (0, 80)]);
+        assert_eq!(stats.time_remaining(), None);
+        assert_eq!(stats.time_remaining_text(), None);
+    }
+
+    #[test]
+    fn estimates_a_linear_drain() {
+        // A quarter of a percent per minute.
+        let stats = history(&[(40, 80), (20, 75), (0, 70)]);
+        let minutes = minutes_remaining(&stats).unwrap();
+        assert!((279..=280).contains(&minutes), "{} minutes", minutes);
+        assert!(stats.time_remaining_text().unwrap().starts_with("≈4h"));
+    }
+
+    #[test]
+    fn estimates_a_noisy_drain_from_its_trend() {
+        let stats = history(&[(50, 80), (42, 79), (30, 76), (21, 75), (10, 72), (0, 70)]);
+        let minutes = minutes_remaining(&stats).unwrap();
+        assert!((330..=360).contains(&minutes), "{} minutes", minutes);
+    }
+
+    #[test]
+    fn estimates_from_the_samples_since_the_last_charge() {
+        let stats = history(&[(40, 50), (30, 45), (20, 60), (10, 58), (0, 56)]);
+        let minutes = minutes_remaining(&stats).unwrap();
+        assert!((279..=280).contains(&minutes), "{} minutes", minutes);
+    }
+
+    #[test]
+    fn needs_enough_samples_since_the_last_charge() {
+        let stats = history(&[(40, 50), (30, 45), (20, 40), (10, 60), (0, 58)]);
+        assert_eq!(stats.time_remaining(), None);
+    }
+
+    #[test]
+    fn ignores_samples_older_than_the_estimate_window() {
+        let stats = history(&[(180, 90), (120, 85), (10, 60), (0, 58)]);
+        assert_eq!(stats.time_remaining(), None);
+    }
+
+    #[test]
+    fn formats_the_time_remaining() {
+        // One percent per minute.
+        let stats = history(&[(20, 70), (10, 60), (0, 50)]);
+        let text = stats.time_remaining_text().unwrap();
+        assert!(text == "≈50m left" || text == "≈49m left", "{}", text);
+    }
+
+    #[test]
+    fn hints_at_a_bud_draining_faster() {
+        let stats = levels(&[(100, 100), (80, 90)]);
+        assert_eq!(
+            stats.drain_balance_hint().as_deref(),
+            Some("Left bud drains 100% faster")
+        );
+    }
+
+    #[test]
+    fn gives_no_hint_for_small_or_balanced_drains() {
+        assert_eq!(levels(&[(100, 100), (95, 97)]).drain_balance_hint(), None);
+        assert_eq!(levels(&[(100, 100), (80, 82)]).drain_balance_hint(), None);
+        assert_eq!(levels(&[(100, 100)]).drain_balance_hint(), None);
+    }
+
+    #[test]
+    fn starts_a_new_cycle_after_charging() {
+        let stats = levels(&[(100, 100), (70, 85), (90, 95), (85, 90)]);
+        assert_eq!(stats.drain_balance_hint(), None);
+    }
+
+    #[test]
+    fn records_only_changed_levels() {
+        let placement = (&Placement::Wearing, &Placement::Wearing);
+        let mut stats = BatteryStats::default();
+        stats.record(&BudsStatus::default());
+        stats.record(&BudsStatus::from_readings((80, 80, 50), placement, true));
+        stats.record(&BudsStatus::from_readings((80, 80, 50), placement, true));
+        stats.record(&BudsStatus::from_readings((79, 80, 50), placement, true));
+        assert_eq!(stats.history.len(), 2);
+    }
+
+    #[test]
+    fn reads_back_saved_samples() {
+        let sample = BatterySample {
+            time: UNIX_EPOCH + Duration::from_secs(1_760_000_000),
+            left: 80,
+            right: 75,
+            case: 0,
+        };
+        let read = BatterySample::from_json(&sample.to_json()).unwrap();
+        assert_eq!(read.time, sample.time);
+        assert_eq!((read.left, read.right, read.case), (80, 75, 0));
+
+        assert!(BatterySample::from_json(&json!({ "time": 1, "left": 300 })).is_none());
+    }
+
+    #[test]
+    fn quotes_csv_fields_only_when_needed() {
+        assert_eq!(csv_field("Galaxy Buds Live"), "Galaxy Buds Live");
+        assert_eq!(csv_field("Buds, mine"), "\"Buds, mine\"");
+        assert_eq!(csv_field("The \"good\" buds"), "\"The \"\"good\"\" buds\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+}
@@ -1,3 +1,4 @@
+use bytes::Bytes;
 use galaxy_buds_rs::{
     message::{
         Message, Payload, ambient_mode, bud_property::NoiseControlMode,
@@ -8,39 +9,64 @@ use galaxy_buds_rs::{
     model::Model,
 };
 
+use crate::crash;
+
+/// Length of a frame with an empty payload: start, length, id, CRC and end of message.
+const MIN_FRAME_LENGTH: usize = 7;
+
 #[derive(Debug)]
 pub enum BudsMessage {
     StatusUpdate(StatusUpdate),
     ExtendedStatusUpdate(ExtendedStatusUpdate),
     NoiseControlsUpdate(NoiseControlsUpdated),
+    SessionControl(SessionControl),
+    /// The payload of the debug data, kept raw since its layout isn't documented anywhere.
+    DebugData(Bytes),
+
+    Unknown { id: u8, buffer: Bytes },
+}
 
-    Unknown { id: u8, buffer: Vec<u8> },
+/// Messages that keep the session going, without carrying any device state.
+///
+/// None of them expect a reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionControl {
+    /// Sent periodically by the device while the connection is idle.
+    KeepAlive,
+    /// Sent by the device after handling a request, with the id of that request.
+    Acknowledgement { request_id: u8 },
 }
 
 impl BudsMessage {
-    /// Parses a raw byte buffer into a BudsMessage.
+    /// Parses a raw frame from buds of the given model into a BudsMessage.
     ///
-    /// Returns `None` for messages that should be ignored, like keep-alives.
-    pub fn from_bytes(buff: &[u8]) -> Option<Self> {
+    /// Returns `None` for frames too short to hold a message id.
+    /// Unknown messages keep a reference to the frame instead of copying it.
+    pub fn from_bytes(buff: &Bytes, model: Model) -> Option<Self> {
         // Basic validation
         if buff.len() < 4 {
             return None;
         }
         let id = buff[3];
 
-        if id == 242 {
-            return None;
-        }
-
-        // TODO: Support other models
-        let message = Message::new(buff, Model::BudsLive);
+        let message = Message::new(buff, model);
         let parsed_message = match id {
             ids::STATUS_UPDATED => Self::StatusUpdate(message.into()),
             ids::EXTENDED_STATUS_UPDATED => Self::ExtendedStatusUpdate(message.into()),
             ids::NOISE_CONTROLS_UPDATE => Self::NoiseControlsUpdate(message.into()),
+            extra_ids::KEEP_ALIVE => Self::SessionControl(SessionControl::KeepAlive),
+            // The payload sits between the header and the CRC and end of message.
+            extra_ids::DEBUG_GET_ALL_DATA if buff.len() >= MIN_FRAME_LENGTH => {
+                Self::DebugData(buff.slice(4..buff.len() - 3))
+            }
+            extra_ids::ACKNOWLEDGEMENT if buff.len() > 4 => {
+                Self::SessionControl(SessionControl::Acknowledgement {
+                    request_id: buff[4],
+                })
+            }
             _ => Self::Unknown {
                 id,
-                buffer: buff.to_vec(),
+                buffer: buff.clone(),
             },
         };
 
@@ -48,11 +74,105 @@ impl BudsMessage {
     }
 }
 
+/// Decodes a message as a specific type and pretty-prints its fields.
+pub type MessageDecoder = fn(Message) -> String;
+
+/// Every message type that can be decoded, with its id and a display name.
+///
+/// Lets the debug window try to decode a frame as any known type, regardless of its actual id.
+pub const DECODE_TABLE: &[(u8, &str, MessageDecoder)] = &[
+    (ids::STATUS_UPDATED, "Status update", |message| {
+        format!("{:#?}", StatusUpdate::from(message))
+    }),
+    (
+        ids::EXTENDED_STATUS_UPDATED,
+        "Extended status update",
+        |message| format!("{:#?}", ExtendedStatusUpdate::from(message)),
+    ),
+    (
+        ids::NOISE_CONTROLS_UPDATE,
+        "Noise controls update",
+        |message| format!("{:#?}", NoiseControlsUpdated::from(message)),
+    ),
+];
+
+impl BudsMessage {
+    /// Decodes a raw frame with the given decoder, ignoring the id in the frame.
+    ///
+    /// Returns an error if the frame doesn't fit the layout of the message type.
+    pub fn decode_as(buff: &[u8], decoder: MessageDecoder) -> Result<String, String> {
+        if buff.len() < MIN_FRAME_LENGTH {
+            return Err("Frame is too short".to_string());
+        }
+
+        // The parsers index into the payload directly and panic on payloads shorter than
+        // their message type, whose lengths `galaxy_buds_rs` doesn't expose.
+        let buff = buff.to_vec();
+        crash::catch_expected_panic(move || decoder(Message::new(&buff, Model::BudsLive)))
+            .map_err(|_| "Frame doesn't match the layout of this message type".to_string())
+    }
+}
+
+/// Message ids that `galaxy_buds_rs` doesn't define.
+///
+/// Unless noted otherwise, they come from the `MsgIds` enum of GalaxyBudsClient
+/// (https://github.com/timschneeb/GalaxyBudsClient), which names them the same way.
+mod extra_ids {
+    pub const DEBUG_GET_ALL_DATA: u8 = 38;
+    /// `UNIVERSAL_MSG_ID_ACKNOWLEDGEMENT` there.
+    pub const ACKNOWLEDGEMENT: u8 = 66;
+    pub const SET_NOISE_CONTROLS_WITH_ONE_EARBUD: u8 = 111;
+    pub const SET_SIDETONE: u8 = 139;
+    /// Not from GalaxyBudsClient: the buds send it unprompted while idle, and earlier versions
+    /// of this app dropped it by this number. Only ever received, never sent.
+    pub const KEEP_ALIVE: u8 = 242;
+}
+
+const SOM: u8 = 0xFD;
+const EOM: u8 = 0xDD;
+
+/// Builds a request frame for messages that `galaxy_buds_rs` has no type for.
+///
+/// Uses the framing of the Buds+ and later models.
+fn encode_frame(id: u8, payload: &[u8]) -> Vec<u8> {
+    // The length covers the id, the payload and the CRC.
+    let length = (payload.len() + 3) as u16;
+    let mut body = Vec::with_capacity(payload.len() + 1);
+    body.push(id);
+    body.extend_from_slice(payload);
+    let crc = crc16(&body);
+
+    let mut frame = vec![SOM, (length & 0xFF) as u8, ((length >> 8) & 0x03) as u8];
+    frame.extend_from_slice(&body);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame.push(EOM);
+    frame
+}
+
+/// CRC-16/XMODEM, as used by the Buds protocol.
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
 #[derive(Debug)]
 pub enum BudsCommand {
     ManagerInfo,
     Find(bool),
     SetNoiseControlMode(NoiseControlMode),
+    /// Allows noise reduction and ambient sound while wearing a single earbud.
+    SetNoiseControlsWithOneEarbud(bool),
+    /// Plays ambient sound while the earbud microphone is used in a call (sidetone).
+    SetAmbientSoundDuringCalls(bool),
+    /// Asks for the debug data, answered with `BudsMessage::DebugData`.
+    GetDebugData,
 }
 
 impl BudsCommand {
@@ -68,6 +188,38 @@ impl BudsCommand {
                 }
                 NoiseControlMode::NoiseReduction => set_noise_reduction::new(true).to_byte_array(),
             },
+            BudsCommand::SetNoiseControlsWithOneEarbud(enabled) => encode_frame(
+                extra_ids::SET_NOISE_CONTROLS_WITH_ONE_EARBUD,
+                &[*enabled as u8],
+            ),
+            BudsCommand::SetAmbientSoundDuringCalls(enabled) => {
+                encode_frame(extra_ids::SET_SIDETONE, &[*enabled as u8])
+            }
+            BudsCommand::GetDebugData => encode_frame(extra_ids::DEBUG_GET_ALL_DATA, &[]),
+        }
+    }
+
+    /// Parses a command typed in the developer console, e.g. `find on` or `noise anc`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut words = input.split_whitespace();
+        let name = words.next().ok_or("Empty command")?;
+        let argument = words.next();
+
+        match (name, argument) {
+            ("manager-info", None) => Ok(BudsCommand::ManagerInfo),
+            ("debug-data", None) => Ok(BudsCommand::GetDebugData),
+            ("find", Some("on")) => Ok(BudsCommand::Find(true)),
+            ("find", Some("off")) => Ok(BudsCommand::Find(false)),
+            ("noise", Some("off")) => Ok(BudsCommand::SetNoiseControlMode(NoiseControlMode::Off)),
+            ("noise", Some("ambient")) => Ok(BudsCommand::SetNoiseControlMode(
+                NoiseControlMode::AmbientSound,
+            )),
+            ("noise", Some("anc")) => Ok(BudsCommand::SetNoiseControlMode(
+                NoiseControlMode::NoiseReduction,
+            )),
+            ("find", _) => Err("Usage: find on|off".to_string()),
+            ("noise", _) => Err("Usage: noise off|ambient|anc".to_string()),
+            _ => Err(format!("Unknown command: {}", name)),
         }
     }
 }
@@ -1,78 +1,316 @@
+use std::time::{Duration, Instant};
+
 use galaxy_buds_rs::message::{
-    bud_property::NoiseControlMode, extended_status_updated::ExtendedStatusUpdate, noise_controls_updated::NoiseControlsUpdated, status_updated::StatusUpdate
+    bud_property::{NoiseControlMode, Placement}, extended_status_updated::ExtendedStatusUpdate, noise_controls_updated::NoiseControlsUpdated, status_updated::StatusUpdate
 };
 
 pub trait UpdateFrom<T> {
     fn update(&mut self, source: T);
 }
 
-#[derive(Debug)]
+/// How long a reading is trusted without the device sending it again.
+///
+/// The buds send a status update whenever a battery level changes, so this only passes when the
+/// connection stopped delivering updates without being dropped.
+const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+const FULL_BATTERY: i8 = 100;
+
+/// A value reported by the device, with when it was last received.
+#[derive(Debug, Clone, Copy)]
+struct Reading<T> {
+    value: T,
+    updated: Instant,
+}
+
+impl<T: Copy> Reading<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            updated: Instant::now(),
+        }
+    }
+}
+
+/// Sets a reading to `value`, now.
+fn set<T: Copy>(reading: &mut Option<Reading<T>>, value: T) {
+    *reading = Some(Reading::new(value));
+}
+
+fn value<T: Copy>(reading: &Option<Reading<T>>) -> Option<T> {
+    reading.map(|reading| reading.value)
+}
+
+/// What is known about the device, merged from the status messages it sent.
+///
+/// Every reading is `None` until a message carrying it was received, so an unknown battery level
+/// isn't mistaken for an empty one.
+#[derive(Debug, Clone, Default)]
 pub struct BudsStatus {
-    battery_left: i8,
-    battery_right: i8,
-    battery_case: i8,
-    noise_control_mode: NoiseControlMode,
+    battery_left: Option<Reading<i8>>,
+    battery_right: Option<Reading<i8>>,
+    battery_case: Option<Reading<i8>>,
+    /// Whether the case is charging, inferred from its battery level going up or down, as the
+    /// status messages only tell where the buds are.
+    charging_case: bool,
+    /// Whether the case is open, or `None` when no bud is in the case to tell.
+    case_open: Option<bool>,
+    /// Whether the left and right buds are in the case, open or closed.
+    in_case: Option<Reading<(bool, bool)>>,
+    /// Whether at least one bud is in an ear.
+    worn: Option<Reading<bool>>,
+    /// Whether both buds are connected to each other, i.e. online.
+    coupled: Option<Reading<bool>>,
+    noise_control_mode: Option<Reading<NoiseControlMode>>,
+    /// Not part of the status updates parsed by `galaxy_buds_rs`, so this is the last value set.
+    noise_controls_with_one_earbud: Option<bool>,
+    /// Same as `noise_controls_with_one_earbud`, only the last value set.
+    ambient_sound_during_calls: Option<bool>,
 }
 
 impl BudsStatus {
+    pub fn battery_left(&self) -> Option<i8> {
+        value(&self.battery_left)
+    }
+
+    pub fn battery_right(&self) -> Option<i8> {
+        value(&self.battery_right)
+    }
+
+    pub fn battery_case(&self) -> Option<i8> {
+        value(&self.battery_case)
+    }
+
+    /// The level of the emptier bud, `None` until received.
+    pub fn lowest_battery(&self) -> Option<i8> {
+        self.battery_left().into_iter().chain(self.battery_right()).min()
+    }
+
+    /// When the battery levels were last received, which is always together.
+    pub fn battery_updated(&self) -> Option<Instant> {
+        self.battery_left.map(|reading| reading.updated)
+    }
+
+    /// Whether the battery levels haven't been received for long enough to be doubted.
+    pub fn is_battery_stale(&self) -> bool {
+        self.battery_updated()
+            .is_some_and(|updated| updated.elapsed() > STALE_AFTER)
+    }
+
+    /// Whether a bud is charging: it's in the case, isn't full, and the case has charge left.
+    pub fn is_buds_charging(&self) -> bool {
+        let Some((left_in_case, right_in_case)) = value(&self.in_case) else {
+            return false;
+        };
+        let is_charging = |in_case: bool, level: Option<i8>| {
+            in_case && level.is_some_and(|level| level < FULL_BATTERY)
+        };
+        self.battery_case() != Some(0)
+            && (is_charging(left_in_case, self.battery_left())
+                || is_charging(right_in_case, self.battery_right()))
+    }
+
+    pub fn is_case_charging(&self) -> bool {
+        self.charging_case && self.battery_case() != Some(FULL_BATTERY)
+    }
+
+    pub fn is_case_open(&self) -> Option<bool> {
+        self.case_open
+    }
+
+    /// Whether both buds are in the case, open or closed. `false` while unknown.
+    pub fn are_both_in_case(&self) -> bool {
+        value(&self.in_case).is_some_and(|(left, right)| left && right)
+    }
+
+    /// Whether a bud is in an ear. `false` while unknown.
+    pub fn is_worn(&self) -> bool {
+        value(&self.worn).unwrap_or(false)
+    }
+
+    /// Tells which bud is offline when only one is connected, since its battery level alone
+    /// just reads 0%.
+    pub fn missing_bud_text(&self) -> Option<String> {
+        if value(&self.coupled) != Some(false) {
+            return None;
+        }
+        // The offline bud reports an empty battery, unless both happen to be empty.
+        let bud = match (self.battery_left(), self.battery_right()) {
+            (Some(0), Some(right)) if right > 0 => "Left bud",
+            (Some(left), Some(0)) if left > 0 => "Right bud",
+            _ => "One bud",
+        };
+        Some(format!("{} not connected — check the case", bud))
+    }
+
+    /// Short description of every battery level, e.g. for notifications.
+    pub fn battery_summary(&self) -> String {
+        format!(
+            "Buds: {}, Case: {}",
+            self.battery_text(),
+            self.case_battery_text()
+        )
+    }
+
     pub fn battery_text(&self) -> String {
-        if self.battery_left == self.battery_right {
-            format!("L / R {}%", self.battery_left)
-        } else {
-            format!("L {}% / R {}%", self.battery_left, self.battery_right)
+        match (self.battery_left(), self.battery_right()) {
+            (Some(left), Some(right)) if left == right => format!("L / R {}%", left),
+            (left, right) => format!("L {} / R {}", percent_text(left), percent_text(right)),
         }
     }
 
     pub fn case_battery_text(&self) -> String {
-        format!("{}%", self.battery_case)
+        percent_text(self.battery_case())
+    }
+
+    pub fn noise_control_mode(&self) -> Option<NoiseControlMode> {
+        value(&self.noise_control_mode)
     }
 
-    pub fn noise_control_mode(&self) -> NoiseControlMode {
-        self.noise_control_mode
+    pub fn noise_controls_with_one_earbud(&self) -> Option<bool> {
+        self.noise_controls_with_one_earbud
+    }
+
+    pub fn set_noise_controls_with_one_earbud(&mut self, enabled: bool) {
+        self.noise_controls_with_one_earbud = Some(enabled);
+    }
+
+    pub fn ambient_sound_during_calls(&self) -> Option<bool> {
+        self.ambient_sound_during_calls
+    }
+
+    pub fn set_ambient_sound_during_calls(&mut self, enabled: bool) {
+        self.ambient_sound_during_calls = Some(enabled);
     }
 
     pub fn noise_control_mode_text(&self) -> String {
         match self.noise_control_mode() {
-            NoiseControlMode::NoiseReduction => "Noise Reduction".to_string(),
-            NoiseControlMode::AmbientSound => "Ambient Sound".to_string(),
-            NoiseControlMode::Off => "Off".to_string(),
+            Some(NoiseControlMode::NoiseReduction) => "Noise Reduction".to_string(),
+            Some(NoiseControlMode::AmbientSound) => "Ambient Sound".to_string(),
+            Some(NoiseControlMode::Off) => "Off".to_string(),
+            None => "N/A".to_string(),
         }
     }
 }
 impl UpdateFrom<&StatusUpdate> for BudsStatus {
     fn update(&mut self, status: &StatusUpdate) {
-        self.battery_left = status.battery_left;
-        self.battery_right = status.battery_right;
-        self.battery_case = status.battery_case;
+        self.update_battery(status.battery_left, status.battery_right, status.battery_case);
+        self.update_placement(&status.placement_left, &status.placement_right);
+        set(&mut self.coupled, status.coupled);
     }
 }
 
 impl UpdateFrom<&ExtendedStatusUpdate> for BudsStatus {
     fn update(&mut self, status: &ExtendedStatusUpdate) {
-        self.battery_left = status.battery_left;
-        self.battery_right = status.battery_right;
-        self.battery_case = status.battery_case;
-        self.noise_control_mode = noise_control_from_status_update(status);
+        self.update_battery(status.battery_left, status.battery_right, status.battery_case);
+        self.update_placement(&status.placement_left, &status.placement_right);
+        set(&mut self.coupled, status.coupled);
+        set(&mut self.noise_control_mode, noise_control_from_status_update(status));
     }
 }
 
 impl UpdateFrom<&NoiseControlsUpdated> for BudsStatus {
     fn update(&mut self, update: &NoiseControlsUpdated) {
-        self.noise_control_mode = update.noise_control_mode;
+        set(&mut self.noise_control_mode, update.noise_control_mode);
+    }
+}
+
+impl UpdateFrom<NoiseControlMode> for BudsStatus {
+    fn update(&mut self, mode: NoiseControlMode) {
+        set(&mut self.noise_control_mode, mode);
     }
 }
 
 impl From<&ExtendedStatusUpdate> for BudsStatus {
     fn from(status: &ExtendedStatusUpdate) -> Self {
-        Self {
-            battery_left: status.battery_left,
-            battery_right: status.battery_right,
-            battery_case: status.battery_case,
-            noise_control_mode: noise_control_from_status_update(status),
+        let mut buds_status = Self::default();
+        buds_status.update(status);
+        buds_status
+    }
+}
+
+impl BudsStatus {
+    /// Updates the battery levels, marking the case as charging while its level goes up.
+    ///
+    /// The charging state is kept when the level doesn't change, or wasn't known before.
+    fn update_battery(&mut self, left: i8, right: i8, case: i8) {
+        let old_case = self.battery_case();
+        update_charging(&mut self.charging_case, old_case, case);
+        set(&mut self.battery_left, left);
+        set(&mut self.battery_right, right);
+        set(&mut self.battery_case, case);
+    }
+
+    fn update_placement(&mut self, left: &Placement, right: &Placement) {
+        self.case_open = case_open_from_placement(left, right);
+        set(&mut self.in_case, (is_in_case(left), is_in_case(right)));
+        set(&mut self.worn, is_worn(left) || is_worn(right));
+    }
+}
+
+#[cfg(test)]
+impl BudsStatus {
+    /// Builds a status as if the device had just sent these readings, for the tests of the views
+    /// built from it.
+    pub fn from_readings(
+        (left, right, case): (i8, i8, i8),
+        (left_placement, right_placement): (&Placement, &Placement),
+        coupled: bool,
+    ) -> Self {
+        let mut status = Self::default();
+        status.update_battery(left, right, case);
+        status.update_placement(left_placement, right_placement);
+        set(&mut status.coupled, coupled);
+        status
+    }
+
+    /// Makes the battery levels look like they were received `age` ago.
+    fn age_battery(&mut self, age: Duration) {
+        for reading in [
+            &mut self.battery_left,
+            &mut self.battery_right,
+            &mut self.battery_case,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            reading.updated = reading.updated.checked_sub(age).expect("uptime is too short");
         }
     }
 }
 
+fn update_charging(charging: &mut bool, old_level: Option<i8>, new_level: i8) {
+    let Some(old_level) = old_level else {
+        return;
+    };
+    if new_level > old_level {
+        *charging = true;
+    } else if new_level < old_level {
+        *charging = false;
+    }
+}
+
+fn percent_text(level: Option<i8>) -> String {
+    level.map_or_else(|| "N/A".to_string(), |level| format!("{}%", level))
+}
+
+/// The case state can only be told from the placement of the buds that are inside it.
+fn case_open_from_placement(left: &Placement, right: &Placement) -> Option<bool> {
+    match (left, right) {
+        (Placement::InOpenCase, _) | (_, Placement::InOpenCase) => Some(true),
+        (Placement::InCloseCase, _) | (_, Placement::InCloseCase) => Some(false),
+        _ => None,
+    }
+}
+
+fn is_in_case(placement: &Placement) -> bool {
+    matches!(placement, Placement::InOpenCase | Placement::InCloseCase)
+}
+
+fn is_worn(placement: &Placement) -> bool {
+    matches!(placement, Placement::Wearing)
+}
+
 fn noise_control_from_status_update(status: &ExtendedStatusUpdate) -> NoiseControlMode {
     if status.noise_reduction {
         NoiseControlMode::NoiseReduction
@@ -82,3 +320,134 @@ fn noise_control_from_status_update(status: &ExtendedStatusUpdate) -> NoiseContr
         NoiseControlMode::Off
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORN: (&Placement, &Placement) = (&Placement::Wearing, &Placement::Wearing);
+    const IN_OPEN_CASE: (&Placement, &Placement) =
+        (&Placement::InOpenCase, &Placement::InOpenCase);
+
+    #[test]
+    fn tells_unknown_levels_from_empty_ones() {
+        let status = BudsStatus::default();
+        assert_eq!(status.battery_left(), None);
+        assert_eq!(status.lowest_battery(), None);
+        assert_eq!(status.battery_summary(), "Buds: L N/A / R N/A, Case: N/A");
+
+        let status = BudsStatus::from_readings((0, 40, 0), WORN, true);
+        assert_eq!(status.battery_left(), Some(0));
+        assert_eq!(status.lowest_battery(), Some(0));
+        assert_eq!(status.battery_summary(), "Buds: L 0% / R 40%, Case: 0%");
+    }
+
+    #[test]
+    fn is_not_stale_before_receiving_anything() {
+        let status = BudsStatus::default();
+        assert_eq!(status.battery_updated(), None);
+        assert!(!status.is_battery_stale());
+    }
+
+    #[test]
+    fn becomes_stale_without_updates() {
+        let mut status = BudsStatus::from_readings((80, 80, 50), WORN, true);
+        assert!(!status.is_battery_stale());
+
+        status.age_battery(STALE_AFTER - Duration::from_secs(60));
+        assert!(!status.is_battery_stale());
+
+        status.age_battery(Duration::from_secs(120));
+        assert!(status.is_battery_stale());
+        assert!(status.battery_updated().unwrap().elapsed() > STALE_AFTER);
+    }
+
+    #[test]
+    fn is_fresh_again_after_an_update() {
+        let mut status = BudsStatus::from_readings((80, 80, 50), WORN, true);
+        status.age_battery(STALE_AFTER * 2);
+
+        status.update_battery(79, 80, 50);
+        assert!(!status.is_battery_stale());
+    }
+
+    #[test]
+    fn keeps_the_battery_levels_on_noise_control_updates() {
+        let mut status = BudsStatus::from_readings((80, 75, 50), WORN, true);
+        status.update(NoiseControlMode::AmbientSound);
+
+        assert_eq!(status.noise_control_mode(), Some(NoiseControlMode::AmbientSound));
+        assert_eq!(status.battery_text(), "L 80% / R 75%");
+        assert!(status.is_worn());
+    }
+
+    #[test]
+    fn keeps_the_noise_control_mode_on_status_updates() {
+        let mut status = BudsStatus::default();
+        status.update(NoiseControlMode::NoiseReduction);
+        status.update_battery(80, 75, 50);
+        status.update_placement(IN_OPEN_CASE.0, IN_OPEN_CASE.1);
+
+        assert_eq!(status.noise_control_mode(), Some(NoiseControlMode::NoiseReduction));
+        assert_eq!(status.battery_left(), Some(80));
+    }
+
+    #[test]
+    fn keeps_settings_without_readings_on_updates() {
+        let mut status = BudsStatus::default();
+        status.set_noise_controls_with_one_earbud(true);
+        status.update_battery(80, 75, 50);
+
+        assert_eq!(status.noise_controls_with_one_earbud(), Some(true));
+        assert_eq!(status.ambient_sound_during_calls(), None);
+    }
+
+    #[test]
+    fn infers_case_charging_from_its_level() {
+        let mut status = BudsStatus::from_readings((100, 100, 40), IN_OPEN_CASE, true);
+        assert!(!status.is_case_charging());
+
+        status.update_battery(100, 100, 45);
+        assert!(status.is_case_charging());
+        // Unchanged levels keep the state.
+        status.update_battery(100, 100, 45);
+        assert!(status.is_case_charging());
+
+        status.update_battery(100, 100, 44);
+        assert!(!status.is_case_charging());
+    }
+
+    #[test]
+    fn replaces_the_placement_on_updates() {
+        let mut status = BudsStatus::from_readings((90, 90, 40), IN_OPEN_CASE, true);
+        assert!(status.are_both_in_case());
+        assert_eq!(status.is_case_open(), Some(true));
+        assert!(status.is_buds_charging());
+
+        status.update_placement(&Placement::Wearing, &Placement::InCloseCase);
+        assert!(!status.are_both_in_case());
+        assert_eq!(status.is_case_open(), Some(false));
+        assert!(status.is_worn());
+
+        status.update_placement(WORN.0, WORN.1);
+        assert_eq!(status.is_case_open(), None);
+        assert!(!status.is_buds_charging());
+    }
+
+    #[test]
+    fn names_the_missing_bud() {
+        let status = BudsStatus::from_readings((0, 60, 50), WORN, false);
+        assert_eq!(
+            status.missing_bud_text().as_deref(),
+            Some("Left bud not connected — check the case")
+        );
+
+        let status = BudsStatus::from_readings((0, 0, 50), WORN, false);
+        assert_eq!(
+            status.missing_bud_text().as_deref(),
+            Some("One bud not connected — check the case")
+        );
+
+        assert_eq!(BudsStatus::from_readings((0, 60, 50), WORN, true).missing_bud_text(), None);
+    }
+}
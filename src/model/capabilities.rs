@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use galaxy_buds_rs::model::Model;
+
+/// Describes which optional features and workarounds apply to a Buds model.
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    /// Requests a status update at this interval when the device hasn't sent one on its own.
+    ///
+    /// `None` for models whose firmware reliably pushes status updates.
+    pub status_poll_interval: Option<Duration>,
+    /// Whether noise controls can be allowed while wearing a single earbud.
+    pub noise_controls_with_one_earbud: bool,
+    /// Whether ambient sound can be played while using the earbud microphone in a call.
+    pub ambient_sound_during_calls: bool,
+}
+
+impl Capabilities {
+    pub fn for_model(model: Model) -> Self {
+        match model {
+            // Older firmwares only send status updates sporadically.
+            Model::Buds | Model::BudsPlus => Self {
+                status_poll_interval: Some(Duration::from_secs(30)),
+                noise_controls_with_one_earbud: false,
+                ambient_sound_during_calls: false,
+            },
+            Model::BudsPro => Self {
+                status_poll_interval: None,
+                noise_controls_with_one_earbud: true,
+                ambient_sound_during_calls: true,
+            },
+            _ => Self {
+                status_poll_interval: None,
+                noise_controls_with_one_earbud: false,
+                ambient_sound_during_calls: false,
+            },
+        }
+    }
+}
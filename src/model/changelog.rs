@@ -0,0 +1,64 @@
+//! Release notes compiled in from `data/changelog.json`, to show what changed after an upgrade.
+
+use serde_json::Value;
+use tracing::error;
+
+const CHANGELOG_JSON: &str = include_str!("../../data/changelog.json");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub version: String,
+    /// One sentence per new feature, in the order they're shown.
+    pub features: Vec<String>,
+}
+
+/// Releases newer than `last_seen` up to the running version, newest first.
+///
+/// Every release is newer than an empty `last_seen`.
+pub fn releases_since(last_seen: &str) -> Vec<Release> {
+    let last_seen = parse_version(last_seen);
+    let current = parse_version(env!("CARGO_PKG_VERSION"));
+    let mut releases: Vec<_> = parse(CHANGELOG_JSON)
+        .into_iter()
+        .filter(|release| {
+            let version = parse_version(&release.version);
+            version > last_seen && version <= current
+        })
+        .collect();
+    releases.sort_by_key(|release| std::cmp::Reverse(parse_version(&release.version)));
+    releases
+}
+
+fn parse(json: &str) -> Vec<Release> {
+    let root: Value = match serde_json::from_str(json) {
+        Ok(root) => root,
+        Err(e) => {
+            error!("Failed to parse the changelog: {}", e);
+            return Vec::new();
+        }
+    };
+    root.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|release| {
+            Some(Release {
+                version: release.get("version")?.as_str()?.to_string(),
+                features: release
+                    .get("features")?
+                    .as_array()?
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Splits a version like `0.10.0` into its numbers, so it sorts after `0.9.0`.
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
@@ -0,0 +1,88 @@
+//! Reads the config of GalaxyBudsClient, the Windows app by ThePBone, which dual-boot users
+//! often keep synced to their Linux install.
+
+use serde_json::Value;
+
+/// Settings of the client that this app has nothing to map to yet, by their key in the config.
+const UNSUPPORTED_KEYS: &[(&str, &str)] = &[
+    ("CustomActionLeft", "Touch actions"),
+    ("CustomActionRight", "Touch actions"),
+    ("EqualizerPreset", "Equalizer"),
+];
+
+/// A device the client was paired with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientDevice {
+    pub address: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientConfig {
+    pub devices: Vec<ClientDevice>,
+    /// Address of the device the client was last connected to.
+    pub last_device: Option<String>,
+    /// Titles of the settings found in the config that won't be imported.
+    pub unsupported: Vec<&'static str>,
+}
+
+impl ClientConfig {
+    pub fn parse(json: &str) -> serde_json::Result<Self> {
+        let root: Value = serde_json::from_str(json)?;
+
+        let mut devices: Vec<_> = root
+            .get("Devices")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(device_from_json)
+            .collect();
+        // Older versions only kept a single device.
+        if let Some(device) = root.get("RegisteredDevice").and_then(device_from_json) {
+            if !devices.iter().any(|known| known.address == device.address) {
+                devices.push(device);
+            }
+        }
+
+        let last_device = root
+            .get("LastDeviceMac")
+            .and_then(Value::as_str)
+            .map(normalize_address)
+            .or_else(|| devices.first().map(|device| device.address.clone()));
+
+        let mut unsupported: Vec<_> = UNSUPPORTED_KEYS
+            .iter()
+            .filter(|(key, _)| root.get(key).is_some_and(|value| !value.is_null()))
+            .map(|(_, title)| *title)
+            .collect();
+        unsupported.dedup();
+
+        Ok(Self {
+            devices,
+            last_device,
+            unsupported,
+        })
+    }
+}
+
+fn device_from_json(value: &Value) -> Option<ClientDevice> {
+    let address = value.get("MacAddress")?.as_str()?;
+    if address.is_empty() {
+        return None;
+    }
+    let name = value
+        .get("Name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    Some(ClientDevice {
+        address: normalize_address(address),
+        name,
+    })
+}
+
+/// BlueZ writes addresses in upper case with colons, the client may use dashes.
+fn normalize_address(address: &str) -> String {
+    address.replace('-', ":").to_uppercase()
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::lock_ext::LockOrRecover;
+
+/// How long the user's confirmation stays valid, so a stale one isn't acted on long after.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Unique across every `Confirmations`, so a token can't be redeemed with another device's.
+static NEXT_TOKEN_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Worker operations the user has to confirm, as they can't be undone or affect another
+/// device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DestructiveOperation {
+    /// Takes the audio profiles from the device the buds are connected to.
+    TakeOver,
+    /// Disconnects for good, and possibly removes the Bluetooth pairing.
+    Forget,
+}
+
+/// Proof that the user confirmed a `DestructiveOperation`, which the worker asks for before
+/// carrying it out.
+///
+/// Only `Confirmations::issue` creates one, and it isn't `Clone`, so it's used up with the
+/// operation it was issued for.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConfirmationToken {
+    id: u64,
+    operation: DestructiveOperation,
+}
+
+/// The confirmations of a device that weren't acted on yet, shared between the confirmation
+/// dialog that issues them and the worker that redeems them.
+#[derive(Debug, Clone, Default)]
+pub struct Confirmations {
+    /// Operation and issue time of each token not redeemed yet, by id.
+    issued: Arc<Mutex<HashMap<u64, (DestructiveOperation, Instant)>>>,
+}
+
+impl Confirmations {
+    /// Issues a token for `operation`, once the user confirmed it.
+    ///
+    /// Meant to be called by `DialogConfirm::ask_for` only, as anything else could issue
+    /// tokens the user never confirmed.
+    pub fn issue(&self, operation: DestructiveOperation) -> ConfirmationToken {
+        self.issue_at(operation, Instant::now())
+    }
+
+    /// Checks that `token` was issued here for `operation` and hasn't expired.
+    pub fn redeem(
+        &self,
+        token: ConfirmationToken,
+        operation: DestructiveOperation,
+    ) -> Result<(), String> {
+        self.redeem_at(token, operation, Instant::now())
+    }
+
+    fn issue_at(&self, operation: DestructiveOperation, now: Instant) -> ConfirmationToken {
+        let id = NEXT_TOKEN_ID.fetch_add(1, Ordering::Relaxed);
+        let mut issued = self.issued.lock_or_recover();
+        // Tokens of dialogs answered but never acted on, e.g. as the page closed.
+        issued.retain(|_, (_, issued_at)| now.duration_since(*issued_at) < TOKEN_LIFETIME);
+        issued.insert(id, (operation, now));
+        ConfirmationToken { id, operation }
+    }
+
+    fn redeem_at(
+        &self,
+        token: ConfirmationToken,
+        operation: DestructiveOperation,
+        now: Instant,
+    ) -> Result<(), String> {
+        if token.operation != operation {
+            return Err(format!(
+                "The confirmation was for {:?}, not {:?}",
+                token.operation, operation
+            ));
+        }
+        let Some((_, issued_at)) = self.issued.lock_or_recover().remove(&token.id) else {
+            return Err(format!("{:?} wasn't confirmed for this device", operation));
+        };
+        if now.duration_since(issued_at) >= TOKEN_LIFETIME {
+            return Err(format!("The confirmation of {:?} expired", operation));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redeems_a_token_for_the_operation_it_was_issued_for() {
+        let confirmations = Confirmations::default();
+        let token = confirmations.issue(DestructiveOperation::Forget);
+        assert_eq!(
+            confirmations.redeem(token, DestructiveOperation::Forget),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_a_token_issued_for_another_operation() {
+        let confirmations = Confirmations::default();
+        let token = confirmations.issue(DestructiveOperation::TakeOver);
+        assert!(
+            confirmations
+                .redeem(token, DestructiveOperation::Forget)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_a_token_issued_for_another_device() {
+        let confirmations = Confirmations::default();
+        let other_device = Confirmations::default();
+        let token = other_device.issue(DestructiveOperation::Forget);
+        assert!(
+            confirmations
+                .redeem(token, DestructiveOperation::Forget)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn shares_the_issued_tokens_between_clones() {
+        let dialog = Confirmations::default();
+        let worker = dialog.clone();
+        let token = dialog.issue(DestructiveOperation::TakeOver);
+        assert_eq!(worker.redeem(token, DestructiveOperation::TakeOver), Ok(()));
+    }
+
+    #[test]
+    fn rejects_an_expired_token() {
+        let confirmations = Confirmations::default();
+        let now = Instant::now();
+        let token = confirmations.issue_at(DestructiveOperation::Forget, now);
+        let later = now + TOKEN_LIFETIME;
+        assert!(
+            confirmations
+                .redeem_at(token, DestructiveOperation::Forget, later)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn forgets_expired_tokens_when_issuing() {
+        let confirmations = Confirmations::default();
+        let now = Instant::now();
+        let _stale = confirmations.issue_at(DestructiveOperation::Forget, now);
+        let token = confirmations.issue_at(DestructiveOperation::Forget, now + TOKEN_LIFETIME);
+        assert_eq!(confirmations.issued.lock_or_recover().len(), 1);
+        assert_eq!(
+            confirmations.redeem_at(token, DestructiveOperation::Forget, now + TOKEN_LIFETIME),
+            Ok(())
+        );
+    }
+}
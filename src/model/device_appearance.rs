@@ -0,0 +1,101 @@
+use gtk4::gio::prelude::SettingsExt;
+
+use crate::consts::{DEVICE_COLOR_KEY, DEVICE_EMOJI_KEY, DEVICE_LABEL_KEY};
+
+/// A color a device can be marked with.
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceColor {
+    /// As stored in the settings.
+    pub id: &'static str,
+    pub title: &'static str,
+    /// CSS class painting a widget in the color, see `style.css`.
+    pub css_class: &'static str,
+    /// CSS class painting an `adw::Avatar` in the color.
+    pub avatar_css_class: &'static str,
+}
+
+impl DeviceColor {
+    const fn new(
+        id: &'static str,
+        title: &'static str,
+        css_class: &'static str,
+        avatar_css_class: &'static str,
+    ) -> Self {
+        Self {
+            id,
+            title,
+            css_class,
+            avatar_css_class,
+        }
+    }
+}
+
+pub const DEVICE_COLORS: &[DeviceColor] = &[
+    DeviceColor::new("", "None", "", ""),
+    DeviceColor::new("blue", "Blue", "device-blue", "device-avatar-blue"),
+    DeviceColor::new("green", "Green", "device-green", "device-avatar-green"),
+    DeviceColor::new("yellow", "Yellow", "device-yellow", "device-avatar-yellow"),
+    DeviceColor::new("orange", "Orange", "device-orange", "device-avatar-orange"),
+    DeviceColor::new("red", "Red", "device-red", "device-avatar-red"),
+    DeviceColor::new("purple", "Purple", "device-purple", "device-avatar-purple"),
+];
+
+/// How the user chose to tell a device apart, e.g. from identical buds in the same household.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeviceAppearance {
+    pub label: String,
+    pub emoji: String,
+    /// Id of one of `DEVICE_COLORS`, empty for none.
+    pub color: String,
+}
+
+impl DeviceAppearance {
+    pub fn from_settings(settings: &gtk4::gio::Settings) -> Self {
+        Self {
+            label: settings.string(DEVICE_LABEL_KEY).to_string(),
+            emoji: settings.string(DEVICE_EMOJI_KEY).to_string(),
+            color: settings.string(DEVICE_COLOR_KEY).to_string(),
+        }
+    }
+
+    /// The label, or `name` when none was set, after the emoji if there's one.
+    pub fn display_name(&self, name: &str) -> String {
+        let name = if self.label.is_empty() {
+            name
+        } else {
+            &self.label
+        };
+        if self.emoji.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} {}", self.emoji, name)
+        }
+    }
+
+    /// CSS class painting the picked color, or `None` when there's none.
+    pub fn color_class(&self) -> Option<&'static str> {
+        DEVICE_COLORS
+            .iter()
+            .find(|color| !color.id.is_empty() && color.id == self.color)
+            .map(|color| color.css_class)
+    }
+}
+
+/// Text of the avatar shown until there's artwork of the device, so its initials are those of
+/// the model, e.g. "BP" for "Galaxy Buds Pro (7A2F)".
+pub fn avatar_text(name: &str) -> String {
+    let name = name.split('(').next().unwrap_or_default().trim();
+    name.strip_prefix("Galaxy ").unwrap_or(name).to_string()
+}
+
+/// CSS class painting the avatar of the device at `address` in one of `DEVICE_COLORS`.
+///
+/// Always the same for the same address, so identical buds still look different.
+pub fn avatar_color_class(address: &str) -> &'static str {
+    let hash = address
+        .bytes()
+        .fold(0u32, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte.into()));
+    // Skipping the "None" color.
+    let colors = &DEVICE_COLORS[1..];
+    colors[hash as usize % colors.len()].avatar_css_class
+}
@@ -1,25 +1,142 @@
-use bluer::Device;
+use std::collections::HashSet;
+
+use bluer::{Address, Device, Modalias, Session, Uuid};
+use futures::join;
+use galaxy_buds_rs::model::Model;
+
+use crate::{consts::KNOWN_SPP_SERVICES, tuning};
 
 #[derive(Debug, Clone)]
 pub struct DeviceInfo {
     pub name: String,
     pub address: String,
     pub device: Device,
+    /// Vendor/product identification reported by BlueZ, if any.
+    pub modalias: Option<String>,
+    /// Name given to the device locally, which BlueZ sets to `name` unless it was changed.
+    pub alias: Option<String>,
+    /// Icon name BlueZ picked for the device class, e.g. `audio-headphones`.
+    pub icon: Option<String>,
+    pub is_paired: bool,
+    pub is_connected: bool,
+    /// Battery level reported through BlueZ, which the buds may not expose.
+    pub battery: Option<u8>,
+    /// UUID of the SPP service the buds are reached by, `None` if they expose no known one.
+    pub spp_uuid: Option<Uuid>,
 }
 
 impl DeviceInfo {
+    /// Reads the device properties from BlueZ, all at once.
     pub async fn from_device(device: Device) -> Self {
-        let name = match device.name().await {
-            Ok(Some(n)) => n,
-            _ => "Unknown".to_string(),
+        let mut info = DeviceInfo {
+            name: String::new(),
+            address: device.address().to_string(),
+            device,
+            modalias: None,
+            alias: None,
+            icon: None,
+            is_paired: false,
+            is_connected: false,
+            battery: None,
+            spp_uuid: None,
         };
+        info.refresh().await;
+        info
+    }
 
-        let address = device.address().to_string();
+    /// Reads the properties of the device known to the default adapter under `address`.
+    pub async fn from_address(address: &str) -> Result<Self, String> {
+        let address: Address = address.parse().map_err(|e| format!("Invalid address: {}", e))?;
+        let session = Session::new().await.map_err(|e| e.to_string())?;
+        let adapter = session.default_adapter().await.map_err(|e| e.to_string())?;
+        let device = adapter.device(address).map_err(|e| e.to_string())?;
+        Ok(Self::from_device(device).await)
+    }
 
-        DeviceInfo {
-            name,
-            address,
-            device,
+    /// Reads the cached properties again, e.g. before showing them after a while.
+    ///
+    /// Properties that can't be read are reset rather than kept, as the device may be gone.
+    pub async fn refresh(&mut self) {
+        let device = &self.device;
+        let (name, alias, icon, is_paired, is_connected, battery, modalias, uuids) = join!(
+            device.name(),
+            device.alias(),
+            device.icon(),
+            device.is_paired(),
+            device.is_connected(),
+            device.battery_percentage(),
+            device.modalias(),
+            device.uuids(),
+        );
+
+        self.name = name.ok().flatten().unwrap_or_else(|| "Unknown".to_string());
+        self.alias = alias.ok();
+        self.icon = icon.ok().flatten();
+        self.is_paired = is_paired.unwrap_or(false);
+        self.is_connected = is_connected.unwrap_or(false);
+        self.battery = battery.ok().flatten();
+        self.modalias = modalias.ok().flatten().as_ref().map(modalias_text);
+        self.spp_uuid = uuids
+            .ok()
+            .flatten()
+            .and_then(|uuids| matching_spp_uuid(&uuids, &self.name));
+    }
+
+    /// Guesses the Buds model from the advertised device name.
+    ///
+    /// Falls back to `Model::BudsLive` when the name doesn't match any known model.
+    pub fn model(&self) -> Model {
+        let name = self.name.to_lowercase();
+        if name.contains("buds live") {
+            Model::BudsLive
+        } else if name.contains("buds pro") {
+            Model::BudsPro
+        } else if name.contains("buds+") {
+            Model::BudsPlus
+        } else if name.contains("galaxy buds (") {
+            Model::Buds
+        } else {
+            Model::BudsLive
         }
     }
+
+    /// Describes the raw identification data of the device, to be included in bug reports.
+    pub fn identification_text(&self) -> String {
+        format!(
+            "Name: {}\nAddress: {}\nModalias: {}",
+            self.name,
+            self.address,
+            self.modalias.as_deref().unwrap_or("N/A")
+        )
+    }
+
+    /// Removes the device from the adapter, dropping the system-wide Bluetooth pairing.
+    pub async fn unpair(&self) -> bluer::Result<()> {
+        let session = Session::new().await?;
+        let adapter = session.adapter(self.device.adapter_name())?;
+        adapter.remove_device(self.device.address()).await
+    }
+}
+
+fn modalias_text(modalias: &Modalias) -> String {
+    format!(
+        "{}:v{:04X}p{:04X}d{:04X}",
+        modalias.source, modalias.vendor, modalias.product, modalias.device
+    )
+}
+
+/// The UUID of the SPP service a device with `name` exposing `uuids` is reached by, if it's
+/// one the buds use.
+///
+/// Only `Tuning::spp_uuid` is looked for when it's set.
+pub fn matching_spp_uuid(uuids: &HashSet<Uuid>, name: &str) -> Option<Uuid> {
+    if let Some(uuid) = tuning::get().spp_uuid {
+        return uuids.contains(&uuid).then_some(uuid);
+    }
+    let name = name.to_lowercase();
+    KNOWN_SPP_SERVICES.iter().find_map(|(uuid, name_hint)| {
+        let uuid: Uuid = uuid.parse().expect("KNOWN_SPP_SERVICES holds valid UUIDs");
+        let is_match = uuids.contains(&uuid) && name_hint.is_none_or(|hint| name.contains(hint));
+        is_match.then_some(uuid)
+    })
 }
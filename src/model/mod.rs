@@ -1,4 +1,13 @@
+pub mod battery_stats;
 pub mod buds_message;
 pub mod buds_status;
+pub mod capabilities;
+pub mod changelog;
+pub mod client_config;
+pub mod confirmation;
+pub mod device_appearance;
 pub mod device_info;
+pub mod policy;
+pub mod session_info;
+pub mod unknown_report;
 pub mod util;
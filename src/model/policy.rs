@@ -0,0 +1,40 @@
+//! Automatic changes made in reaction to the device status.
+
+use galaxy_buds_rs::message::bud_property::NoiseControlMode;
+
+use crate::model::buds_status::BudsStatus;
+
+/// The noise control mode to switch to after the placement of the buds changed, if any.
+///
+/// Noise control keeps drawing power while the buds sit in an open case, so it's turned off
+/// once both go in. It isn't turned back on when they come out.
+pub fn noise_control_on_placement(
+    was_in_case: bool,
+    status: &BudsStatus,
+) -> Option<NoiseControlMode> {
+    if !was_in_case
+        && status.are_both_in_case()
+        && status
+            .noise_control_mode()
+            .is_some_and(|mode| mode != NoiseControlMode::Off)
+    {
+        Some(NoiseControlMode::Off)
+    } else {
+        None
+    }
+}
+
+/// Battery levels, in percent, at which the user is told the buds are running low.
+pub const LOW_BATTERY_LEVELS: [i8; 2] = [20, 10];
+
+/// The lowest of `LOW_BATTERY_LEVELS` the battery went below since the previous reading, if any.
+///
+/// Nothing is crossed while either reading is unknown, so connecting to buds that are already
+/// low doesn't count.
+pub fn low_battery_crossed(previous: Option<i8>, current: Option<i8>) -> Option<i8> {
+    let (previous, current) = (previous?, current?);
+    LOW_BATTERY_LEVELS
+        .into_iter()
+        .filter(|level| previous >= *level && current < *level)
+        .min()
+}
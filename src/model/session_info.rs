@@ -0,0 +1,26 @@
+use galaxy_buds_rs::{message::extended_status_updated::ExtendedStatusUpdate, model::Model};
+
+/// What is known about the device once it has answered the manager info request.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionInfo {
+    /// The model the session's messages are parsed for.
+    ///
+    /// The response doesn't identify the model, so this is still guessed from the device name.
+    pub model: Model,
+    /// Protocol revision reported by the device firmware.
+    pub revision: u8,
+}
+
+impl SessionInfo {
+    /// Reads the session from the response to the manager info request, which the device
+    /// sends as an extended status update.
+    ///
+    /// Nothing is negotiated: the request is always the same, and only the revision is taken
+    /// from the response.
+    pub fn from_response(model: Model, response: &ExtendedStatusUpdate) -> Self {
+        Self {
+            model,
+            revision: response.revision,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use bytes::Bytes;
+
+use crate::{
+    consts::ISSUES_URL,
+    model::{session_info::SessionInfo, util::to_hex},
+};
+
+/// Most samples put in a report, so the issue URL stays short enough for browsers.
+pub const MAX_REPORT_SAMPLES: usize = 5;
+
+/// Samples of a message id the app can't parse yet, to be reported upstream.
+#[derive(Debug, Clone)]
+pub struct UnknownIdReport {
+    pub id: u8,
+    /// The session the first sample came from, `None` for imported captures.
+    pub session: Option<SessionInfo>,
+    pub samples: Vec<Bytes>,
+}
+
+impl UnknownIdReport {
+    pub fn title(&self) -> String {
+        format!("Unsupported message id {} (0x{:02X})", self.id, self.id)
+    }
+
+    /// Describes the message as the body of a GitHub issue.
+    pub fn markdown(&self) -> String {
+        let (model, revision) = match &self.session {
+            Some(session) => (format!("{:?}", session.model), session.revision.to_string()),
+            None => ("Unknown (imported capture)".to_string(), "N/A".to_string()),
+        };
+
+        let mut body = format!(
+            "### Message\n\n\
+             - Id: {} (0x{:02X})\n\
+             - Model: {}\n\
+             - Protocol revision: {}\n\
+             - Firmware: <!-- As shown in the Galaxy Wearable app -->\n\
+             - App version: {}\n\n\
+             ### Samples\n\n```\n",
+            self.id,
+            self.id,
+            model,
+            revision,
+            env!("CARGO_PKG_VERSION")
+        );
+        for sample in &self.samples {
+            body.push_str(&to_hex(sample));
+            body.push('\n');
+        }
+        body.push_str(
+            "```\n\n### What was happening\n\n\
+             <!-- E.g. the buds were put in the case, or a setting was changed -->\n",
+        );
+        body
+    }
+
+    /// Link to a new issue, filled in with the report.
+    pub fn issue_url(&self) -> String {
+        let escape = |text: &str| gtk4::glib::Uri::escape_string(text, None, false);
+        format!(
+            "{}/new?title={}&body={}",
+            ISSUES_URL,
+            escape(&self.title()),
+            escape(&self.markdown())
+        )
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 pub trait OptionNaExt <T>{
     fn or_na<F>(&self, f: F) -> String
     where
@@ -15,3 +17,44 @@ impl<T> OptionNaExt<T> for Option<T> {
         }
     }
 }
+
+/// Describes how long ago `time` was, e.g. "2 hours ago".
+pub fn relative_time(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    let (count, unit) = match secs {
+        0..60 => return "just now".to_string(),
+        60..3600 => (secs / 60, "minute"),
+        3600..86400 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+/// Formats bytes as space-separated, uppercase hex pairs.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses hex bytes, with or without whitespace between them, e.g. `FD 03 00` or `fd0300`.
+pub fn parse_hex(input: &str) -> Result<Vec<u8>, String> {
+    let digits: String = input.split_whitespace().collect();
+    if digits.is_empty() || !digits.is_ascii() || digits.len() % 2 != 0 {
+        return Err("Expected an even number of hex digits".to_string());
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("Invalid hex byte: {}", &digits[i..i + 2]))
+        })
+        .collect()
+}
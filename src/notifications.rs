@@ -0,0 +1,42 @@
+//! Desktop notifications, with buttons for the most common actions so the window doesn't have
+//! to be opened for them.
+
+use gtk4::{gio::prelude::ApplicationExt, glib::variant::ToVariant};
+
+/// Sent when the case is opened, if enabled in the preferences.
+pub fn case_opened(battery_summary: &str) {
+    let notification = gtk4::gio::Notification::new("Case opened");
+    notification.set_body(Some(battery_summary));
+    send("case-opened", &notification);
+}
+
+/// Sent when the buds connect while the window isn't visible.
+pub fn connected(device_name: &str) {
+    let notification = gtk4::gio::Notification::new(&format!("{} connected", device_name));
+    add_buttons(&notification);
+    send("connected", &notification);
+}
+
+/// Sent when the emptier bud drops below one of `policy::LOW_BATTERY_LEVELS`.
+///
+/// Clicking it opens the page of the device at `address`.
+pub fn low_battery(address: &str, level: i8) {
+    let notification = gtk4::gio::Notification::new("Buds battery low");
+    notification.set_body(Some(&format!("Below {}%", level)));
+    notification.set_priority(gtk4::gio::NotificationPriority::High);
+    notification
+        .set_default_action_and_target_value("app.open-device", Some(&address.to_variant()));
+    add_buttons(&notification);
+    send("low-battery", &notification);
+}
+
+/// Adds the buttons for the app actions registered in `actions::register_app_actions`.
+fn add_buttons(notification: &gtk4::gio::Notification) {
+    notification.add_button("Switch to ANC", "app.switch-to-anc");
+    notification.add_button("Find", "app.find-buds");
+}
+
+/// Sends the notification under `id`, replacing the previous one of the same kind.
+fn send(id: &str, notification: &gtk4::gio::Notification) {
+    relm4::main_application().send_notification(Some(id), notification);
+}
@@ -0,0 +1,184 @@
+//! Hides Bluetooth addresses and serial numbers in log files and exports, so they can be shared
+//! publicly.
+//!
+//! They are replaced by a hash rather than removed, so lines about the same device can still be
+//! matched up.
+
+use std::{borrow::Cow, sync::OnceLock};
+
+use gtk4::glib::{self, ChecksumType};
+
+use crate::logging::state_dir;
+
+/// Length of an address written as six hex pairs with separators, e.g. `AA:BB:CC:DD:EE:FF`.
+const ADDRESS_LEN: usize = 17;
+/// Length of a Samsung serial number, e.g. `R3CT40ABCDE`.
+const SERIAL_LEN: usize = 11;
+/// Fewest digits in a serial number, so uppercase words like `RECONNECTING` aren't taken for one.
+const MIN_SERIAL_DIGITS: usize = 2;
+/// Hex digits of the hash kept in place of an identifier.
+const HASH_LEN: usize = 8;
+
+/// Replaces every Bluetooth address in `text` with `<device-…>` and every serial number with
+/// `<serial-…>`, each followed by a salted hash of what it replaces.
+///
+/// Both `:` and `-` separators are recognized in addresses, in either case. Returns `text`
+/// unchanged when it holds neither.
+pub fn identifiers(text: &str) -> Cow<'_, str> {
+    redact(text, salt())
+}
+
+fn redact<'a>(text: &'a str, salt: &str) -> Cow<'a, str> {
+    let bytes = text.as_bytes();
+    let mut redacted = String::new();
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let found = if is_address_at(bytes, i) {
+            let address = text[i..i + ADDRESS_LEN].to_ascii_uppercase().replace('-', ":");
+            Some((ADDRESS_LEN, token("device", salt, &address)))
+        } else if is_serial_at(bytes, i) {
+            Some((SERIAL_LEN, token("serial", salt, &text[i..i + SERIAL_LEN])))
+        } else {
+            None
+        };
+        match found {
+            Some((len, token)) => {
+                redacted.push_str(&text[copied..i]);
+                redacted.push_str(&token);
+                i += len;
+                copied = i;
+            }
+            None => i += 1,
+        }
+    }
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    redacted.push_str(&text[copied..]);
+    Cow::Owned(redacted)
+}
+
+/// Whether an address starts at `start`, and isn't part of a longer run of hex digits.
+fn is_address_at(bytes: &[u8], start: usize) -> bool {
+    let Some(candidate) = bytes.get(start..start + ADDRESS_LEN) else {
+        return false;
+    };
+    let separator = candidate[2];
+    if separator != b':' && separator != b'-' {
+        return false;
+    }
+    let is_address = candidate.iter().enumerate().all(|(i, byte)| {
+        if i % 3 == 2 {
+            *byte == separator
+        } else {
+            byte.is_ascii_hexdigit()
+        }
+    });
+    let is_bounded = |byte: Option<&u8>| {
+        byte.is_none_or(|byte| !byte.is_ascii_alphanumeric() && *byte != separator)
+    };
+    is_address
+        && is_bounded(start.checked_sub(1).and_then(|before| bytes.get(before)))
+        && is_bounded(bytes.get(start + ADDRESS_LEN))
+}
+
+/// Whether a serial number starts at `start`: an `R` and ten uppercase letters or digits, the
+/// way Samsung numbers its phones and buds, not inside a longer word.
+fn is_serial_at(bytes: &[u8], start: usize) -> bool {
+    let Some(candidate) = bytes.get(start..start + SERIAL_LEN) else {
+        return false;
+    };
+    let is_serial = candidate[0] == b'R'
+        && candidate
+            .iter()
+            .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+        && candidate.iter().filter(|byte| byte.is_ascii_digit()).count() >= MIN_SERIAL_DIGITS;
+    let is_bounded = |byte: Option<&u8>| byte.is_none_or(|byte| !byte.is_ascii_alphanumeric());
+    is_serial
+        && is_bounded(start.checked_sub(1).and_then(|before| bytes.get(before)))
+        && is_bounded(bytes.get(start + SERIAL_LEN))
+}
+
+fn token(kind: &str, salt: &str, value: &str) -> String {
+    let hash = glib::compute_checksum_for_str(ChecksumType::Sha256, &format!("{salt}{value}"))
+        .map(|hash| hash.as_str()[..HASH_LEN].to_string())
+        .unwrap_or_default();
+    format!("<{}-{}>", kind, hash)
+}
+
+/// Random value the hashes are salted with, as addresses only have about 24 unknown bits once
+/// the manufacturer's prefix is known, few enough to hash them all.
+///
+/// Saved so an identifier gets the same hash across launches. Failing to read or save it isn't
+/// logged, as this runs while writing log lines; the salt then only lasts until the app exits.
+fn salt() -> &'static str {
+    static SALT: OnceLock<String> = OnceLock::new();
+    SALT.get_or_init(|| {
+        let path = state_dir().join("redaction-salt");
+        if let Ok(salt) = std::fs::read_to_string(&path)
+            && !salt.trim().is_empty()
+        {
+            return salt.trim().to_string();
+        }
+        let salt = glib::uuid_string_random().to_string();
+        let _ = std::fs::create_dir_all(state_dir()).and_then(|_| std::fs::write(&path, &salt));
+        salt
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT: &str = "test-salt";
+
+    #[test]
+    fn hashes_addresses_with_either_separator_and_case() {
+        let redacted = redact("Connecting to AA:BB:CC:DD:EE:FF", SALT);
+        assert!(redacted.starts_with("Connecting to <device-"), "{}", redacted);
+        assert!(!redacted.contains("AA:BB"));
+
+        assert_eq!(redact("aa-bb-cc-dd-ee-ff", SALT), redact("AA:BB:CC:DD:EE:FF", SALT));
+    }
+
+    #[test]
+    fn leaves_longer_runs_of_hex_digits_alone() {
+        for text in [
+            "AA:BB:CC:DD:EE:FF:00",
+            "00:AA:BB:CC:DD:EE:FF",
+            "AA:BB:CC:DD:EE:FFF",
+            "XAA:BB:CC:DD:EE:FF",
+            "AA:BB-CC:DD:EE:FF",
+        ] {
+            assert_eq!(redact(text, SALT), text);
+        }
+    }
+
+    #[test]
+    fn returns_text_without_identifiers_unchanged() {
+        assert!(matches!(redact("Battery: 80%", SALT), Cow::Borrowed("Battery: 80%")));
+        assert!(matches!(redact("", SALT), Cow::Borrowed("")));
+    }
+
+    #[test]
+    fn hashes_serial_numbers() {
+        let redacted = redact("Serial: R3CT40ABCDE, left", SALT);
+        assert!(redacted.starts_with("Serial: <serial-"), "{}", redacted);
+        assert!(redacted.ends_with(">, left"), "{}", redacted);
+    }
+
+    #[test]
+    fn leaves_words_that_look_like_serials_alone() {
+        for text in ["RECONNECTED", "R3CT40ABCDEF", "XR3CT40ABCDE", "R3ct40abcde", "RECONNECT1D"] {
+            assert_eq!(redact(text, SALT), text);
+        }
+    }
+
+    #[test]
+    fn hashes_differently_with_another_salt() {
+        let address = "AA:BB:CC:DD:EE:FF";
+        assert_eq!(redact(address, SALT), redact(address, SALT));
+        assert_ne!(redact(address, SALT), redact(address, "another-salt"));
+    }
+}
@@ -0,0 +1,40 @@
+use std::fmt::Debug;
+
+use relm4::Sender;
+use tracing::warn;
+
+/// Extension for senders whose receiver may already be gone.
+pub trait SendOrLog<T> {
+    /// Sends a message, logging a warning instead of failing if the receiver was dropped.
+    ///
+    /// Receivers go away with their component, e.g. when a page is popped while a
+    /// connection attempt is still in flight, so this isn't worth crashing over.
+    fn send_or_log(&self, message: T);
+}
+
+impl<T: Debug> SendOrLog<T> for Sender<T> {
+    fn send_or_log(&self, message: T) {
+        if let Err(message) = self.send(message) {
+            warn!("Receiver dropped, could not send {:?}", message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sending_to_a_dropped_receiver_doesnt_panic() {
+        let (sender, receiver) = relm4::channel::<u32>();
+        drop(receiver);
+        sender.send_or_log(1);
+    }
+
+    #[test]
+    fn sending_to_a_live_receiver_delivers() {
+        let (sender, receiver) = relm4::channel::<u32>();
+        sender.send_or_log(1);
+        assert_eq!(receiver.recv_sync(), Some(1));
+    }
+}
@@ -0,0 +1,114 @@
+//! Snapshots the session, so it can be restored after a crash.
+//!
+//! The snapshot is written periodically and by the panic hook, and removed when the app quits
+//! normally, so one left over at startup means the last session didn't end cleanly.
+
+use std::{
+    io::ErrorKind,
+    path::PathBuf,
+    sync::Mutex,
+    time::Duration,
+};
+
+use galaxy_buds_rs::message::bud_property::NoiseControlMode;
+use serde_json::{Value, json};
+use tracing::{debug, error};
+
+use crate::{
+    app::page_manage::PageId, dbus, lock_ext::LockOrRecover, logging::state_dir, window_state,
+};
+
+/// How often the snapshot is written while the app runs.
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The latest snapshot, kept here for the panic hook, which can't reach the app.
+static LATEST: Mutex<Option<SessionSnapshot>> = Mutex::new(None);
+
+/// The part of a session worth restoring: the device page that was open and what it was doing.
+#[derive(Debug, Clone)]
+pub struct SessionSnapshot {
+    /// Address of the device whose page was open.
+    pub address: String,
+    /// The device subpage that was open, if any.
+    pub subpage: Option<PageId>,
+    /// Noise modes queued by automations that the device didn't get yet.
+    pub queued_noise_modes: Vec<NoiseControlMode>,
+}
+
+fn path() -> PathBuf {
+    state_dir().join("session.json")
+}
+
+/// Keeps `snapshot` as the latest one, `None` when there's nothing to restore.
+///
+/// Only kept in memory until the next `save`.
+pub fn record(snapshot: Option<SessionSnapshot>) {
+    *LATEST.lock_or_recover() = snapshot;
+}
+
+/// Writes the latest snapshot, or removes the saved one when there's nothing to restore.
+pub fn save() {
+    let json = LATEST.lock_or_recover().as_ref().map(to_json);
+    let result = match json {
+        Some(json) => std::fs::create_dir_all(state_dir())
+            .and_then(|_| std::fs::write(path(), json.to_string())),
+        None => remove(),
+    };
+    if let Err(e) = result {
+        error!("Failed to save the session snapshot: {}", e);
+    }
+}
+
+/// Forgets the session when the app quits normally, so it isn't offered on the next launch.
+pub fn discard() {
+    record(None);
+    save();
+}
+
+/// Takes the snapshot left by a session that didn't end cleanly, if there is one.
+pub fn take_previous() -> Option<SessionSnapshot> {
+    let json = std::fs::read_to_string(path()).ok()?;
+    if let Err(e) = remove() {
+        error!("Failed to remove the session snapshot: {}", e);
+    }
+    let snapshot = from_json(&json);
+    match &snapshot {
+        Some(snapshot) => debug!(?snapshot, "The previous session didn't end cleanly"),
+        None => error!("Failed to parse the session snapshot"),
+    }
+    snapshot
+}
+
+fn remove() -> std::io::Result<()> {
+    match std::fs::remove_file(path()) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+fn to_json(snapshot: &SessionSnapshot) -> Value {
+    let queued_noise_modes: Vec<_> = snapshot
+        .queued_noise_modes
+        .iter()
+        .map(|mode| dbus::noise_mode_name(*mode))
+        .collect();
+    json!({
+        "address": snapshot.address,
+        "subpage": window_state::subpage_name(snapshot.subpage),
+        "queued-noise-modes": queued_noise_modes,
+    })
+}
+
+fn from_json(json: &str) -> Option<SessionSnapshot> {
+    let root: Value = serde_json::from_str(json).ok()?;
+    let queued_noise_modes = root["queued-noise-modes"]
+        .as_array()?
+        .iter()
+        .filter_map(|mode| dbus::parse_noise_mode(mode.as_str()?))
+        .collect();
+    Some(SessionSnapshot {
+        address: root["address"].as_str()?.to_string(),
+        subpage: window_state::parse_subpage(root["subpage"].as_str()?),
+        queued_noise_modes,
+    })
+}
@@ -1,29 +1,245 @@
-use crate::consts::APP_ID;
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+#[cfg(debug_assertions)]
+use std::sync::OnceLock;
+
+use gtk4::{
+    gio::prelude::{SettingsExt, SettingsSchemaExt},
+    glib::{self, object::ObjectExt},
+};
+use tokio::sync::mpsc;
+use tracing::{debug, error};
+
+use crate::consts::{APP_ID, DEVICE_SCHEMA_ID};
+
+/// How long to wait for more changes before writing them.
+const WRITE_DEBOUNCE: Duration = Duration::from_millis(500);
 
 #[cfg(debug_assertions)]
 include!(concat!(env!("OUT_DIR"), "/settings_schema_path.rs"));
 
 pub fn get_settings() -> gtk4::gio::Settings {
-    #[cfg(debug_assertions)]
-    {
-        println!("Running in DEBUG mode. Loading schema from build directory.");
+    new_settings(APP_ID, None)
+}
 
-        let schema_source = gtk4::gio::SettingsSchemaSource::from_directory(
+/// Settings of a single device, e.g. the label it's shown with.
+pub fn get_device_settings(address: &str) -> gtk4::gio::Settings {
+    // Paths only allow alphanumerics, dashes and underscores.
+    let path = format!(
+        "/com/github/rodrigost23/GalaxyBudsGui/devices/{}/",
+        address.replace(':', "")
+    );
+    new_settings(DEVICE_SCHEMA_ID, Some(&path))
+}
+
+/// Resets the saved settings of the device at `address` to their defaults, except `kept_keys`.
+pub fn reset_device_settings(address: &str, kept_keys: &[&str]) {
+    let device_settings = get_device_settings(address);
+    let Some(schema) = device_settings.settings_schema() else {
+        return;
+    };
+    for key in schema.list_keys() {
+        if !kept_keys.contains(&key.as_str()) {
+            device_settings.reset(&key);
+        }
+    }
+}
+
+/// The schemas compiled into the build directory, loaded once rather than for every device.
+#[cfg(debug_assertions)]
+fn debug_schema_source() -> &'static gtk4::gio::SettingsSchemaSource {
+    static SCHEMA_SOURCE: OnceLock<gtk4::gio::SettingsSchemaSource> = OnceLock::new();
+    SCHEMA_SOURCE.get_or_init(|| {
+        debug!("Running in DEBUG mode. Loading schema from build directory.");
+        gtk4::gio::SettingsSchemaSource::from_directory(
             GSETTINGS_SCHEMA_DIR,
             gtk4::gio::SettingsSchemaSource::default().as_ref(),
             false,
         )
-        .expect("Could not create settings schema source in debug");
+        .expect("Could not create settings schema source in debug")
+    })
+}
 
-        let schema = schema_source
-            .lookup(APP_ID, false)
+fn new_settings(schema_id: &str, path: Option<&str>) -> gtk4::gio::Settings {
+    #[cfg(debug_assertions)]
+    {
+        let schema = debug_schema_source()
+            .lookup(schema_id, false)
             .expect("Schema not found in debug");
 
-        gtk4::gio::Settings::new_full(&schema, None::<&gtk4::gio::SettingsBackend>, None)
+        gtk4::gio::Settings::new_full(&schema, None::<&gtk4::gio::SettingsBackend>, path)
     }
     #[cfg(not(debug_assertions))]
     {
-        println!("Running in RELEASE mode. Loading schema from system path.");
-        gtk4::gio::Settings::new(APP_ID)
+        debug!(schema_id, "Loading schema from system path");
+        match path {
+            Some(path) => gtk4::gio::Settings::with_path(schema_id, path),
+            None => gtk4::gio::Settings::new(schema_id),
+        }
+    }
+}
+
+/// Calls back when settings change, so options take effect without restarting the app.
+///
+/// The callbacks are disconnected when it's dropped, e.g. along with the component owning it.
+#[derive(Debug)]
+pub struct SettingsDispatcher {
+    settings: gtk4::gio::Settings,
+    handlers: Vec<glib::SignalHandlerId>,
+}
+
+impl SettingsDispatcher {
+    pub fn new(settings: gtk4::gio::Settings) -> Self {
+        Self {
+            settings,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Calls `callback` with the settings whenever one of `keys` changes.
+    pub fn on_change(
+        &mut self,
+        keys: &[&'static str],
+        callback: impl Fn(&gtk4::gio::Settings) + Clone + 'static,
+    ) {
+        for key in keys {
+            let callback = callback.clone();
+            let handler = self
+                .settings
+                .connect_changed(Some(key), move |settings, _| callback(settings));
+            self.handlers.push(handler);
+        }
+    }
+}
+
+impl Drop for SettingsDispatcher {
+    fn drop(&mut self) {
+        for handler in self.handlers.drain(..) {
+            self.settings.disconnect(handler);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    String(String),
+    Boolean(bool),
+    Int(i32),
+    Int64(i64),
+    StringArray(Vec<String>),
+}
+
+/// Pending values by device address, `None` for the app's own settings, and key.
+type PendingWrites = Rc<RefCell<HashMap<(Option<String>, &'static str), SettingValue>>>;
+
+/// Writes settings from a task on the main loop rather than during `update`, where a busy
+/// dconf would stall the UI.
+///
+/// Writes are batched until no change came in for `WRITE_DEBOUNCE`, and only the last value
+/// set for each key is written. Reading a key right after setting it may still return the
+/// old value.
+#[derive(Debug, Clone)]
+pub struct SettingsWriter {
+    settings: gtk4::gio::Settings,
+    /// Address of the device whose settings are written, see `for_device`.
+    device: Option<String>,
+    pending: PendingWrites,
+    sender: mpsc::UnboundedSender<()>,
+}
+
+impl SettingsWriter {
+    pub fn new(settings: gtk4::gio::Settings) -> Self {
+        let pending = PendingWrites::default();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        relm4::spawn_local({
+            let settings = settings.clone();
+            let pending = pending.clone();
+            async move {
+                while receiver.recv().await.is_some() {
+                    // Keep collecting until the changes stop coming.
+                    loop {
+                        match glib::future_with_timeout(WRITE_DEBOUNCE, receiver.recv()).await {
+                            Ok(Some(())) => continue,
+                            Ok(None) | Err(_) => break,
+                        }
+                    }
+                    write_pending(&settings, &pending);
+                }
+            }
+        });
+        Self {
+            settings,
+            device: None,
+            pending,
+            sender,
+        }
+    }
+
+    /// A writer for the settings of the device at `address`, batched with this one's.
+    pub fn for_device(&self, address: &str) -> Self {
+        Self {
+            device: Some(address.to_string()),
+            ..self.clone()
+        }
+    }
+
+    pub fn set_string(&self, key: &'static str, value: &str) {
+        self.set(key, SettingValue::String(value.to_string()));
+    }
+
+    pub fn set_boolean(&self, key: &'static str, value: bool) {
+        self.set(key, SettingValue::Boolean(value));
+    }
+
+    pub fn set_int(&self, key: &'static str, value: i32) {
+        self.set(key, SettingValue::Int(value));
+    }
+
+    pub fn set_int64(&self, key: &'static str, value: i64) {
+        self.set(key, SettingValue::Int64(value));
+    }
+
+    pub fn set_strv(&self, key: &'static str, value: &[String]) {
+        self.set(key, SettingValue::StringArray(value.to_vec()));
+    }
+
+    /// Writes the pending changes right away, e.g. before quitting.
+    pub fn flush(&self) {
+        write_pending(&self.settings, &self.pending);
+    }
+
+    fn set(&self, key: &'static str, value: SettingValue) {
+        self.pending
+            .borrow_mut()
+            .insert((self.device.clone(), key), value);
+        let _ = self.sender.send(());
+    }
+}
+
+fn write_pending(settings: &gtk4::gio::Settings, pending: &PendingWrites) {
+    // Taken out first, as writing emits `changed`, whose handlers may set more keys.
+    let writes: Vec<_> = pending.borrow_mut().drain().collect();
+    let mut device_settings = HashMap::new();
+    for ((device, key), value) in writes {
+        let settings = match &device {
+            Some(address) => &*device_settings
+                .entry(address.clone())
+                .or_insert_with(|| get_device_settings(address)),
+            None => settings,
+        };
+        let result = match value {
+            SettingValue::String(value) => settings.set_string(key, &value),
+            SettingValue::Boolean(value) => settings.set_boolean(key, value),
+            SettingValue::Int(value) => settings.set_int(key, value),
+            SettingValue::Int64(value) => settings.set_int64(key, value),
+            SettingValue::StringArray(value) => settings.set_strv(key, value.as_slice()),
+        };
+        if let Err(e) = result {
+            error!(
+                key,
+                device = device.as_deref(),
+                "Failed to save setting: {}",
+                e
+            );
+        }
     }
 }
@@ -0,0 +1,249 @@
+//! Global shortcuts through the XDG desktop portal, which work while the window isn't focused.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use futures::channel::oneshot;
+use gtk4::{
+    gio,
+    glib::{self, Variant, VariantTy, prelude::ToVariant, variant::ObjectPath},
+};
+use tracing::{debug, warn};
+
+const PORTAL_BUS: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+const SESSION_INTERFACE: &str = "org.freedesktop.portal.Session";
+
+/// Id of the shortcut toggling noise reduction, as registered with the portal.
+const TOGGLE_ANC_ID: &str = "toggle-anc";
+
+/// Keys the noise reduction toggle can be bound to, by their name in the settings and title.
+pub const SHORTCUT_TRIGGERS: &[(&str, &str)] = &[
+    ("", "None"),
+    ("XF86AudioMicMute", "Microphone Mute Key"),
+    ("XF86Launch1", "Launch1 Key"),
+];
+
+/// Makes the request and session tokens unique within the app.
+static NEXT_TOKEN: AtomicU32 = AtomicU32::new(0);
+
+/// What came out of asking the portal for a shortcut.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindOutcome {
+    /// No key was picked.
+    Off,
+    /// Bound to the key the desktop describes like this.
+    Bound(String),
+    /// The desktop or the user left the shortcut without a key, usually as it's already taken.
+    Unassigned,
+    /// The portal isn't there or refused, with the reason.
+    Failed(String),
+}
+
+/// A portal session holding the shortcuts, which are released when it's dropped.
+#[derive(Debug)]
+pub struct GlobalShortcuts {
+    connection: gio::DBusConnection,
+    session: String,
+    /// Unsubscribes from the shortcut's activations when dropped.
+    activated: Option<gio::SignalSubscription>,
+}
+
+impl Drop for GlobalShortcuts {
+    fn drop(&mut self) {
+        // Before closing the session, so no activation comes in between.
+        drop(self.activated.take());
+        self.connection.call(
+            Some(PORTAL_BUS),
+            &self.session,
+            SESSION_INTERFACE,
+            "Close",
+            None,
+            None,
+            gio::DBusCallFlags::NONE,
+            -1,
+            gio::Cancellable::NONE,
+            |_| {},
+        );
+    }
+}
+
+/// Binds the noise reduction toggle to `trigger`, calling `on_activated` when it's pressed.
+///
+/// The shortcut stays bound for as long as the returned session is kept.
+pub async fn bind_toggle_anc(
+    trigger: &str,
+    on_activated: impl Fn() + 'static,
+) -> (Option<GlobalShortcuts>, BindOutcome) {
+    if trigger.is_empty() {
+        return (None, BindOutcome::Off);
+    }
+    let Some(connection) = relm4::main_application().dbus_connection() else {
+        return (None, BindOutcome::Failed("No D-Bus connection".to_string()));
+    };
+
+    let session = match create_session(&connection).await {
+        Ok(session) => session,
+        Err(e) => {
+            warn!("Failed to create a global shortcuts session: {}", e);
+            return (None, BindOutcome::Failed(e));
+        }
+    };
+    let mut shortcuts = GlobalShortcuts {
+        connection: connection.clone(),
+        session: session.clone(),
+        activated: None,
+    };
+
+    let activated = connection.subscribe_to_signal(
+        Some(PORTAL_BUS),
+        Some(SHORTCUTS_INTERFACE),
+        Some("Activated"),
+        Some(PORTAL_PATH),
+        None,
+        gio::DBusSignalFlags::NONE,
+        move |signal| {
+            let parameters = signal.parameters;
+            let is_ours = parameters.child_value(0).str() == Some(session.as_str())
+                && parameters.child_value(1).str() == Some(TOGGLE_ANC_ID);
+            if is_ours {
+                on_activated();
+            }
+        },
+    );
+    shortcuts.activated = Some(activated);
+
+    let outcome = match bind_shortcut(&connection, &shortcuts.session, trigger).await {
+        Ok(Some(description)) => BindOutcome::Bound(description),
+        Ok(None) => BindOutcome::Unassigned,
+        Err(e) => {
+            warn!("Failed to bind the global shortcut: {}", e);
+            return (None, BindOutcome::Failed(e));
+        }
+    };
+    debug!(?outcome, trigger, "Global shortcut bound");
+    (Some(shortcuts), outcome)
+}
+
+async fn create_session(connection: &gio::DBusConnection) -> Result<String, String> {
+    let session_token = next_token();
+    let results = request(connection, "CreateSession", |handle_token| {
+        let options = glib::VariantDict::new(None);
+        options.insert_value("handle_token", &handle_token.to_variant());
+        options.insert_value("session_handle_token", &session_token.to_variant());
+        Variant::tuple_from_iter([options.end()])
+    })
+    .await?;
+
+    results
+        .lookup_value("session_handle", None)
+        .and_then(|handle| handle.str().map(str::to_string))
+        .ok_or_else(|| "The portal returned no session".to_string())
+}
+
+/// Returns how the desktop describes the key the shortcut ended up on, if it got one.
+async fn bind_shortcut(
+    connection: &gio::DBusConnection,
+    session: &str,
+    trigger: &str,
+) -> Result<Option<String>, String> {
+    let session_path = ObjectPath::try_from(session.to_string()).map_err(|e| e.to_string())?;
+    let results = request(connection, "BindShortcuts", |handle_token| {
+        let shortcut = glib::VariantDict::new(None);
+        shortcut.insert_value("description", &"Toggle noise reduction".to_variant());
+        shortcut.insert_value("preferred_trigger", &trigger.to_variant());
+        let shortcuts = Variant::array_from_iter_with_type(
+            VariantTy::new("(sa{sv})").unwrap(),
+            [Variant::tuple_from_iter([
+                TOGGLE_ANC_ID.to_variant(),
+                shortcut.end(),
+            ])],
+        );
+        let options = glib::VariantDict::new(None);
+        options.insert_value("handle_token", &handle_token.to_variant());
+        Variant::tuple_from_iter([
+            session_path.to_variant(),
+            shortcuts,
+            "".to_variant(),
+            options.end(),
+        ])
+    })
+    .await?;
+
+    let Some(shortcuts) = results.lookup_value("shortcuts", None) else {
+        return Ok(None);
+    };
+    let description = shortcuts
+        .iter()
+        .find(|shortcut| shortcut.child_value(0).str() == Some(TOGGLE_ANC_ID))
+        .and_then(|shortcut| {
+            glib::VariantDict::new(Some(&shortcut.child_value(1)))
+                .lookup_value("trigger_description", None)
+                .and_then(|description| description.str().map(str::to_string))
+        })
+        .filter(|description| !description.is_empty());
+    Ok(description)
+}
+
+/// Calls a portal method and waits for the response to the request it starts.
+///
+/// `arguments` gets the token the request is made with.
+async fn request(
+    connection: &gio::DBusConnection,
+    method: &str,
+    arguments: impl FnOnce(&str) -> Variant,
+) -> Result<glib::VariantDict, String> {
+    let token = next_token();
+    let sender = connection
+        .unique_name()
+        .ok_or_else(|| "The D-Bus connection has no name".to_string())?;
+    let sender = sender.trim_start_matches(':').replace('.', "_");
+    let request_path = format!("{PORTAL_PATH}/request/{sender}/{token}");
+
+    // Subscribed before calling, as the response may come before the call returns.
+    let (response_sender, response) = oneshot::channel();
+    let mut response_sender = Some(response_sender);
+    let subscription = connection.subscribe_to_signal(
+        Some(PORTAL_BUS),
+        Some(REQUEST_INTERFACE),
+        Some("Response"),
+        Some(&request_path),
+        None,
+        gio::DBusSignalFlags::NONE,
+        move |signal| {
+            if let Some(response_sender) = response_sender.take() {
+                let _ = response_sender.send(signal.parameters.clone());
+            }
+        },
+    );
+
+    let call = connection
+        .call_future(
+            Some(PORTAL_BUS),
+            PORTAL_PATH,
+            SHORTCUTS_INTERFACE,
+            method,
+            Some(&arguments(&token)),
+            None,
+            gio::DBusCallFlags::NONE,
+            -1,
+        )
+        .await;
+    let response = match call {
+        Ok(_) => response.await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    subscription.unsubscribe();
+
+    let response = response?;
+    match response.child_value(0).get::<u32>() {
+        Some(0) => Ok(glib::VariantDict::new(Some(&response.child_value(1)))),
+        Some(1) => Err("Cancelled by the user".to_string()),
+        _ => Err(format!("{method} failed")),
+    }
+}
+
+fn next_token() -> String {
+    format!("galaxy_buds_{}", NEXT_TOKEN.fetch_add(1, Ordering::Relaxed))
+}
@@ -0,0 +1,219 @@
+//! Answering and hanging up through ModemManager, for phones running e.g. Phosh.
+//!
+//! The call controls show while the buds use the hands-free profile, see
+//! `AudioCard::is_in_call`, which is all a desktop has. Calls on a modem only add answer and
+//! hang up, as desktop VoIP apps share no interface to answer or end their calls.
+
+use gtk4::{
+    gio,
+    glib::{Variant, VariantTy, prelude::ToVariant},
+};
+use tracing::debug;
+
+const MODEM_MANAGER_BUS: &str = "org.freedesktop.ModemManager1";
+const MODEM_MANAGER_PATH: &str = "/org/freedesktop/ModemManager1";
+const VOICE_INTERFACE: &str = "org.freedesktop.ModemManager1.Modem.Voice";
+const CALL_INTERFACE: &str = "org.freedesktop.ModemManager1.Call";
+
+/// The part of a call's `MMCallState` that has controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallState {
+    /// Ringing, or waiting while another call is active.
+    Incoming,
+    /// Dialing or ringing on the other end.
+    Outgoing,
+    Active,
+    Held,
+}
+
+impl CallState {
+    /// Maps `MMCallState`, `None` for unknown and terminated calls.
+    fn from_modem_manager(state: i32) -> Option<Self> {
+        match state {
+            1 | 2 => Some(Self::Outgoing),
+            3 | 6 => Some(Self::Incoming),
+            4 => Some(Self::Active),
+            5 => Some(Self::Held),
+            _ => None,
+        }
+    }
+
+    /// Orders calls by how much they need attention, the highest first.
+    fn urgency(self) -> u8 {
+        match self {
+            Self::Incoming => 3,
+            Self::Active => 2,
+            Self::Outgoing => 1,
+            Self::Held => 0,
+        }
+    }
+}
+
+/// A call on one of the modems ModemManager manages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModemCall {
+    path: String,
+    pub state: CallState,
+}
+
+impl ModemCall {
+    /// Finds the call that needs attention the most, an incoming one first.
+    ///
+    /// Returns `None` without a call, and also when ModemManager isn't running, as it's only
+    /// there on phones.
+    pub async fn find() -> Option<Self> {
+        let connection = gio::bus_get_future(gio::BusType::System).await.ok()?;
+        let objects = call(
+            &connection,
+            MODEM_MANAGER_PATH,
+            "org.freedesktop.DBus.ObjectManager",
+            "GetManagedObjects",
+            None,
+        )
+        .await
+        .inspect_err(|e| debug!("ModemManager unavailable: {}", e))
+        .ok()?;
+
+        let mut found: Option<Self> = None;
+        for path in call_paths(&objects) {
+            let Some(state) = call_state(&connection, &path).await else {
+                continue;
+            };
+            if found.as_ref().is_none_or(|found| state.urgency() > found.state.urgency()) {
+                found = Some(Self { path, state });
+            }
+        }
+        found
+    }
+
+    pub async fn accept(&self) -> Result<(), String> {
+        self.call_method("Accept").await
+    }
+
+    /// Hangs up, or declines the call while it's incoming.
+    pub async fn hang_up(&self) -> Result<(), String> {
+        self.call_method("Hangup").await
+    }
+
+    async fn call_method(&self, method: &str) -> Result<(), String> {
+        let connection = gio::bus_get_future(gio::BusType::System)
+            .await
+            .map_err(|e| e.to_string())?;
+        call(&connection, &self.path, CALL_INTERFACE, method, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn state_text(&self) -> &'static str {
+        match self.state {
+            CallState::Incoming => "Incoming call",
+            CallState::Outgoing => "Calling…",
+            CallState::Active => "In a call",
+            CallState::Held => "Call on hold",
+        }
+    }
+}
+
+/// Lists the calls of every modem with voice support, from ModemManager's managed objects.
+fn call_paths(objects: &Variant) -> Vec<String> {
+    // a{oa{sa{sv}}}: the interfaces of each object, with their properties.
+    objects
+        .child_value(0)
+        .iter()
+        .filter_map(|object| object.child_value(1).lookup_value(VOICE_INTERFACE, None))
+        .filter_map(|voice| voice.lookup_value("Calls", Some(VariantTy::OBJECT_PATH_ARRAY)))
+        .flat_map(|calls| calls.iter().filter_map(|path| path.str().map(str::to_string)))
+        .collect()
+}
+
+async fn call_state(connection: &gio::DBusConnection, path: &str) -> Option<CallState> {
+    let arguments = (CALL_INTERFACE, "State").to_variant();
+    let reply = call(
+        connection,
+        path,
+        "org.freedesktop.DBus.Properties",
+        "Get",
+        Some(arguments),
+    )
+    .await
+    .ok()?;
+    let state = reply.child_value(0).as_variant()?.get::<i32>()?;
+    CallState::from_modem_manager(state)
+}
+
+/// Calls a ModemManager method, without starting ModemManager if it isn't running.
+async fn call(
+    connection: &gio::DBusConnection,
+    path: &str,
+    interface: &str,
+    method: &str,
+    arguments: Option<Variant>,
+) -> Result<Variant, gtk4::glib::Error> {
+    connection
+        .call_future(
+            Some(MODEM_MANAGER_BUS),
+            path,
+            interface,
+            method,
+            arguments.as_ref(),
+            None,
+            gio::DBusCallFlags::NO_AUTO_START,
+            -1,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use gtk4::glib::{VariantDict, variant::ObjectPath};
+
+    use super::*;
+
+    fn object_path(path: &str) -> Variant {
+        ObjectPath::try_from(path).unwrap().to_variant()
+    }
+
+    /// Builds the reply of `GetManagedObjects` from the interfaces of each object.
+    fn managed_objects(objects: &[(&str, &[(&str, Variant)])]) -> Variant {
+        let objects = objects.iter().map(|(path, interfaces)| {
+            let interfaces = interfaces.iter().map(|(name, properties)| {
+                Variant::from_dict_entry(&name.to_variant(), properties)
+            });
+            let interfaces =
+                Variant::array_from_iter_with_type(VariantTy::new("{sa{sv}}").unwrap(), interfaces);
+            Variant::from_dict_entry(&object_path(path), &interfaces)
+        });
+        let objects =
+            Variant::array_from_iter_with_type(VariantTy::new("{oa{sa{sv}}}").unwrap(), objects);
+        Variant::tuple_from_iter([objects])
+    }
+
+    #[test]
+    fn maps_modem_manager_call_states() {
+        assert_eq!(CallState::from_modem_manager(0), None);
+        assert_eq!(CallState::from_modem_manager(2), Some(CallState::Outgoing));
+        assert_eq!(CallState::from_modem_manager(3), Some(CallState::Incoming));
+        assert_eq!(CallState::from_modem_manager(4), Some(CallState::Active));
+        assert_eq!(CallState::from_modem_manager(6), Some(CallState::Incoming));
+        assert_eq!(CallState::from_modem_manager(7), None);
+    }
+
+    #[test]
+    fn lists_calls_of_modems_with_voice_support() {
+        let voice = VariantDict::new(None);
+        let calls = vec![ObjectPath::try_from("/org/freedesktop/ModemManager1/Call/1").unwrap()];
+        voice.insert_value("Calls", &calls.to_variant());
+        let objects = managed_objects(&[
+            ("/org/freedesktop/ModemManager1/Modem/0", &[(VOICE_INTERFACE, voice.end())]),
+            ("/org/freedesktop/ModemManager1/SIM/0", &[]),
+        ]);
+
+        assert_eq!(call_paths(&objects), ["/org/freedesktop/ModemManager1/Call/1"]);
+    }
+
+    #[test]
+    fn lists_no_calls_without_modems() {
+        assert!(call_paths(&managed_objects(&[])).is_empty());
+    }
+}
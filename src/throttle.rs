@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+
+/// Lets something through at most once per interval, counting what it held back.
+///
+/// Meant for errors shown to the user, which can come in every few seconds while a
+/// connection keeps dropping.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    interval: Duration,
+    last_admitted: Option<Instant>,
+    /// Calls held back since the last one let through.
+    suppressed: u32,
+}
+
+impl Throttle {
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_admitted: None,
+            suppressed: 0,
+        }
+    }
+
+    /// Whether to go ahead now, with how many calls were held back since the last time.
+    ///
+    /// Returns `None` while within the interval, counting the call as held back.
+    pub fn admit(&mut self) -> Option<u32> {
+        let now = Instant::now();
+        if self
+            .last_admitted
+            .is_some_and(|last_admitted| now.duration_since(last_admitted) < self.interval)
+        {
+            self.suppressed += 1;
+            return None;
+        }
+        self.last_admitted = Some(now);
+        Some(std::mem::take(&mut self.suppressed))
+    }
+}
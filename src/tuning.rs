@@ -0,0 +1,81 @@
+//! Connection parameters that can be overridden with environment variables, to experiment with
+//! clones and OEM devices that don't quite behave like Galaxy Buds.
+//!
+//! - `GALAXY_BUDS_SPP_UUID`: UUID of the SPP service the buds are found and connected by,
+//!   instead of the ones in `consts::KNOWN_SPP_SERVICES`.
+//! - `GALAXY_BUDS_READ_BUFFER_SIZE`: how many bytes to read from the device at once.
+//! - `GALAXY_BUDS_HANDSHAKE_TIMEOUT_MS`: how long to wait for the device to identify itself.
+//!
+//! They're read once at startup; invalid values are logged and the defaults used instead.
+
+use std::{sync::OnceLock, time::Duration};
+
+use bluer::Uuid;
+use tracing::{info, warn};
+
+const SPP_UUID_VAR: &str = "GALAXY_BUDS_SPP_UUID";
+const READ_BUFFER_SIZE_VAR: &str = "GALAXY_BUDS_READ_BUFFER_SIZE";
+const HANDSHAKE_TIMEOUT_VAR: &str = "GALAXY_BUDS_HANDSHAKE_TIMEOUT_MS";
+
+const DEFAULT_READ_BUFFER_SIZE: usize = 2048;
+/// How long to wait for the device to answer the manager info request before assuming it
+/// doesn't speak the Galaxy Buds protocol.
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+static TUNING: OnceLock<Tuning> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    /// Replaces the known SPP UUIDs when set.
+    pub spp_uuid: Option<Uuid>,
+    pub read_buffer_size: usize,
+    pub handshake_timeout: Duration,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            spp_uuid: None,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+        }
+    }
+}
+
+impl Tuning {
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        let tuning = Self {
+            spp_uuid: parse_var(SPP_UUID_VAR, |value| value.parse().ok()),
+            read_buffer_size: parse_var(READ_BUFFER_SIZE_VAR, |value| {
+                value.parse().ok().filter(|size| *size > 0)
+            })
+            .unwrap_or(defaults.read_buffer_size),
+            handshake_timeout: parse_var(HANDSHAKE_TIMEOUT_VAR, |value| {
+                value.parse().ok().map(Duration::from_millis)
+            })
+            .unwrap_or(defaults.handshake_timeout),
+        };
+        if tuning != defaults {
+            info!(?tuning, "Using connection parameters from the environment");
+        }
+        tuning
+    }
+}
+
+/// Reads the environment variables, which only happens the first time.
+///
+/// Called at startup, so invalid values are logged right away rather than on first connect.
+pub fn get() -> &'static Tuning {
+    TUNING.get_or_init(Tuning::from_env)
+}
+
+/// The value of `name` as parsed by `parse`, or `None` if it's unset or invalid.
+fn parse_var<T>(name: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    let parsed = parse(value.trim());
+    if parsed.is_none() {
+        warn!(name, value, "Ignoring an invalid environment variable");
+    }
+    parsed
+}
@@ -0,0 +1,108 @@
+//! Saves and restores the state of the main window across launches.
+
+use gtk4::{
+    gdk::prelude::{DisplayExt, MonitorExt},
+    gio::prelude::{SettingsExt, SettingsExtManual},
+    prelude::{CastNone, GtkWindowExt, ListModelExt, NativeExt},
+};
+use tracing::debug;
+
+use crate::{
+    app::page_manage::PageId,
+    consts::{LAST_SUBPAGE_KEY, WINDOW_MAXIMIZED_KEY, WINDOW_MONITOR_KEY},
+    settings::SettingsWriter,
+};
+
+/// Binds the window size and maximized state to `settings`, and remembers its monitor on close.
+///
+/// Must be called before the window is presented, so the saved state applies to it.
+pub fn bind(
+    window: &adw::ApplicationWindow,
+    settings: &gtk4::gio::Settings,
+    settings_writer: &SettingsWriter,
+) {
+    settings
+        .bind("window-width", window, "default-width")
+        .flags(gtk4::gio::SettingsBindFlags::DEFAULT)
+        .build();
+    settings
+        .bind("window-height", window, "default-height")
+        .flags(gtk4::gio::SettingsBindFlags::DEFAULT)
+        .build();
+    settings
+        .bind(WINDOW_MAXIMIZED_KEY, window, "maximized")
+        .flags(gtk4::gio::SettingsBindFlags::DEFAULT)
+        .build();
+
+    fit_to_monitor(window, settings);
+
+    window.connect_close_request({
+        let settings_writer = settings_writer.clone();
+        move |window| {
+            let connector = window
+                .surface()
+                .and_then(|surface| surface.display().monitor_at_surface(&surface))
+                .and_then(|monitor| monitor.connector())
+                .unwrap_or_default();
+            // Written with the rest when the app shuts down, if it quits with the window.
+            settings_writer.set_string(WINDOW_MONITOR_KEY, &connector);
+            gtk4::glib::Propagation::Proceed
+        }
+    });
+}
+
+/// Shrinks the saved size to fit the first monitor when the one the window was last on is gone.
+///
+/// Windows can't pick their monitor on every platform, but they can at least avoid
+/// opening larger than the screen, e.g. after undocking a laptop.
+fn fit_to_monitor(window: &adw::ApplicationWindow, settings: &gtk4::gio::Settings) {
+    let saved_connector = settings.string(WINDOW_MONITOR_KEY);
+    let Some(display) = gtk4::gdk::Display::default() else {
+        return;
+    };
+
+    let monitors = display.monitors();
+    let monitors: Vec<gtk4::gdk::Monitor> = (0..monitors.n_items())
+        .filter_map(|i| monitors.item(i).and_downcast())
+        .collect();
+    if saved_connector.is_empty()
+        || monitors
+            .iter()
+            .any(|monitor| monitor.connector().as_deref() == Some(saved_connector.as_str()))
+    {
+        return;
+    }
+
+    if let Some(geometry) = monitors.first().map(|monitor| monitor.geometry()) {
+        debug!(monitor = %saved_connector, "Last monitor is gone, fitting the window to another one");
+        let (width, height) = window.default_size();
+        window.set_default_size(width.min(geometry.width()), height.min(geometry.height()));
+    }
+}
+
+/// The device subpage that was open when the app was last closed.
+pub fn saved_subpage(settings: &gtk4::gio::Settings) -> Option<PageId> {
+    parse_subpage(settings.string(LAST_SUBPAGE_KEY).as_str())
+}
+
+/// Remembers the open device subpage, or that none is open.
+pub fn save_subpage(settings_writer: &SettingsWriter, page_id: Option<PageId>) {
+    settings_writer.set_string(LAST_SUBPAGE_KEY, subpage_name(page_id));
+}
+
+/// Names a device subpage the way it's saved, empty for none.
+pub fn subpage_name(page_id: Option<PageId>) -> &'static str {
+    match page_id {
+        Some(PageId::Noise) => "noise",
+        Some(PageId::Details) => "details",
+        None => "",
+    }
+}
+
+pub fn parse_subpage(name: &str) -> Option<PageId> {
+    match name {
+        "noise" => Some(PageId::Noise),
+        "details" => Some(PageId::Details),
+        _ => None,
+    }
+}